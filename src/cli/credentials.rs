@@ -0,0 +1,201 @@
+/*
+   The GPLv3 License (GPLv3)
+
+   Copyright (c) 2022 Jacob Skoog
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU General Public License as published by
+   the Free Software Foundation, either version 3 of the License, or
+   (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU General Public License for more details.
+
+   You should have received a copy of the GNU General Public License
+   along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::path::PathBuf;
+
+use clap::Args;
+use twitter_request::errors::TwitterBuilderError;
+use twitter_request::twitter::access_level::AccessLevel;
+use twitter_request::twitter::config::{Config, Profile, Secret};
+use twitter_request::twitter::query_params::main_options::MainOptions;
+use twitter_request::{AuthenticationData, Endpoint};
+
+use super::filters::FieldArgs;
+
+/// Where the credentials come from. A profile of the config file is read first, the
+/// environment variables override it, and the flags override both. The environment is
+/// first loaded from `--env-file`, or `.env` if there is one.
+#[derive(Args, Debug, Default)]
+pub struct Credentials {
+    /// The config file of profiles [env: TWITTER_REQUEST_CONFIG, default:
+    /// ~/.config/twitter_request/config.toml]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// The profile of the config file to use [env: TWITTER_PROFILE]
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// App only bearer token [env: BEARER_TOKEN]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    bearer_token: Option<Secret>,
+    /// OAuth 1.0a consumer key [env: CONSUMER_KEY]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    consumer_key: Option<Secret>,
+    /// OAuth 1.0a consumer secret [env: CONSUMER_SECRET]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    consumer_secret: Option<Secret>,
+    /// OAuth 1.0a access token [env: ACCESS_TOKEN]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    access_token: Option<Secret>,
+    /// OAuth 1.0a access token secret [env: ACCESS_TOKEN_SECRET]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    access_token_secret: Option<Secret>,
+    /// OAuth 2.0 user access token [env: OAUTH2_USER_TOKEN]
+    #[arg(long, global = true, value_parser = parse_secret)]
+    oauth2_token: Option<Secret>,
+    /// The access tier of the app, to catch requests it can't make before sending them
+    /// [env: TWITTER_ACCESS_LEVEL]
+    #[arg(long, global = true, value_parser = parse_access_level)]
+    access_level: Option<AccessLevel>,
+    /// A file of KEY=value lines to load the environment variables from
+    #[arg(long, global = true)]
+    env_file: Option<PathBuf>,
+}
+
+impl Credentials {
+    /// Loads the environment and the config file, and returns the profile with the
+    /// flags applied to it
+    pub fn resolve(&self) -> Result<Session, String> {
+        match &self.env_file {
+            Some(path) => dotenv::from_path(path)
+                .map_err(|e| format!("Could not load {}: {}", path.display(), e))?,
+            None => {
+                dotenv::dotenv().ok();
+            }
+        }
+        let config = match &self.config {
+            Some(path) => Config::load(path),
+            None => Config::load_default(),
+        }
+        .map_err(|e| e.to_string())?;
+        let mut profile = config
+            .profile(self.profile.as_deref())
+            .map_err(|e| e.to_string())?;
+        let flags = [
+            (&self.bearer_token, &mut profile.bearer_token),
+            (&self.consumer_key, &mut profile.consumer_key),
+            (&self.consumer_secret, &mut profile.consumer_secret),
+            (&self.access_token, &mut profile.access_token),
+            (&self.access_token_secret, &mut profile.access_token_secret),
+            (&self.oauth2_token, &mut profile.oauth2_user_token),
+        ];
+        for (flag, secret) in flags {
+            if let Some(value) = flag {
+                *secret = Some(value.clone());
+            }
+        }
+        if let Some(access_level) = self.access_level {
+            profile.access_level = Some(access_level);
+        }
+        Ok(Session { profile })
+    }
+}
+
+/// The profile the command runs with
+pub struct Session {
+    pub profile: Profile,
+}
+
+impl Session {
+    /// App only authentication, for endpoints that read public data
+    pub fn app(&self) -> Result<AuthenticationData, String> {
+        self.profile.app_auth().ok_or_else(|| {
+            String::from("Give a bearer token with --bearer-token, BEARER_TOKEN or a profile")
+        })
+    }
+
+    /// OAuth 1.0a user context authentication if all four of its credentials are
+    /// given, or else an OAuth 2.0 user token
+    pub fn user(&self) -> Result<AuthenticationData, String> {
+        self.profile.user_auth().ok_or_else(|| {
+            String::from(
+                "Give OAuth 1.0a credentials with --consumer-key, --consumer-secret, \
+                 --access-token and --access-token-secret, or an OAuth 2.0 user token \
+                 with --oauth2-token",
+            )
+        })
+    }
+
+    /// OAuth 1.0a user context authentication, which some endpoints such as media upload require
+    pub fn oauth1(&self) -> Option<AuthenticationData> {
+        self.profile.oauth1_auth()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.profile.base_url
+    }
+
+    /// The field and expansion flags, or else the profile's defaults that the endpoint accepts
+    pub fn field_options(&self, fields: &FieldArgs, endpoint: &Endpoint) -> Vec<MainOptions> {
+        let options = fields.options();
+        if !options.is_empty() {
+            return options;
+        }
+        self.profile
+            .default_options()
+            .into_iter()
+            .filter(|option| endpoint.supports_option(option))
+            .collect()
+    }
+}
+
+fn parse_access_level(value: &str) -> Result<AccessLevel, TwitterBuilderError> {
+    AccessLevel::try_from(value)
+}
+
+/// Keeps the credentials given as flags in a `Secret`, so they aren't printed by `Debug`
+fn parse_secret(value: &str) -> Result<Secret, String> {
+    Ok(Secret::new(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser, Debug)]
+    struct Cli {
+        #[command(flatten)]
+        credentials: Credentials,
+    }
+
+    #[test]
+    fn debug_redacts_secrets_test() {
+        let cli = Cli::try_parse_from([
+            "twitter_request",
+            "--bearer-token=bearer-secret",
+            "--consumer-key=key-secret",
+            "--consumer-secret=consumer-secret",
+            "--access-token=token-secret",
+            "--access-token-secret=access-secret",
+            "--oauth2-token=oauth2-secret",
+            "--profile=work",
+        ])
+        .unwrap();
+        let debug = format!("{:?}", cli);
+        assert!(!debug.contains("-secret"), "{}", debug);
+        assert!(debug.contains("work"));
+        assert_eq!(
+            cli.credentials
+                .oauth2_token
+                .map(|token| token.expose().to_string()),
+            Some(String::from("oauth2-secret"))
+        );
+    }
+}
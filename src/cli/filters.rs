@@ -0,0 +1,370 @@
+/*
+   The GPLv3 License (GPLv3)
+
+   Copyright (c) 2022 Jacob Skoog
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU General Public License as published by
+   the Free Software Foundation, either version 3 of the License, or
+   (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU General Public License for more details.
+
+   You should have received a copy of the GNU General Public License
+   along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use chrono::{DateTime, Utc};
+use clap::{Args, ValueEnum};
+use twitter_request::errors::TwitterBuilderError;
+use twitter_request::twitter::query_filters::group::GroupList;
+use twitter_request::twitter::query_filters::ids::Id;
+use twitter_request::twitter::query_params::expansions::Expansions;
+use twitter_request::twitter::query_params::main_options::{Exclude, MainOptions, Order};
+use twitter_request::twitter::twitter_objects::{tweet::TweetField, user::UserField};
+use twitter_request::{Exact, Filter, Is};
+
+/// Flags that build the query of a search, count or stream rule
+#[derive(Args, Debug, Default)]
+pub struct FilterArgs {
+    /// Keywords the tweets must contain
+    pub keywords: Vec<String>,
+    /// An exact phrase the tweets must contain
+    #[arg(long)]
+    pub phrase: Vec<String>,
+    /// A keyword the tweets must not contain
+    #[arg(long)]
+    pub without: Vec<String>,
+    /// Tweets posted by the user
+    #[arg(long)]
+    pub from: Vec<String>,
+    /// Tweets replying to the user
+    #[arg(long)]
+    pub to: Vec<String>,
+    /// Retweets of the user's tweets
+    #[arg(long)]
+    pub retweets_of: Vec<String>,
+    /// Tweets with the context annotation, as domain_id.entity_id
+    #[arg(long)]
+    pub context: Vec<String>,
+    /// Tweets with the entity annotation
+    #[arg(long)]
+    pub entity: Vec<String>,
+    /// Tweets linking to the url
+    #[arg(long)]
+    pub url: Vec<String>,
+    /// Tweets in the language, as a BCP 47 code
+    #[arg(long)]
+    pub lang: Option<String>,
+    /// Tweets tagged with the place
+    #[arg(long)]
+    pub place: Option<String>,
+    /// Tweets tagged with a place in the country, as an ISO alpha-2 code
+    #[arg(long)]
+    pub place_country: Option<String>,
+    /// Tweets in the conversation
+    #[arg(long)]
+    pub conversation_id: Option<String>,
+    /// Tweets that are of the kind
+    #[arg(long, value_enum)]
+    pub is: Vec<IsKind>,
+    /// Tweets that are not of the kind
+    #[arg(long, value_enum)]
+    pub is_not: Vec<IsKind>,
+    /// Tweets that have the kind of content
+    #[arg(long, value_enum)]
+    pub has: Vec<HasKind>,
+    /// Tweets that do not have the kind of content
+    #[arg(long, value_enum)]
+    pub has_not: Vec<HasKind>,
+    /// Match tweets matching any of the filters instead of all of them
+    #[arg(long)]
+    pub any: bool,
+}
+
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum IsKind {
+    Retweet,
+    Reply,
+    Quote,
+    Verified,
+    Nullcast,
+}
+
+impl IsKind {
+    fn filter(self, is: Is) -> Filter {
+        match self {
+            IsKind::Retweet => Filter::IsRetweet(is),
+            IsKind::Reply => Filter::IsReply(is),
+            IsKind::Quote => Filter::IsQuote(is),
+            IsKind::Verified => Filter::IsVerified(is),
+            IsKind::Nullcast => Filter::IsNullcast(is),
+        }
+    }
+}
+
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HasKind {
+    Hashtags,
+    Cashtags,
+    Links,
+    Mentions,
+    Media,
+    Images,
+    Videos,
+    Geo,
+}
+
+impl HasKind {
+    fn filter(self, is: Is) -> Filter {
+        match self {
+            HasKind::Hashtags => Filter::HasHashtags(is),
+            HasKind::Cashtags => Filter::HasCashtags(is),
+            HasKind::Links => Filter::HasLinks(is),
+            HasKind::Mentions => Filter::HasMentions(is),
+            HasKind::Media => Filter::HasMedia(is),
+            HasKind::Images => Filter::HasImages(is),
+            HasKind::Videos => Filter::HasVideos(is),
+            HasKind::Geo => Filter::HasGeo(is),
+        }
+    }
+}
+
+impl FilterArgs {
+    /// Gets the filters of the flags
+    pub fn filters(&self) -> Vec<Filter> {
+        let mut filters = Vec::new();
+        let strings = |values: &[String], filter: fn(String, Is) -> Filter| {
+            values
+                .iter()
+                .map(move |value| filter(value.clone(), Is::Is))
+                .collect::<Vec<Filter>>()
+        };
+        filters.extend(
+            self.keywords
+                .iter()
+                .map(|k| Filter::Keyword(k.clone(), Exact::Not, Is::Is)),
+        );
+        filters.extend(
+            self.phrase
+                .iter()
+                .map(|p| Filter::Keyword(p.clone(), Exact::Is, Is::Is)),
+        );
+        filters.extend(
+            self.without
+                .iter()
+                .map(|k| Filter::Keyword(k.clone(), Exact::Not, Is::Not)),
+        );
+        filters.extend(strings(&self.from, Filter::From));
+        filters.extend(strings(&self.to, Filter::To));
+        filters.extend(strings(&self.retweets_of, Filter::RetweetsOf));
+        filters.extend(strings(&self.context, Filter::Context));
+        filters.extend(strings(&self.entity, Filter::Entity));
+        filters.extend(strings(&self.url, Filter::Url));
+        filters.extend(strings(self.lang.as_slice(), Filter::LangLang));
+        filters.extend(strings(self.place.as_slice(), Filter::LocPlace));
+        filters.extend(strings(
+            self.place_country.as_slice(),
+            Filter::LocPlaceCountry,
+        ));
+        filters.extend(strings(
+            self.conversation_id.as_slice(),
+            Filter::ConvConversationId,
+        ));
+        filters.extend(self.is.iter().map(|kind| kind.filter(Is::Is)));
+        filters.extend(self.is_not.iter().map(|kind| kind.filter(Is::Not)));
+        filters.extend(self.has.iter().map(|kind| kind.filter(Is::Is)));
+        filters.extend(self.has_not.iter().map(|kind| kind.filter(Is::Not)));
+        filters
+    }
+
+    /// Gets the query of the flags, None if no filter was given
+    pub fn group_list(&self) -> Option<GroupList> {
+        let mut filters = self.filters().into_iter();
+        let mut group_list = GroupList::new(filters.next()?);
+        for filter in filters {
+            if self.any {
+                group_list.push_filter_or(filter);
+            } else {
+                group_list.push_filter_and(filter);
+            }
+        }
+        Some(group_list)
+    }
+
+    /// Like `group_list`, but fails when no filter was given
+    pub fn required_group_list(&self) -> Result<GroupList, String> {
+        self.group_list()
+            .ok_or_else(|| String::from("Give at least one keyword or filter flag"))
+    }
+}
+
+/// Flags for the fields and expansions to include in the response
+#[derive(Args, Debug, Default)]
+pub struct FieldArgs {
+    /// Tweet fields to include, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = parse_tweet_field)]
+    pub tweet_fields: Vec<TweetField>,
+    /// User fields to include, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = parse_user_field)]
+    pub user_fields: Vec<UserField>,
+    /// Objects to expand into the includes, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = parse_expansion)]
+    pub expansions: Vec<Expansions>,
+}
+
+impl FieldArgs {
+    pub fn options(&self) -> Vec<MainOptions> {
+        let mut options = Vec::new();
+        if !self.tweet_fields.is_empty() {
+            options.push(MainOptions::TweetFields(self.tweet_fields.clone()));
+        }
+        if !self.user_fields.is_empty() {
+            options.push(MainOptions::UserFields(self.user_fields.clone()));
+        }
+        if !self.expansions.is_empty() {
+            options.push(MainOptions::Expansions(self.expansions.clone()));
+        }
+        options
+    }
+}
+
+/// Flags limiting the time or ids the results are from
+#[derive(Args, Debug, Default)]
+pub struct TimeArgs {
+    /// The oldest time to get tweets from, e.g. 2022-01-01T00:00:00Z
+    #[arg(long)]
+    pub start_time: Option<DateTime<Utc>>,
+    /// The time to get tweets until, e.g. 2022-02-01T00:00:00Z
+    #[arg(long)]
+    pub end_time: Option<DateTime<Utc>>,
+    /// Only get tweets newer than this tweet
+    #[arg(long, value_parser = parse_id)]
+    pub since_id: Option<Id>,
+    /// Only get tweets older than this tweet
+    #[arg(long, value_parser = parse_id)]
+    pub until_id: Option<Id>,
+}
+
+impl TimeArgs {
+    pub fn options(&self) -> Vec<MainOptions> {
+        let mut options = Vec::new();
+        options.extend(self.start_time.map(MainOptions::StartTime));
+        options.extend(self.end_time.map(MainOptions::EndTime));
+        options.extend(self.since_id.map(MainOptions::SinceId));
+        options.extend(self.until_id.map(MainOptions::UntilId));
+        options
+    }
+}
+
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SortOrder {
+    Recency,
+    Relevancy,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Recency => Order::Recency,
+            SortOrder::Relevancy => Order::Relevancy,
+        }
+    }
+}
+
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ExcludeKind {
+    Retweets,
+    Replies,
+}
+
+impl From<ExcludeKind> for Exclude {
+    fn from(exclude: ExcludeKind) -> Self {
+        match exclude {
+            ExcludeKind::Retweets => Exclude::Retweets,
+            ExcludeKind::Replies => Exclude::Replies,
+        }
+    }
+}
+
+pub fn parse_id(value: &str) -> Result<Id, String> {
+    Id::try_from(value).map_err(|_| format!("\"{}\" is not a valid id", value))
+}
+
+fn parse_tweet_field(value: &str) -> Result<TweetField, TwitterBuilderError> {
+    TweetField::try_from(value)
+}
+
+fn parse_user_field(value: &str) -> Result<UserField, TwitterBuilderError> {
+    UserField::try_from(value)
+}
+
+fn parse_expansion(value: &str) -> Result<Expansions, TwitterBuilderError> {
+    Expansions::try_from(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Test {
+        #[command(flatten)]
+        filters: FilterArgs,
+        #[command(flatten)]
+        fields: FieldArgs,
+    }
+
+    fn parse(args: &[&str]) -> Test {
+        Test::try_parse_from(std::iter::once("test").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn group_list_test() {
+        let test = parse(&[
+            "rust",
+            "--phrase",
+            "open source",
+            "--from",
+            "rustlang",
+            "--is-not",
+            "retweet",
+            "--has",
+            "links",
+            "--lang",
+            "en",
+        ]);
+        assert_eq!(
+            test.filters.group_list().unwrap().to_string(),
+            "(rust \"open source\" from:rustlang lang:en -is:retweet has:links)"
+        );
+        let test = parse(&["--from", "a", "--from", "b", "--any"]);
+        assert_eq!(
+            test.filters.group_list().unwrap().to_string(),
+            "(from:a OR from:b)"
+        );
+        assert!(parse(&[]).filters.group_list().is_none());
+    }
+
+    #[test]
+    fn field_args_test() {
+        let test = parse(&[
+            "--tweet-fields",
+            "author_id,created_at",
+            "--expansions",
+            "author_id",
+        ]);
+        assert_eq!(
+            test.fields.options(),
+            vec![
+                MainOptions::TweetFields(vec![TweetField::AuthorId, TweetField::CreatedAt]),
+                MainOptions::Expansions(vec![Expansions::AuthorId]),
+            ]
+        );
+        assert!(Test::try_parse_from(["test", "--tweet-fields", "nonsense"]).is_err());
+    }
+}
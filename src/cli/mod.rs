@@ -0,0 +1,594 @@
+/*
+   The GPLv3 License (GPLv3)
+
+   Copyright (c) 2022 Jacob Skoog
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU General Public License as published by
+   the Free Software Foundation, either version 3 of the License, or
+   (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU General Public License for more details.
+
+   You should have received a copy of the GNU General Public License
+   along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+mod credentials;
+mod filters;
+mod output;
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use futures::StreamExt;
+use http::Method;
+use reqwest::Client;
+use serde_json::Value;
+use twitter_request::errors::TwitterError;
+use twitter_request::twitter::actions::Actions;
+use twitter_request::twitter::batch_lookup::BatchTweetLookup;
+use twitter_request::twitter::hydrate::{read_ids, CsvColumn, Hydrator, IdFormat};
+use twitter_request::twitter::media_upload::{MediaCategory, MediaUpload};
+use twitter_request::twitter::new_tweet::NewTweet;
+use twitter_request::twitter::query_filters::ids::Id;
+use twitter_request::twitter::query_params::main_options::{Granularity, MainOptions};
+use twitter_request::twitter::twitter_objects::response::{Includes, TwitterResponse};
+use twitter_request::twitter::twitter_objects::stream_rule::StreamRule;
+use twitter_request::{AuthenticationData, Endpoint, TwitterRequest};
+
+use credentials::Credentials;
+use filters::{parse_id, ExcludeKind, FieldArgs, FilterArgs, SortOrder, TimeArgs};
+use output::{Format, StreamPrinter};
+
+/// Search, look up, stream and post tweets with the Twitter API v2
+#[derive(Parser, Debug)]
+#[command(name = "twitter_request", version)]
+pub struct Cli {
+    /// How the results are printed
+    #[arg(long, short, value_enum, global = true, default_value_t = Format::Json)]
+    format: Format,
+    #[command(flatten)]
+    credentials: Credentials,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Search the tweets of the last 7 days, or the full archive with --all
+    Search {
+        #[command(flatten)]
+        filters: FilterArgs,
+        #[command(flatten)]
+        time: TimeArgs,
+        #[command(flatten)]
+        fields: FieldArgs,
+        /// Search the full archive instead of the last 7 days
+        #[arg(long)]
+        all: bool,
+        /// The number of tweets per page
+        #[arg(long)]
+        max_results: Option<u16>,
+        /// The number of pages to get
+        #[arg(long, default_value_t = 1)]
+        pages: u32,
+        /// The order the tweets are returned in
+        #[arg(long, value_enum)]
+        sort_order: Option<SortOrder>,
+    },
+    /// Count the tweets matching a query over time
+    Count {
+        #[command(flatten)]
+        filters: FilterArgs,
+        #[command(flatten)]
+        time: TimeArgs,
+        /// Count the full archive instead of the last 7 days
+        #[arg(long)]
+        all: bool,
+        /// The length of the time periods the tweets are counted in
+        #[arg(long, value_enum)]
+        granularity: Option<GranularityArg>,
+    },
+    /// Look up tweets by id
+    Lookup {
+        /// The ids of the tweets
+        #[arg(value_parser = parse_id, required_unless_present = "file")]
+        ids: Vec<Id>,
+        /// A file of ids to look up, one per line, optionally gzipped
+        #[arg(long, conflicts_with = "ids")]
+        file: Option<PathBuf>,
+        /// Read the ids of --file from this CSV column, given as an index or a header name
+        #[arg(long, requires = "file")]
+        csv_column: Option<String>,
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// Look up users by username, or by id with --ids
+    User {
+        /// The usernames, or ids with --ids
+        #[arg(required = true)]
+        users: Vec<String>,
+        /// Look the users up by id instead of username
+        #[arg(long)]
+        ids: bool,
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// Get the tweets a user posted, or the tweets mentioning them
+    Timeline {
+        /// The id of the user
+        #[arg(value_parser = parse_id)]
+        user_id: Id,
+        /// Get the tweets mentioning the user instead
+        #[arg(long)]
+        mentions: bool,
+        /// Leave out the kinds of tweets
+        #[arg(long, value_enum, value_delimiter = ',')]
+        exclude: Vec<ExcludeKind>,
+        #[command(flatten)]
+        time: TimeArgs,
+        #[command(flatten)]
+        fields: FieldArgs,
+        /// The number of tweets per page
+        #[arg(long)]
+        max_results: Option<u16>,
+        /// The number of pages to get
+        #[arg(long, default_value_t = 1)]
+        pages: u32,
+    },
+    /// Print the tweets matching the filtered stream's rules as they are posted
+    Stream {
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// List, add or delete the filtered stream's rules
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Hydrate a file of ids into a JSONL file, resuming where a previous run stopped
+    Hydrate {
+        /// A file of ids, one per line, optionally gzipped
+        input: PathBuf,
+        /// The JSONL file the responses are written to
+        output: PathBuf,
+        /// Read the ids from this CSV column, given as an index or a header name
+        #[arg(long)]
+        csv_column: Option<String>,
+        /// Where the progress is saved, by default the output file with .checkpoint added
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// The number of requests sent at a time
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        concurrency: Option<u32>,
+        #[command(flatten)]
+        fields: FieldArgs,
+    },
+    /// Post a tweet
+    Post {
+        /// The text of the tweet
+        text: String,
+        /// The id of the tweet to reply to
+        #[arg(long, value_parser = parse_id)]
+        reply_to: Option<Id>,
+        /// The id of the tweet to quote
+        #[arg(long, value_parser = parse_id)]
+        quote: Option<Id>,
+        /// An image, gif or video to attach, which needs OAuth 1.0a credentials
+        #[arg(long)]
+        media: Vec<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum RulesCommand {
+    /// List the rules
+    List,
+    /// Add a rule built from the filter flags
+    Add {
+        #[command(flatten)]
+        filters: Box<FilterArgs>,
+        /// A label sent with the tweets that match the rule
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Delete rules by id
+    Delete {
+        #[arg(value_parser = parse_id, required = true)]
+        ids: Vec<Id>,
+    },
+}
+
+#[derive(clap::ValueEnum, PartialEq, Eq, Debug, Clone, Copy)]
+enum GranularityArg {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl From<GranularityArg> for Granularity {
+    fn from(granularity: GranularityArg) -> Self {
+        match granularity {
+            GranularityArg::Minute => Granularity::Minute,
+            GranularityArg::Hour => Granularity::Hour,
+            GranularityArg::Day => Granularity::Day,
+        }
+    }
+}
+
+/// Parses the arguments and runs the command
+pub async fn run() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let session = cli.credentials.resolve()?;
+    let client = Client::new();
+    let format = cli.format;
+    let credentials = &session;
+    match cli.command {
+        Command::Search {
+            filters,
+            time,
+            fields,
+            all,
+            max_results,
+            pages,
+            sort_order,
+        } => {
+            let endpoint = if all {
+                Endpoint::SearchTweetsAll
+            } else {
+                Endpoint::SearchTweetsRecent
+            };
+            let options = credentials.field_options(&fields, &endpoint);
+            let mut builder = TwitterRequest::builder()
+                .set_method(Method::GET)
+                .set_endpoint(endpoint)
+                .add_group_list(filters.required_group_list()?);
+            for option in time.options().into_iter().chain(options) {
+                builder = builder.add_option(option);
+            }
+            if let Some(max_results) = max_results {
+                builder = builder.add_option(MainOptions::MaxResults(max_results.into()));
+            }
+            if let Some(order) = sort_order {
+                builder = builder.add_option(MainOptions::SortOrder(order.into()));
+            }
+            let request = builder
+                .set_authentication(credentials.app()?)
+                .set_base_url(credentials.base_url())
+                .build()?;
+            print_pages(format, pages_of(&client, request, pages).await?)?;
+        }
+        Command::Count {
+            filters,
+            time,
+            all,
+            granularity,
+        } => {
+            let endpoint = if all {
+                Endpoint::LookupTweetsCountAll
+            } else {
+                Endpoint::LookupTweetsCountRecent
+            };
+            let mut builder = TwitterRequest::builder()
+                .set_method(Method::GET)
+                .set_endpoint(endpoint)
+                .add_group_list(filters.required_group_list()?);
+            for option in time.options() {
+                builder = builder.add_option(option);
+            }
+            if let Some(granularity) = granularity {
+                builder = builder.add_option(MainOptions::Granularity(granularity.into()));
+            }
+            let request = builder
+                .set_authentication(credentials.app()?)
+                .set_base_url(credentials.base_url())
+                .build()?;
+            print_pages(format, pages_of(&client, request, u32::MAX).await?)?;
+        }
+        Command::Lookup {
+            ids,
+            file,
+            csv_column,
+            fields,
+        } => {
+            let ids = match file {
+                Some(path) => read_ids(path, &id_format(csv_column))?,
+                None => ids,
+            };
+            let mut lookup = BatchTweetLookup::new().base_url(credentials.base_url());
+            for option in credentials.field_options(&fields, &Endpoint::LookupTweets) {
+                lookup = lookup.add_option(option);
+            }
+            let result = lookup.lookup(&client, &credentials.app()?, ids).await?;
+            output::print_response(format, &to_values(&result.tweets)?, &result.includes)?;
+            for id in &result.not_found {
+                eprintln!("Not found: {}", id);
+            }
+            for id in &result.not_authorized {
+                eprintln!("Not authorized: {}", id);
+            }
+        }
+        Command::User { users, ids, fields } => {
+            let mut builder = TwitterRequest::builder().set_method(Method::GET);
+            if ids {
+                builder = builder.set_endpoint(Endpoint::UsersByIds);
+                for user in &users {
+                    builder = builder.add_id(parse_id(user)?.into());
+                }
+            } else {
+                builder = builder
+                    .set_endpoint(Endpoint::UsersByUsernames)
+                    .add_usernames(users.iter().map(String::as_str));
+            }
+            for option in credentials.field_options(&fields, &Endpoint::UsersByIds) {
+                builder = builder.add_option(option);
+            }
+            let request = builder
+                .set_authentication(credentials.app()?)
+                .set_base_url(credentials.base_url())
+                .build()?;
+            print_pages(format, pages_of(&client, request, 1).await?)?;
+        }
+        Command::Timeline {
+            user_id,
+            mentions,
+            exclude,
+            time,
+            fields,
+            max_results,
+            pages,
+        } => {
+            let endpoint = if mentions {
+                Endpoint::TimelineUserMentions(user_id)
+            } else {
+                Endpoint::TimelineUserTweets(user_id)
+            };
+            let options = credentials.field_options(&fields, &endpoint);
+            let mut builder = TwitterRequest::builder()
+                .set_method(Method::GET)
+                .set_endpoint(endpoint);
+            if !exclude.is_empty() {
+                builder = builder.add_option(MainOptions::Exclude(
+                    exclude.into_iter().map(Into::into).collect(),
+                ));
+            }
+            for option in time.options().into_iter().chain(options) {
+                builder = builder.add_option(option);
+            }
+            if let Some(max_results) = max_results {
+                builder = builder.add_option(MainOptions::MaxResults(max_results.into()));
+            }
+            let request = builder
+                .set_authentication(credentials.app()?)
+                .set_base_url(credentials.base_url())
+                .build()?;
+            print_pages(format, pages_of(&client, request, pages).await?)?;
+        }
+        Command::Stream { fields } => {
+            let mut builder = TwitterRequest::builder()
+                .set_method(Method::GET)
+                .set_endpoint(Endpoint::StreamTweets);
+            for option in credentials.field_options(&fields, &Endpoint::StreamTweets) {
+                builder = builder.add_option(option);
+            }
+            let request = builder
+                .set_authentication(credentials.app()?)
+                .set_base_url(credentials.base_url())
+                .build()?;
+            let mut tweets = request.send_streaming::<Value>(&client).await?.boxed();
+            let mut printer = StreamPrinter::new(format);
+            while let Some(line) = tweets.next().await {
+                let mut line = line?;
+                // Each line is a response with a single tweet, print just the tweet
+                printer.print(&line.get_mut("data").map(Value::take).unwrap_or(line))?;
+            }
+        }
+        Command::Rules { command } => {
+            rules(
+                &client,
+                credentials.app()?,
+                credentials.base_url(),
+                format,
+                command,
+            )
+            .await?
+        }
+        Command::Hydrate {
+            input,
+            output,
+            csv_column,
+            checkpoint,
+            concurrency,
+            fields,
+        } => {
+            let mut hydrator = Hydrator::new(output).base_url(credentials.base_url());
+            if let Some(checkpoint) = checkpoint {
+                hydrator = hydrator.checkpoint(checkpoint);
+            }
+            if let Some(concurrency) = concurrency {
+                hydrator = hydrator.concurrency(concurrency as usize);
+            }
+            for option in credentials.field_options(&fields, &Endpoint::LookupTweets) {
+                hydrator = hydrator.add_option(option);
+            }
+            let summary = hydrator
+                .hydrate_file(&client, &credentials.app()?, input, &id_format(csv_column))
+                .await?;
+            eprintln!(
+                "Hydrated {} ids: {} tweets, {} not found, {} not authorized",
+                summary.ids, summary.tweets, summary.not_found, summary.not_authorized
+            );
+        }
+        Command::Post {
+            text,
+            reply_to,
+            quote,
+            media,
+        } => {
+            let mut tweet = NewTweet::builder().set_text(&text);
+            if let Some(reply_to) = reply_to {
+                tweet = tweet.set_in_reply_to(reply_to);
+            }
+            if let Some(quote) = quote {
+                tweet = tweet.set_quote_tweet_id(quote);
+            }
+            if !media.is_empty() {
+                let auth = credentials
+                    .oauth1()
+                    .ok_or("Uploading media needs OAuth 1.0a credentials")?;
+                for path in &media {
+                    tweet = tweet.add_media_id(upload(&client, &auth, path).await?);
+                }
+            }
+            let created = Actions::new()
+                .base_url(credentials.base_url())
+                .create_tweet(&client, credentials.user()?, &tweet.build()?)
+                .await?;
+            output::print(format, &[serde_json::to_value(created)?])?;
+        }
+    }
+    Ok(())
+}
+
+async fn rules(
+    client: &Client,
+    auth: AuthenticationData,
+    base_url: &str,
+    format: Format,
+    command: RulesCommand,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        RulesCommand::List => {
+            let rules = TwitterRequest::builder()
+                .set_base_url(base_url)
+                .set_endpoint(Endpoint::StreamRules)
+                .set_method(Method::GET)
+                .set_authentication(auth)
+                .build()?
+                .send_typed::<Vec<StreamRule>>(client)
+                .await?
+                // There are no rules if there is no data
+                .data
+                .unwrap_or_default();
+            output::print(format, &to_values(&rules)?)?;
+        }
+        RulesCommand::Add { filters, tag } => {
+            let value = filters.required_group_list()?.to_string();
+            let rule = Actions::new()
+                .base_url(base_url)
+                .add_stream_rule(client, auth, &value, tag.as_deref())
+                .await?;
+            output::print(format, &[serde_json::to_value(rule)?])?;
+        }
+        RulesCommand::Delete { ids } => {
+            Actions::new()
+                .base_url(base_url)
+                .delete_stream_rules(client, auth, &ids)
+                .await?;
+            eprintln!("Deleted {} rules", ids.len());
+        }
+    }
+    Ok(())
+}
+
+/// The results of a paginated request
+struct Pages {
+    records: Vec<Value>,
+    includes: Includes,
+    /// Why the pages ended before all the results were fetched, if they did
+    error: Option<TwitterError>,
+}
+
+/// Sends the request and the requests for up to `pages - 1` pages after it, and
+/// returns the data of all of them and the objects their expansions included. Reaching
+/// the endpoint's history limit ends the pages with the results fetched so far.
+async fn pages_of(
+    client: &Client,
+    request: TwitterRequest,
+    pages: u32,
+) -> Result<Pages, Box<dyn Error>> {
+    let mut records = Vec::new();
+    let mut includes = Includes::default();
+    let mut request = Some(request);
+    let mut error = None;
+    for _ in 0..pages {
+        let current = match request.take() {
+            Some(current) => current,
+            None => break,
+        };
+        let response: TwitterResponse<Vec<Value>> =
+            current.clone().send_typed_waiting(client).await?;
+        if response.data.is_none() && !response.errors.is_empty() {
+            return Err(Box::new(response.into_data().unwrap_err()));
+        }
+        records.extend(response.data.unwrap_or_default());
+        if let Some(included) = response.includes {
+            includes.merge(included);
+        }
+        if let Some(meta) = &response.meta {
+            match current.next_page(meta) {
+                Ok(next) => request = next,
+                Err(e @ TwitterError::HistoryLimitError(..)) => error = Some(e),
+                Err(e) => return Err(Box::new(e)),
+            }
+        }
+    }
+    Ok(Pages {
+        records,
+        includes,
+        error,
+    })
+}
+
+/// Prints the results, then fails with the error that ended the pages early, if any
+fn print_pages(format: Format, pages: Pages) -> Result<(), Box<dyn Error>> {
+    output::print_response(format, &pages.records, &pages.includes)?;
+    match pages.error {
+        Some(e) => Err(Box::new(e)),
+        None => Ok(()),
+    }
+}
+
+async fn upload(
+    client: &Client,
+    auth: &AuthenticationData,
+    path: &Path,
+) -> Result<Id, Box<dyn Error>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let (media_type, category) = match extension.as_str() {
+        "jpg" | "jpeg" => ("image/jpeg", MediaCategory::TweetImage),
+        "png" => ("image/png", MediaCategory::TweetImage),
+        "webp" => ("image/webp", MediaCategory::TweetImage),
+        "gif" => ("image/gif", MediaCategory::TweetGif),
+        "mp4" => ("video/mp4", MediaCategory::TweetVideo),
+        "mov" => ("video/quicktime", MediaCategory::TweetVideo),
+        _ => return Err(format!("Unknown media type of {}", path.display()).into()),
+    };
+    Ok(MediaUpload::new(path, media_type)
+        .media_category(category)
+        .upload(client, auth)
+        .await?)
+}
+
+fn id_format(csv_column: Option<String>) -> IdFormat {
+    match csv_column {
+        Some(column) => IdFormat::Csv(match column.parse() {
+            Ok(index) => CsvColumn::Index(index),
+            Err(_) => CsvColumn::Name(column),
+        }),
+        None => IdFormat::Text,
+    }
+}
+
+fn to_values<T: serde::Serialize>(items: &[T]) -> Result<Vec<Value>, serde_json::Error> {
+    items.iter().map(serde_json::to_value).collect()
+}
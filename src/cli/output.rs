@@ -0,0 +1,338 @@
+/*
+   The GPLv3 License (GPLv3)
+
+   Copyright (c) 2022 Jacob Skoog
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU General Public License as published by
+   the Free Software Foundation, either version 3 of the License, or
+   (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU General Public License for more details.
+
+   You should have received a copy of the GNU General Public License
+   along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use twitter_request::twitter::twitter_objects::response::Includes;
+
+/// The longest a table cell gets before it is cut off
+const MAX_CELL_WIDTH: usize = 40;
+
+/// How the results are printed
+#[derive(ValueEnum, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Format {
+    /// One pretty printed JSON array
+    #[default]
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// An aligned text table of the top level fields
+    Table,
+    /// CSV with a header row of the top level fields
+    Csv,
+}
+
+/// Writes the records to standard output in the format
+pub fn print(format: Format, records: &[Value]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write(&mut out, format, records)?;
+    out.flush()
+}
+
+/// Writes the records and the objects the expansions of the request included with them
+pub fn print_response(format: Format, records: &[Value], includes: &Includes) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    write_response(&mut out, format, records, includes)?;
+    out.flush()
+}
+
+/// Writes a record that arrived on a stream as soon as it arrives. Tables and CSV
+/// don't know all the columns up front, so they get the columns of the first record.
+pub struct StreamPrinter {
+    format: Format,
+    columns: Option<Vec<String>>,
+}
+
+impl StreamPrinter {
+    pub fn new(format: Format) -> StreamPrinter {
+        StreamPrinter {
+            format,
+            columns: None,
+        }
+    }
+
+    pub fn print(&mut self, record: &Value) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        self.write(&mut out, record)?;
+        out.flush()
+    }
+
+    fn write(&mut self, out: &mut impl Write, record: &Value) -> io::Result<()> {
+        match self.format {
+            Format::Json | Format::Jsonl => writeln!(out, "{}", record),
+            Format::Table | Format::Csv => {
+                let first = self.columns.is_none();
+                let columns = self
+                    .columns
+                    .get_or_insert_with(|| columns(std::slice::from_ref(record)));
+                let rows = [row(record, columns)];
+                if self.format == Format::Table {
+                    if first {
+                        writeln!(out, "{}", columns.join("\t"))?;
+                    }
+                    let cells: Vec<String> = rows[0].iter().map(|c| table_cell(c)).collect();
+                    writeln!(out, "{}", cells.join("\t"))
+                } else {
+                    let mut writer = csv::WriterBuilder::new().from_writer(out);
+                    if first {
+                        writer.write_record(columns.iter())?;
+                    }
+                    writer.write_record(&rows[0])?;
+                    writer.flush()
+                }
+            }
+        }
+    }
+}
+
+pub fn write(out: &mut impl Write, format: Format, records: &[Value]) -> io::Result<()> {
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)
+        }
+        Format::Jsonl => records
+            .iter()
+            .try_for_each(|record| writeln!(out, "{}", record)),
+        Format::Table => write_table(out, records),
+        Format::Csv => {
+            let columns = columns(records);
+            let mut writer = csv::WriterBuilder::new().from_writer(out);
+            writer.write_record(&columns)?;
+            for record in records {
+                writer.write_record(row(record, &columns))?;
+            }
+            writer.flush()
+        }
+    }
+}
+
+/// Writes the records like `write` when nothing is included. Otherwise JSON is an object
+/// of the records as `data` next to `includes`, JSON lines end with an `includes` line,
+/// and tables and CSV are followed by a section for each kind of included object.
+pub fn write_response(
+    out: &mut impl Write,
+    format: Format,
+    records: &[Value],
+    includes: &Includes,
+) -> io::Result<()> {
+    if includes.is_empty() {
+        return write(out, format, records);
+    }
+    let includes = serde_json::to_value(includes)?;
+    match format {
+        Format::Json => {
+            serde_json::to_writer_pretty(
+                &mut *out,
+                &json!({ "data": records, "includes": includes }),
+            )?;
+            writeln!(out)
+        }
+        Format::Jsonl => {
+            write(out, format, records)?;
+            writeln!(out, "{}", json!({ "includes": includes }))
+        }
+        Format::Table | Format::Csv => {
+            write(out, format, records)?;
+            let kinds = includes.as_object().into_iter().flatten();
+            for (kind, objects) in kinds {
+                match objects {
+                    Value::Array(objects) if !objects.is_empty() => {
+                        writeln!(out)?;
+                        writeln!(out, "includes.{}", kind)?;
+                        write(out, format, objects)?;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_table(out: &mut impl Write, records: &[Value]) -> io::Result<()> {
+    let columns = columns(records);
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| {
+            row(record, &columns)
+                .iter()
+                .map(|c| table_cell(c))
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([column.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    writeln!(out, "{}", line(&columns))?;
+    for row in &rows {
+        writeln!(out, "{}", line(row))?;
+    }
+    Ok(())
+}
+
+/// The top level fields of the records, with `id` first and the rest sorted.
+/// Fields that are null or missing in every record are left out.
+fn columns(records: &[Value]) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for record in records {
+        if let Value::Object(fields) = record {
+            names.extend(
+                fields
+                    .iter()
+                    .filter(|(_, value)| !value.is_null())
+                    .map(|(name, _)| name.clone()),
+            );
+        }
+    }
+    let mut columns = Vec::with_capacity(names.len());
+    if names.remove("id") {
+        columns.push(String::from("id"));
+    }
+    columns.extend(names);
+    columns
+}
+
+fn row(record: &Value, columns: &[String]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| cell(record.get(column).unwrap_or(&Value::Null)))
+        .collect()
+}
+
+/// Renders a field as text, with nested objects and arrays as JSON
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Puts a cell on one line and cuts it off at `MAX_CELL_WIDTH` characters
+fn table_cell(text: &str) -> String {
+    let text = text.replace(['\n', '\r', '\t'], " ");
+    if text.chars().count() > MAX_CELL_WIDTH {
+        let cut: String = text.chars().take(MAX_CELL_WIDTH - 3).collect();
+        format!("{}...", cut)
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(format: Format, records: &[Value]) -> String {
+        let mut out = Vec::new();
+        write(&mut out, format, records).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn output_test() {
+        let records = [
+            json!({"text": "hello\nworld", "id": "1", "lang": null}),
+            json!({"id": "22", "text": "a, \"quoted\" tweet", "author_id": "7"}),
+        ];
+        assert_eq!(
+            render(Format::Jsonl, &records),
+            "{\"id\":\"1\",\"lang\":null,\"text\":\"hello\\nworld\"}\n\
+             {\"author_id\":\"7\",\"id\":\"22\",\"text\":\"a, \\\"quoted\\\" tweet\"}\n"
+        );
+        assert_eq!(
+            render(Format::Table, &records),
+            "id  author_id  text\n\
+             1              hello world\n\
+             22  7          a, \"quoted\" tweet\n"
+        );
+        assert_eq!(
+            render(Format::Csv, &records),
+            "id,author_id,text\n\
+             1,,\"hello\nworld\"\n\
+             22,7,\"a, \"\"quoted\"\" tweet\"\n"
+        );
+        let long = json!({"id": "3", "text": "x".repeat(100)});
+        let table = render(Format::Table, &[long]);
+        assert!(table.lines().nth(1).unwrap().ends_with("xxx..."));
+        assert_eq!(table.lines().nth(1).unwrap().len(), 4 + MAX_CELL_WIDTH);
+    }
+
+    #[test]
+    fn includes_test() {
+        let records = [json!({"id": "1", "text": "hi", "author_id": "7"})];
+        let includes: Includes = serde_json::from_value(json!({
+            "users": [{"id": "7", "name": "Seven", "username": "seven"}],
+            "media": [{"media_key": "3_1", "type": "photo"}],
+        }))
+        .unwrap();
+        let render_with = |format, includes: &Includes| {
+            let mut out = Vec::new();
+            write_response(&mut out, format, &records, includes).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let json: Value = serde_json::from_str(&render_with(Format::Json, &includes)).unwrap();
+        assert_eq!(json["data"][0]["id"], "1");
+        assert_eq!(json["includes"]["users"][0]["username"], "seven");
+        assert_eq!(json["includes"]["media"][0]["media_key"], "3_1");
+
+        let jsonl = render_with(Format::Jsonl, &includes);
+        let last: Value = serde_json::from_str(jsonl.lines().last().unwrap()).unwrap();
+        assert_eq!(last["includes"]["media"][0]["type"], "photo");
+
+        let csv = render_with(Format::Csv, &includes);
+        let sections: Vec<&str> = csv.split("\n\n").collect();
+        assert_eq!(sections.len(), 3);
+        assert!(sections[1].starts_with("includes.media\nmedia_key,type\n3_1,photo"));
+        assert!(sections[2].starts_with("includes.users\nid,"));
+
+        // Without includes the output is the same as for the records alone
+        assert_eq!(
+            render_with(Format::Table, &Includes::default()),
+            render(Format::Table, &records)
+        );
+    }
+}
@@ -18,11 +18,15 @@ along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
 use std::fmt::Display;
+use std::path::PathBuf;
 
-use http::Method;
+use http::{Method, StatusCode};
 use thiserror::Error;
 
+use crate::twitter::access_level::AccessLevel;
 use crate::twitter::endpoints::AuthenticationType;
+use crate::twitter::rate_limit::RateLimit;
+use crate::twitter::twitter_objects::response::ResponseError;
 use crate::twitter::Endpoint;
 
 #[derive(Error, Debug)]
@@ -35,6 +39,21 @@ pub enum TwitterError {
     WrongAuthError(Endpoint, AuthenticationType, Method),
     BadAuthError(AuthenticationType),
     BadQueryError(String),
+    ApiError(StatusCode, String),
+    HistoryLimitError(Endpoint, u32),
+    RateLimitError(RateLimit),
+    ResponseErrors(Vec<ResponseError>),
+    BuilderError(TwitterBuilderError),
+    IoError(std::io::Error),
+    MediaProcessingError(String),
+    ComplianceJobError(String),
+    JsonError(serde_json::Error),
+    BadIdError(u64, String),
+    ConfigError(String),
+    CassetteError(String),
+    CacheError(String),
+    StreamRuleError(String),
+    CursorError(PathBuf, String),
 }
 
 impl Display for TwitterError {
@@ -80,10 +99,73 @@ impl Display for TwitterError {
             TwitterError::BadQueryError(info) => {
                 write!(f, "{}", info)
             }
+            TwitterError::ApiError(status, body) => {
+                write!(f, "Twitter responded with {}: {}", status, body)
+            }
+            TwitterError::HistoryLimitError(endpoint, limit) => {
+                write!(
+                    f,
+                    "Reached the end of what {} can paginate through, only the {} most recent tweets are available",
+                    endpoint, limit
+                )
+            }
+            TwitterError::RateLimitError(rate_limit) => {
+                write!(
+                    f,
+                    "Rate limit of {} requests reached, resets at {}",
+                    rate_limit.limit, rate_limit.reset
+                )
+            }
+            TwitterError::ResponseErrors(errors) => {
+                let titles: Vec<String> = errors
+                    .iter()
+                    .map(|e| match &e.detail {
+                        Some(detail) => format!("{}: {}", e.title, detail),
+                        None => e.title.to_string(),
+                    })
+                    .collect();
+                write!(f, "Twitter returned no data, {}", titles.join(", "))
+            }
+            TwitterError::BuilderError(e) => write!(f, "{}", e),
+            TwitterError::IoError(e) => write!(f, "{}", e),
+            TwitterError::MediaProcessingError(message) => {
+                write!(
+                    f,
+                    "Twitter could not process the uploaded media: {}",
+                    message
+                )
+            }
+            TwitterError::ComplianceJobError(message) => {
+                write!(f, "The compliance job did not complete, {}", message)
+            }
+            TwitterError::JsonError(e) => write!(f, "{}", e),
+            TwitterError::BadIdError(line, value) => {
+                write!(f, "\"{}\" on line {} is not a valid id", value, line)
+            }
+            TwitterError::ConfigError(message) => write!(f, "Bad config, {}", message),
+            TwitterError::CassetteError(message) => write!(f, "Cassette error, {}", message),
+            TwitterError::CacheError(message) => write!(f, "Cache error, {}", message),
+            TwitterError::StreamRuleError(message) => {
+                write!(f, "The stream rule was not added, {}", message)
+            }
+            TwitterError::CursorError(path, value) => {
+                write!(
+                    f,
+                    "The cursor \"{}\" saved in {} is not a valid id",
+                    value,
+                    path.display()
+                )
+            }
         }
     }
 }
 
+impl From<TwitterBuilderError> for TwitterError {
+    fn from(e: TwitterBuilderError) -> Self {
+        TwitterError::BuilderError(e)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum TwitterBuilderError {
     #[error("no authentication provided, cannot build TwitterRequest")]
@@ -94,4 +176,8 @@ pub enum TwitterBuilderError {
     NoMethodError,
     #[error("Bad query, {0}")]
     BadQueryError(String),
+    #[error("Bad tweet, {0}")]
+    BadTweetError(String),
+    #[error("{0} requires {1} access, but the credentials have {2} access")]
+    AccessLevelError(String, AccessLevel, AccessLevel),
 }
@@ -17,80 +17,12 @@
    along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-use dotenv::dotenv;
-use http::Method;
-use reqwest;
-use std::env;
-use twitter_request::errors::{TwitterBuilderError, TwitterError};
-use twitter_request::{twitter, Endpoint, Filter, TwitterRequest};
+mod cli;
 
 #[tokio::main]
 async fn main() {
-    dotenv().expect(".env file not found!");
-    let client = reqwest::Client::new();
-    // let req = client
-    //     .request(http::Method::GET, endpoint.to_string())
-    //     .bearer_auth(env::var("BEARER_TOKEN").unwrap().as_str())
-    //     .header(reqwest::header::CONTENT_TYPE, "application/json")
-    //     .query(&[("query", "(from:Archival_Blob)")]); // Twitter puts all its shit into query=(key:val)(key:val)
-    // println!("req_builder: {:?}", req);
-    // let request = req.try_clone().unwrap().build().unwrap();
-    // let url = request.url().as_str();
-    // println!("url: {}", url);
-    // println!("req: {:?}", request);
-    // let res = req.send().await;
-    // match res {
-    //     Ok(r) => {
-    //         println!("{:?}", r)
-    //     }
-    //     Err(e) => {
-    //         println!("{:?}", e)
-    //     }
-    // };
-    //
-    let request = TwitterRequest::builder()
-        .set_endpoint(Endpoint::SearchTweetsRecent)
-        .add_and_filter(Filter::From("Archival_Blob".to_string(), true.into()))
-        .add_bearer_token(&env::var("BEARER_TOKEN").unwrap())
-        .set_method(Method::GET)
-        .build();
-    let request = match request {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("{:?}", e);
-            panic!("Error building request")
-        }
-    };
-    let req = request.send_request(&client).await;
-    match req {
-        Ok(r) => {
-            println!("ok: {:?}", &r);
-            println!("resonse text: {:?}", r.text().await.unwrap())
-        }
-        Err(e) => {
-            println!("error: {}", e)
-        }
-    }
-    let request = TwitterRequest::builder()
-        .set_endpoint(Endpoint::LookupTweets)
-        .add_id(1585200978755665925)
-        .add_id(1585228185222144000)
-        .add_bearer_token(&env::var("BEARER_TOKEN").unwrap())
-        .set_method(Method::GET)
-        .build();
-    let request = match request {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("{:?}", e);
-            panic!("Error building request")
-        }
-    };
-    let req = request.send_request(&client).await;
-    match req {
-        Ok(r) => {
-            println!("ok: {:?}", &r);
-            println!("response text: {:?}", r.text().await.unwrap())
-        }
-        Err(e) => eprintln!("error: {}", e),
+    if let Err(e) = cli::run().await {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,105 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TwitterBuilderError;
+
+/// The access tier of the developer account a set of credentials belongs to.
+/// The tiers are ranked Free < Basic < Pro < Enterprise, and the legacy Academic
+/// Research tier gives the same access as Pro.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLevel {
+    Free,
+    Basic,
+    Pro,
+    Enterprise,
+    Academic,
+}
+
+impl AccessLevel {
+    pub const ALL: [AccessLevel; 5] = [
+        AccessLevel::Free,
+        AccessLevel::Basic,
+        AccessLevel::Pro,
+        AccessLevel::Enterprise,
+        AccessLevel::Academic,
+    ];
+
+    /// Checks if this tier gives access to what `required` is needed for
+    pub fn satisfies(&self, required: &AccessLevel) -> bool {
+        self.rank() >= required.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            AccessLevel::Free => 0,
+            AccessLevel::Basic => 1,
+            AccessLevel::Pro | AccessLevel::Academic => 2,
+            AccessLevel::Enterprise => 3,
+        }
+    }
+}
+
+impl Display for AccessLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AccessLevel::Free => "free",
+            AccessLevel::Basic => "basic",
+            AccessLevel::Pro => "pro",
+            AccessLevel::Enterprise => "enterprise",
+            AccessLevel::Academic => "academic",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl TryFrom<&str> for AccessLevel {
+    type Error = TwitterBuilderError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        AccessLevel::ALL
+            .into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| {
+                TwitterBuilderError::BadQueryError(format!("\"{}\" is not an access level", value))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn satisfies_test() {
+        assert!(AccessLevel::Pro.satisfies(&AccessLevel::Basic));
+        assert!(AccessLevel::Basic.satisfies(&AccessLevel::Basic));
+        assert!(!AccessLevel::Free.satisfies(&AccessLevel::Basic));
+        assert!(AccessLevel::Academic.satisfies(&AccessLevel::Pro));
+        assert!(AccessLevel::Pro.satisfies(&AccessLevel::Academic));
+        assert!(!AccessLevel::Academic.satisfies(&AccessLevel::Enterprise));
+        assert!(AccessLevel::Enterprise.satisfies(&AccessLevel::Academic));
+        assert_eq!(
+            AccessLevel::try_from("Academic").unwrap(),
+            AccessLevel::Academic
+        );
+    }
+}
@@ -0,0 +1,1037 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use http::Method;
+use reqwest::Client;
+use serde_json::json;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::new_tweet::NewTweet;
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::request::{TwitterRequest, TwitterRequestBuilder};
+use crate::twitter::twitter_objects::dm::SentDm;
+use crate::twitter::twitter_objects::list::{
+    CreatedList, ListDeletedStatus, ListMemberStatus, ListPinnedStatus, ListUpdatedStatus,
+};
+use crate::twitter::twitter_objects::stream_rule::StreamRule;
+use crate::twitter::twitter_objects::tweet::{
+    BookmarkedStatus, CreatedTweet, HiddenStatus, LikedStatus, RetweetedStatus,
+};
+use crate::twitter::twitter_objects::user::{BlockingStatus, FollowingStatus, MutingStatus};
+
+/// Sends actions taken as a user, such as tweeting, following and liking, to the API.
+/// They go to twitter unless another base url, such as a mock server's, is set.
+#[derive(Debug, Clone)]
+pub struct Actions {
+    base_url: String,
+}
+
+impl Default for Actions {
+    fn default() -> Self {
+        Actions::new()
+    }
+}
+
+impl Actions {
+    /// Creates actions that are sent to `TWITTER_URL`
+    pub fn new() -> Actions {
+        Actions {
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Posts a new tweet as the user `auth` is the user context of
+    pub async fn create_tweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        tweet: &NewTweet,
+    ) -> Result<CreatedTweet, TwitterError> {
+        self.request(Endpoint::LookupTweets, Method::POST, auth)
+            .set_new_tweet(tweet)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Hides the reply `tweet_id` if `hidden` is true, or unhides it otherwise.
+    /// `auth` must be user context authentication for the author of the conversation
+    /// the reply is in.
+    pub async fn hide_reply(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        tweet_id: Id,
+        hidden: bool,
+    ) -> Result<HiddenStatus, TwitterError> {
+        self.request(Endpoint::LookupTweetHidden(tweet_id), Method::PUT, auth)
+            .set_body(json!({ "hidden": hidden }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `source_user_id` follow `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn follow_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<FollowingStatus, TwitterError> {
+        self.request(Endpoint::UserFollowing(source_user_id), Method::POST, auth)
+            .set_body(json!({ "target_user_id": target_user_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `source_user_id` unfollow `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn unfollow_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<FollowingStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnfollow(source_user_id, target_user_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `user_id` like `tweet_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn like_tweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        tweet_id: Id,
+    ) -> Result<LikedStatus, TwitterError> {
+        self.request(Endpoint::UserLikes(user_id), Method::POST, auth)
+            .set_body(json!({ "tweet_id": tweet_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `user_id` unlike `tweet_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn unlike_tweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        tweet_id: Id,
+    ) -> Result<LikedStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnlike(user_id, tweet_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `user_id` retweet `tweet_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn retweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        tweet_id: Id,
+    ) -> Result<RetweetedStatus, TwitterError> {
+        self.request(Endpoint::UserRetweets(user_id), Method::POST, auth)
+            .set_body(json!({ "tweet_id": tweet_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Undoes the retweet `user_id` made of `source_tweet_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn undo_retweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        source_tweet_id: Id,
+    ) -> Result<RetweetedStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnretweet(user_id, source_tweet_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Creates a list owned by the user `auth` is the user context of
+    pub async fn create_list(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        name: &str,
+        description: Option<&str>,
+        private: bool,
+    ) -> Result<CreatedList, TwitterError> {
+        let mut body = json!({ "name": name, "private": private });
+        if let Some(description) = description {
+            body["description"] = json!(description);
+        }
+        self.request(Endpoint::CreateList, Method::POST, auth)
+            .set_body(body)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Updates the parts of `list_id` that are Some, leaving the rest as they are.
+    /// `auth` must be user context authentication for the owner of the list.
+    pub async fn update_list(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        list_id: Id,
+        name: Option<&str>,
+        description: Option<&str>,
+        private: Option<bool>,
+    ) -> Result<ListUpdatedStatus, TwitterError> {
+        let mut body = json!({});
+        if let Some(name) = name {
+            body["name"] = json!(name);
+        }
+        if let Some(description) = description {
+            body["description"] = json!(description);
+        }
+        if let Some(private) = private {
+            body["private"] = json!(private);
+        }
+        self.request(Endpoint::LookupList(list_id), Method::PUT, auth)
+            .set_body(body)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Deletes `list_id`.
+    /// `auth` must be user context authentication for the owner of the list.
+    pub async fn delete_list(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        list_id: Id,
+    ) -> Result<ListDeletedStatus, TwitterError> {
+        self.request(Endpoint::LookupList(list_id), Method::DELETE, auth)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Adds `user_id` to the members of `list_id`.
+    /// `auth` must be user context authentication for the owner of the list.
+    pub async fn add_list_member(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        list_id: Id,
+        user_id: Id,
+    ) -> Result<ListMemberStatus, TwitterError> {
+        self.request(Endpoint::ListMembers(list_id), Method::POST, auth)
+            .set_body(json!({ "user_id": user_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Removes `user_id` from the members of `list_id`.
+    /// `auth` must be user context authentication for the owner of the list.
+    pub async fn remove_list_member(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        list_id: Id,
+        user_id: Id,
+    ) -> Result<ListMemberStatus, TwitterError> {
+        self.request(
+            Endpoint::ListRemoveMember(list_id, user_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `user_id` pin `list_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn pin_list(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        list_id: Id,
+    ) -> Result<ListPinnedStatus, TwitterError> {
+        self.request(Endpoint::UserPinnedLists(user_id), Method::POST, auth)
+            .set_body(json!({ "list_id": list_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `user_id` unpin `list_id`.
+    /// `auth` must be user context authentication for the user.
+    pub async fn unpin_list(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        list_id: Id,
+    ) -> Result<ListPinnedStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnpinList(user_id, list_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `user_id` bookmark `tweet_id`.
+    /// `auth` must be an OAuth 2.0 user token for the user.
+    pub async fn bookmark_tweet(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        tweet_id: Id,
+    ) -> Result<BookmarkedStatus, TwitterError> {
+        self.request(Endpoint::UserBookmarks(user_id), Method::POST, auth)
+            .set_body(json!({ "tweet_id": tweet_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Removes `tweet_id` from the bookmarks of `user_id`.
+    /// `auth` must be an OAuth 2.0 user token for the user.
+    pub async fn remove_bookmark(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        user_id: Id,
+        tweet_id: Id,
+    ) -> Result<BookmarkedStatus, TwitterError> {
+        self.request(
+            Endpoint::UserRemoveBookmark(user_id, tweet_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `source_user_id` block `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn block_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<BlockingStatus, TwitterError> {
+        self.request(Endpoint::UserBlocking(source_user_id), Method::POST, auth)
+            .set_body(json!({ "target_user_id": target_user_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `source_user_id` unblock `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn unblock_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<BlockingStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnblock(source_user_id, target_user_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Makes `source_user_id` mute `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn mute_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<MutingStatus, TwitterError> {
+        self.request(Endpoint::UserMuting(source_user_id), Method::POST, auth)
+            .set_body(json!({ "target_user_id": target_user_id }))
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+
+    /// Makes `source_user_id` unmute `target_user_id`.
+    /// `auth` must be user context authentication for the source user.
+    pub async fn unmute_user(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        source_user_id: Id,
+        target_user_id: Id,
+    ) -> Result<MutingStatus, TwitterError> {
+        self.request(
+            Endpoint::UserUnmute(source_user_id, target_user_id),
+            Method::DELETE,
+            auth,
+        )
+        .build()?
+        .send_typed(client)
+        .await?
+        .into_data()
+    }
+
+    /// Sends a direct message to `participant_id`, in the one to one conversation with them.
+    /// `text` may be empty if media is attached.
+    pub async fn send_dm(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        participant_id: Id,
+        text: &str,
+        media_ids: &[Id],
+    ) -> Result<SentDm, TwitterError> {
+        self.send_message(
+            client,
+            auth,
+            Endpoint::DmConversationWithMessages(participant_id),
+            dm_message(text, media_ids)?,
+        )
+        .await
+    }
+
+    /// Sends a direct message to the existing conversation `dm_conversation_id`.
+    /// `text` may be empty if media is attached.
+    pub async fn send_dm_to_conversation(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        dm_conversation_id: &str,
+        text: &str,
+        media_ids: &[Id],
+    ) -> Result<SentDm, TwitterError> {
+        self.send_message(
+            client,
+            auth,
+            Endpoint::DmConversationMessages(String::from(dm_conversation_id)),
+            dm_message(text, media_ids)?,
+        )
+        .await
+    }
+
+    /// Creates a group conversation with `participant_ids` and sends the first message to it.
+    /// `text` may be empty if media is attached.
+    pub async fn create_group_dm(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        participant_ids: &[Id],
+        text: &str,
+        media_ids: &[Id],
+    ) -> Result<SentDm, TwitterError> {
+        if participant_ids.is_empty() {
+            return Err(TwitterError::BadQueryError(String::from(
+                "a group conversation needs participants",
+            )));
+        }
+        let body = json!({
+            "conversation_type": "Group",
+            "participant_ids": participant_ids,
+            "message": dm_message(text, media_ids)?,
+        });
+        self.send_message(client, auth, Endpoint::DmConversations, body)
+            .await
+    }
+
+    /// Adds a rule to the filtered stream, where `value` is the query tweets must match
+    /// and `tag` an optional label sent with the tweets that match it
+    pub async fn add_stream_rule(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        value: &str,
+        tag: Option<&str>,
+    ) -> Result<StreamRule, TwitterError> {
+        let mut rule = json!({ "value": value });
+        if let Some(tag) = tag {
+            rule["tag"] = json!(tag);
+        }
+        let response = self
+            .request(Endpoint::StreamRules, Method::POST, auth)
+            .set_body(json!({ "add": [rule] }))
+            .build()?
+            .send_typed::<Vec<StreamRule>>(client)
+            .await?;
+        // An invalid or duplicate rule is reported in `errors`, if at all
+        match response.data.and_then(|mut rules| rules.pop()) {
+            Some(rule) => Ok(rule),
+            None if !response.errors.is_empty() => {
+                Err(TwitterError::ResponseErrors(response.errors))
+            }
+            None => Err(TwitterError::StreamRuleError(format!(
+                "twitter did not add {}",
+                value
+            ))),
+        }
+    }
+
+    /// Deletes the rules with the ids from the filtered stream
+    pub async fn delete_stream_rules(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        rule_ids: &[Id],
+    ) -> Result<(), TwitterError> {
+        // Deleting returns only a summary in `meta`, so there is no data to take out
+        let response = self
+            .request(Endpoint::StreamRules, Method::POST, auth)
+            .set_body(json!({ "delete": { "ids": rule_ids } }))
+            .build()?
+            .send_typed::<Vec<StreamRule>>(client)
+            .await?;
+        if response.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TwitterError::ResponseErrors(response.errors))
+        }
+    }
+
+    /// Starts a request to the endpoint at the base url of the actions
+    fn request(
+        &self,
+        endpoint: Endpoint,
+        method: Method,
+        auth: AuthenticationData,
+    ) -> TwitterRequestBuilder {
+        TwitterRequest::builder()
+            .set_endpoint(endpoint)
+            .set_method(method)
+            .set_authentication(auth)
+            .set_base_url(&self.base_url)
+    }
+
+    async fn send_message(
+        &self,
+        client: &Client,
+        auth: AuthenticationData,
+        endpoint: Endpoint,
+        body: serde_json::Value,
+    ) -> Result<SentDm, TwitterError> {
+        self.request(endpoint, Method::POST, auth)
+            .set_body(body)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()
+    }
+}
+
+/// Builds the message object of a direct message request, which needs text or media
+fn dm_message(text: &str, media_ids: &[Id]) -> Result<serde_json::Value, TwitterError> {
+    if text.is_empty() && media_ids.is_empty() {
+        return Err(TwitterError::BadQueryError(String::from(
+            "a direct message needs text or media",
+        )));
+    }
+    let mut message = json!({});
+    if !text.is_empty() {
+        message["text"] = json!(text);
+    }
+    if !media_ids.is_empty() {
+        let attachments: Vec<serde_json::Value> = media_ids
+            .iter()
+            .map(|media_id| json!({ "media_id": media_id }))
+            .collect();
+        message["attachments"] = json!(attachments);
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer, ReceivedRequest};
+
+    fn oauth1() -> AuthenticationData {
+        AuthenticationData::new_oauth_auth("key", "secret", "token", "token-secret")
+    }
+
+    /// Sends the action to a server that answers with `data`, and returns the
+    /// action's result and the request the server received
+    async fn send<T, F, Fut>(data: Value, action: F) -> (T, ReceivedRequest)
+    where
+        F: FnOnce(Actions, Client) -> Fut,
+        Fut: Future<Output = Result<T, TwitterError>>,
+    {
+        let server = MockServer::start().await;
+        server.mock(Mock::any().respond_with(MockResponse::data(data)).times(1));
+        let actions = Actions::new().base_url(server.url());
+        let result = action(actions, Client::new()).await.unwrap();
+        server.verify();
+        (result, server.received().remove(0))
+    }
+
+    /// Checks the method, path and authorization scheme of the request, and returns its JSON body
+    fn check(request: &ReceivedRequest, method: &str, path: &str, scheme: &str) -> Option<Value> {
+        assert_eq!(request.method, method);
+        assert_eq!(request.path, path);
+        let authorization = request.header("authorization").unwrap();
+        assert!(
+            authorization.starts_with(scheme),
+            "{} is not {} authorization",
+            authorization,
+            scheme
+        );
+        if request.body.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&request.body).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn like_test() {
+        let (status, request) = send(json!({ "liked": true }), |actions, client| async move {
+            actions
+                .like_tweet(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(status.liked);
+        let body = check(&request, "POST", "/2/users/1/likes", "OAuth ");
+        assert_eq!(body, Some(json!({ "tweet_id": "2" })));
+
+        let (status, request) = send(json!({ "liked": false }), |actions, client| async move {
+            actions
+                .unlike_tweet(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(!status.liked);
+        assert_eq!(
+            check(&request, "DELETE", "/2/users/1/likes/2", "OAuth "),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn follow_test() {
+        let data = json!({ "following": true, "pending_follow": false });
+        let (status, request) = send(data, |actions, client| async move {
+            actions
+                .follow_user(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(status.following);
+        let body = check(&request, "POST", "/2/users/1/following", "OAuth ");
+        assert_eq!(body, Some(json!({ "target_user_id": "2" })));
+
+        let (status, request) = send(
+            json!({ "following": false }),
+            |actions, client| async move {
+                actions
+                    .unfollow_user(&client, oauth1(), 1.into(), 2.into())
+                    .await
+            },
+        )
+        .await;
+        assert!(!status.following);
+        let route = "/2/users/1/following/2";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn retweet_test() {
+        let (status, request) = send(json!({ "retweeted": true }), |actions, client| async move {
+            actions.retweet(&client, oauth1(), 1.into(), 2.into()).await
+        })
+        .await;
+        assert!(status.retweeted);
+        let body = check(&request, "POST", "/2/users/1/retweets", "OAuth ");
+        assert_eq!(body, Some(json!({ "tweet_id": "2" })));
+
+        let (status, request) = send(
+            json!({ "retweeted": false }),
+            |actions, client| async move {
+                actions
+                    .undo_retweet(&client, oauth1(), 1.into(), 2.into())
+                    .await
+            },
+        )
+        .await;
+        assert!(!status.retweeted);
+        let route = "/2/users/1/retweets/2";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn create_tweet_test() {
+        let tweet = NewTweet::builder()
+            .set_text("hello")
+            .set_in_reply_to(1)
+            .build()
+            .unwrap();
+        let data = json!({ "id": "2", "text": "hello" });
+        let (created, request) = send(data, |actions, client| async move {
+            actions.create_tweet(&client, oauth1(), &tweet).await
+        })
+        .await;
+        assert_eq!(created.id, Id::from(2));
+        let body = check(&request, "POST", "/2/tweets", "OAuth ");
+        assert_eq!(
+            body,
+            Some(json!({ "text": "hello", "reply": { "in_reply_to_tweet_id": "1" } }))
+        );
+    }
+
+    #[tokio::test]
+    async fn list_test() {
+        let data = json!({ "id": "3", "name": "rust" });
+        let (created, request) = send(data, |actions, client| async move {
+            actions
+                .create_list(&client, oauth1(), "rust", Some("crates"), true)
+                .await
+        })
+        .await;
+        assert_eq!(created.id, Id::from(3));
+        let body = check(&request, "POST", "/2/lists", "OAuth ");
+        assert_eq!(
+            body,
+            Some(json!({ "name": "rust", "description": "crates", "private": true }))
+        );
+
+        // Only the parts that are given are updated
+        let (status, request) = send(json!({ "updated": true }), |actions, client| async move {
+            actions
+                .update_list(&client, oauth1(), 3.into(), None, None, Some(false))
+                .await
+        })
+        .await;
+        assert!(status.updated);
+        let body = check(&request, "PUT", "/2/lists/3", "OAuth ");
+        assert_eq!(body, Some(json!({ "private": false })));
+
+        let (status, request) = send(json!({ "deleted": true }), |actions, client| async move {
+            actions.delete_list(&client, oauth1(), 3.into()).await
+        })
+        .await;
+        assert!(status.deleted);
+        assert_eq!(check(&request, "DELETE", "/2/lists/3", "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn list_member_test() {
+        let (status, request) = send(json!({ "is_member": true }), |actions, client| async move {
+            actions
+                .add_list_member(&client, oauth1(), 3.into(), 1.into())
+                .await
+        })
+        .await;
+        assert!(status.is_member);
+        let body = check(&request, "POST", "/2/lists/3/members", "OAuth ");
+        assert_eq!(body, Some(json!({ "user_id": "1" })));
+
+        let (status, request) = send(
+            json!({ "is_member": false }),
+            |actions, client| async move {
+                actions
+                    .remove_list_member(&client, oauth1(), 3.into(), 1.into())
+                    .await
+            },
+        )
+        .await;
+        assert!(!status.is_member);
+        let route = "/2/lists/3/members/1";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn pin_list_test() {
+        let (status, request) = send(json!({ "pinned": true }), |actions, client| async move {
+            actions
+                .pin_list(&client, oauth1(), 1.into(), 3.into())
+                .await
+        })
+        .await;
+        assert!(status.pinned);
+        let body = check(&request, "POST", "/2/users/1/pinned_lists", "OAuth ");
+        assert_eq!(body, Some(json!({ "list_id": "3" })));
+
+        let (status, request) = send(json!({ "pinned": false }), |actions, client| async move {
+            actions
+                .unpin_list(&client, oauth1(), 1.into(), 3.into())
+                .await
+        })
+        .await;
+        assert!(!status.pinned);
+        let route = "/2/users/1/pinned_lists/3";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn bookmark_test() {
+        let oauth2 = || AuthenticationData::new_oauth2_user_auth("user-token");
+        let (status, request) = send(
+            json!({ "bookmarked": true }),
+            |actions, client| async move {
+                actions
+                    .bookmark_tweet(&client, oauth2(), 1.into(), 2.into())
+                    .await
+            },
+        )
+        .await;
+        assert!(status.bookmarked);
+        let body = check(
+            &request,
+            "POST",
+            "/2/users/1/bookmarks",
+            "Bearer user-token",
+        );
+        assert_eq!(body, Some(json!({ "tweet_id": "2" })));
+
+        let (status, request) = send(
+            json!({ "bookmarked": false }),
+            |actions, client| async move {
+                actions
+                    .remove_bookmark(&client, oauth2(), 1.into(), 2.into())
+                    .await
+            },
+        )
+        .await;
+        assert!(!status.bookmarked);
+        let route = "/2/users/1/bookmarks/2";
+        assert_eq!(check(&request, "DELETE", route, "Bearer user-token"), None);
+
+        // Bookmarks only take an OAuth 2.0 user token, so nothing is sent with OAuth 1.0a
+        let result = Actions::new()
+            .base_url("http://127.0.0.1:9")
+            .bookmark_tweet(&Client::new(), oauth1(), 1.into(), 2.into())
+            .await;
+        assert!(matches!(result, Err(TwitterError::WrongAuthError(..))));
+    }
+
+    #[tokio::test]
+    async fn block_and_mute_test() {
+        let (status, request) = send(json!({ "blocking": true }), |actions, client| async move {
+            actions
+                .block_user(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(status.blocking);
+        let body = check(&request, "POST", "/2/users/1/blocking", "OAuth ");
+        assert_eq!(body, Some(json!({ "target_user_id": "2" })));
+
+        let (status, request) = send(json!({ "blocking": false }), |actions, client| async move {
+            actions
+                .unblock_user(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(!status.blocking);
+        let route = "/2/users/1/blocking/2";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+
+        let (status, request) = send(json!({ "muting": true }), |actions, client| async move {
+            actions
+                .mute_user(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(status.muting);
+        let body = check(&request, "POST", "/2/users/1/muting", "OAuth ");
+        assert_eq!(body, Some(json!({ "target_user_id": "2" })));
+
+        let (status, request) = send(json!({ "muting": false }), |actions, client| async move {
+            actions
+                .unmute_user(&client, oauth1(), 1.into(), 2.into())
+                .await
+        })
+        .await;
+        assert!(!status.muting);
+        let route = "/2/users/1/muting/2";
+        assert_eq!(check(&request, "DELETE", route, "OAuth "), None);
+    }
+
+    #[tokio::test]
+    async fn hide_reply_test() {
+        let (status, request) = send(json!({ "hidden": true }), |actions, client| async move {
+            actions.hide_reply(&client, oauth1(), 2.into(), true).await
+        })
+        .await;
+        assert!(status.hidden);
+        let body = check(&request, "PUT", "/2/tweets/2/hidden", "OAuth ");
+        assert_eq!(body, Some(json!({ "hidden": true })));
+    }
+
+    #[tokio::test]
+    async fn dm_test() {
+        let sent = || json!({ "dm_conversation_id": "1-2", "dm_event_id": "5" });
+        let (dm, request) = send(sent(), |actions, client| async move {
+            actions
+                .send_dm(&client, oauth1(), 2.into(), "hi", &[9.into()])
+                .await
+        })
+        .await;
+        assert_eq!(dm.dm_event_id, Id::from(5));
+        let route = "/2/dm_conversations/with/2/messages";
+        let body = check(&request, "POST", route, "OAuth ");
+        assert_eq!(
+            body,
+            Some(json!({ "text": "hi", "attachments": [{ "media_id": "9" }] }))
+        );
+
+        let (dm, request) = send(sent(), |actions, client| async move {
+            actions
+                .send_dm_to_conversation(&client, oauth1(), "1-2", "hi", &[])
+                .await
+        })
+        .await;
+        assert_eq!(dm.dm_conversation_id, "1-2");
+        let route = "/2/dm_conversations/1-2/messages";
+        let body = check(&request, "POST", route, "OAuth ");
+        assert_eq!(body, Some(json!({ "text": "hi" })));
+
+        let (_, request) = send(sent(), |actions, client| async move {
+            actions
+                .create_group_dm(&client, oauth1(), &[2.into(), 3.into()], "hi all", &[])
+                .await
+        })
+        .await;
+        let body = check(&request, "POST", "/2/dm_conversations", "OAuth ");
+        assert_eq!(
+            body,
+            Some(json!({
+                "conversation_type": "Group",
+                "participant_ids": ["2", "3"],
+                "message": { "text": "hi all" },
+            }))
+        );
+
+        // Empty messages and groups are refused before anything is sent
+        let actions = Actions::new().base_url("http://127.0.0.1:9");
+        let empty = actions
+            .send_dm(&Client::new(), oauth1(), 2.into(), "", &[])
+            .await;
+        assert!(matches!(empty, Err(TwitterError::BadQueryError(_))));
+        let no_one = actions
+            .create_group_dm(&Client::new(), oauth1(), &[], "hi", &[])
+            .await;
+        assert!(matches!(no_one, Err(TwitterError::BadQueryError(_))));
+    }
+
+    #[tokio::test]
+    async fn stream_rules_test() {
+        let app = || AuthenticationData::new_bearer_auth("app-token");
+        let data = json!([{ "id": "7", "value": "from:rustlang", "tag": "rust" }]);
+        let (rule, request) = send(data, |actions, client| async move {
+            actions
+                .add_stream_rule(&client, app(), "from:rustlang", Some("rust"))
+                .await
+        })
+        .await;
+        assert_eq!(rule.id, Id::from(7));
+        let route = "/2/tweets/search/stream/rules";
+        let body = check(&request, "POST", route, "Bearer app-token");
+        assert_eq!(
+            body,
+            Some(json!({ "add": [{ "value": "from:rustlang", "tag": "rust" }] }))
+        );
+
+        let (_, request) = send(Value::Null, |actions, client| async move {
+            actions
+                .delete_stream_rules(&client, app(), &[7.into(), 8.into()])
+                .await
+        })
+        .await;
+        let body = check(&request, "POST", route, "Bearer app-token");
+        assert_eq!(body, Some(json!({ "delete": { "ids": ["7", "8"] } })));
+
+        let server = MockServer::start().await;
+        server.mock(Mock::any().respond_with(MockResponse::data(json!([]))));
+        let added = Actions::new()
+            .base_url(server.url())
+            .add_stream_rule(&Client::new(), app(), "from:rustlang", None)
+            .await;
+        assert!(matches!(added, Err(TwitterError::StreamRuleError(_))));
+    }
+}
@@ -0,0 +1,607 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use http::Method;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::query_filters::group::GroupList;
+use crate::twitter::query_params::main_options::{Granularity, MainOptions};
+use crate::twitter::request::TwitterRequest;
+use crate::twitter::twitter_objects::tweet::{Tweet, TweetCount};
+
+/// The name of the merged output in the work directory
+pub const OUTPUT_FILE: &str = "tweets.jsonl";
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// How the time window of a backfill is split into slices
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Slicing {
+    /// Into this many slices of the same length
+    Even(u32),
+    /// Into slices of about this many tweets each, going by the daily counts of
+    /// `Endpoint::LookupTweetsCountAll`
+    ByCount(u64),
+}
+
+/// The progress of one slice
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+struct SliceState {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    /// The token of the next page to fetch, None before the first page
+    next_token: Option<String>,
+    done: bool,
+    /// The length of the slice's file once its fetched pages were written
+    output_bytes: u64,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+struct Checkpoint {
+    query: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    slices: Vec<SliceState>,
+}
+
+/// What a backfill collected
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BackfillSummary {
+    pub slices: usize,
+    /// The number of tweets in the output, after duplicates were removed
+    pub tweets: u64,
+    /// The JSONL file holding the tweets, oldest first
+    pub output: PathBuf,
+}
+
+/// Collects every tweet matching a query in a time window from the full archive.
+/// The window is split into slices that are paginated concurrently, each into its
+/// own file in the work directory. A checkpoint in the work directory records how far
+/// each slice has come, so running the backfill again after it was interrupted continues
+/// where it stopped. When every slice is done the tweets are merged into `OUTPUT_FILE`
+/// in the work directory, oldest first and without duplicates.
+///
+/// Full-archive search allows one request per second, which the backfill keeps to
+/// across all slices, and it waits for the rate limit to reset when it is hit.
+pub struct Backfill {
+    query: GroupList,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    work_dir: PathBuf,
+    slicing: Slicing,
+    concurrency: usize,
+    min_interval: Duration,
+    options: Vec<MainOptions>,
+    base_url: String,
+}
+
+impl Backfill {
+    /// Creates a backfill of the tweets matching `query` from `start` up to `end`,
+    /// split into 10 even slices that are fetched 2 at a time
+    pub fn new(
+        query: GroupList,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        work_dir: impl Into<PathBuf>,
+    ) -> Backfill {
+        Backfill {
+            query,
+            start,
+            end,
+            work_dir: work_dir.into(),
+            slicing: Slicing::Even(10),
+            concurrency: 2,
+            min_interval: Duration::from_secs(1),
+            options: Vec::new(),
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Sets how the window is split into slices. Has no effect when a checkpoint
+    /// exists, as the slices are then read from it.
+    pub fn slicing(mut self, slicing: Slicing) -> Self {
+        self.slicing = slicing;
+        self
+    }
+
+    /// Sets how many slices are fetched at a time, at least 1
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the least time between two requests, across all slices
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Adds a field option to the search requests
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Runs the backfill, or continues it from the checkpoint in the work directory.
+    /// Fails if the start of the window is not before its end.
+    pub async fn run(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+    ) -> Result<BackfillSummary, TwitterError> {
+        if self.start >= self.end {
+            return Err(TwitterError::BadQueryError(String::from(
+                "the start of a backfill must be before its end",
+            )));
+        }
+        tokio::fs::create_dir_all(&self.work_dir)
+            .await
+            .map_err(TwitterError::IoError)?;
+        let throttle = Throttle::new(self.min_interval);
+        let checkpoint = match self.read_checkpoint().await? {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let checkpoint = Checkpoint {
+                    query: self.query.to_string(),
+                    start: self.start,
+                    end: self.end,
+                    slices: self.plan(client, auth, &throttle).await?,
+                };
+                self.write_checkpoint(&checkpoint).await?;
+                checkpoint
+            }
+        };
+        let slices = checkpoint.slices.len();
+        let pending: Vec<usize> = (0..slices)
+            .filter(|i| !checkpoint.slices[*i].done)
+            .collect();
+        let checkpoint = Mutex::new(checkpoint);
+
+        let mut fetches = stream::iter(pending)
+            .map(|slice| self.fetch_slice(client, auth, slice, &checkpoint, &throttle))
+            .buffer_unordered(self.concurrency);
+        while let Some(fetched) = fetches.next().await {
+            fetched?;
+        }
+
+        let output = self.work_dir.join(OUTPUT_FILE);
+        let tweets = self.merge(slices, &output).await?;
+        Ok(BackfillSummary {
+            slices,
+            tweets,
+            output,
+        })
+    }
+
+    /// Splits the window into slices
+    async fn plan(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        throttle: &Throttle,
+    ) -> Result<Vec<SliceState>, TwitterError> {
+        let bounds = match self.slicing {
+            Slicing::Even(slices) => even_slices(self.start, self.end, slices),
+            Slicing::ByCount(tweets_per_slice) => {
+                let counts = self.counts(client, auth, throttle).await?;
+                slices_by_count(self.start, self.end, &counts, tweets_per_slice)
+            }
+        };
+        Ok(bounds
+            .into_iter()
+            .map(|(start, end)| SliceState {
+                start,
+                end,
+                next_token: None,
+                done: false,
+                output_bytes: 0,
+            })
+            .collect())
+    }
+
+    /// Gets the daily tweet counts of the window
+    async fn counts(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        throttle: &Throttle,
+    ) -> Result<Vec<TweetCount>, TwitterError> {
+        let mut counts = Vec::new();
+        let mut request = Some(
+            TwitterRequest::builder()
+                .set_endpoint(Endpoint::LookupTweetsCountAll)
+                .set_method(Method::GET)
+                .set_authentication(auth.clone())
+                .set_base_url(&self.base_url)
+                .add_group_list(self.query.clone())
+                .add_option(MainOptions::StartTime(self.start))
+                .add_option(MainOptions::EndTime(self.end))
+                .add_option(MainOptions::Granularity(Granularity::Day))
+                .build()?,
+        );
+        while let Some(page) = request {
+            throttle.wait().await;
+            let response = page
+                .clone()
+                .send_typed_waiting::<Vec<TweetCount>>(client)
+                .await?;
+            counts.extend(response.data.unwrap_or_default());
+            request = match response.meta {
+                Some(meta) => page.next_page(&meta)?,
+                None => None,
+            };
+        }
+        counts.sort_by_key(|count| count.start);
+        Ok(counts)
+    }
+
+    /// Fetches the remaining pages of a slice into its file
+    async fn fetch_slice(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        slice: usize,
+        checkpoint: &Mutex<Checkpoint>,
+        throttle: &Throttle,
+    ) -> Result<(), TwitterError> {
+        let state = checkpoint.lock().await.slices[slice].clone();
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(self.slice_path(slice))
+            .await
+            .map_err(TwitterError::IoError)?;
+        file.set_len(state.output_bytes)
+            .await
+            .map_err(TwitterError::IoError)?;
+        file.seek(SeekFrom::End(0))
+            .await
+            .map_err(TwitterError::IoError)?;
+
+        let mut builder = TwitterRequest::builder()
+            .set_endpoint(Endpoint::SearchTweetsAll)
+            .set_method(Method::GET)
+            .set_authentication(auth.clone())
+            .set_base_url(&self.base_url)
+            .add_group_list(self.query.clone())
+            .add_option(MainOptions::StartTime(state.start))
+            .add_option(MainOptions::EndTime(state.end))
+            .add_option(MainOptions::MaxResults(500.into()));
+        for option in &self.options {
+            builder = builder.add_option(option.clone());
+        }
+        if let Some(token) = &state.next_token {
+            builder = builder.add_option(MainOptions::NextToken(token.clone()));
+        }
+        let mut request = Some(builder.build()?);
+        while let Some(page) = request {
+            throttle.wait().await;
+            let response = page
+                .clone()
+                .send_typed_waiting::<Vec<Tweet>>(client)
+                .await?;
+            let mut lines = Vec::new();
+            for tweet in response.data.unwrap_or_default() {
+                serde_json::to_writer(&mut lines, &tweet).map_err(TwitterError::JsonError)?;
+                lines.push(b'\n');
+            }
+            file.write_all(&lines)
+                .await
+                .map_err(TwitterError::IoError)?;
+            file.sync_data().await.map_err(TwitterError::IoError)?;
+
+            request = match &response.meta {
+                Some(meta) => page.next_page(meta)?,
+                None => None,
+            };
+            let mut checkpoint = checkpoint.lock().await;
+            let state = &mut checkpoint.slices[slice];
+            state.output_bytes += lines.len() as u64;
+            state.next_token = response.meta.and_then(|meta| meta.next_token);
+            state.done = request.is_none();
+            self.write_checkpoint(&checkpoint).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the tweets of every slice to `output`, oldest first and without
+    /// duplicates, returning how many were written
+    async fn merge(&self, slices: usize, output: &Path) -> Result<u64, TwitterError> {
+        let mut seen = HashSet::new();
+        let mut merged = tokio::fs::File::create(output)
+            .await
+            .map_err(TwitterError::IoError)?;
+        // The slices are in order and do not overlap, so only the tweets within
+        // a slice need sorting
+        for slice in 0..slices {
+            let lines = tokio::fs::read_to_string(self.slice_path(slice))
+                .await
+                .map_err(TwitterError::IoError)?;
+            let mut tweets = lines
+                .lines()
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<Tweet>, _>>()
+                .map_err(TwitterError::JsonError)?;
+            // Ids grow with the time tweets are posted
+            tweets.sort_by_key(|tweet| tweet.id);
+            let mut lines = Vec::new();
+            for tweet in tweets.iter().filter(|tweet| seen.insert(tweet.id)) {
+                serde_json::to_writer(&mut lines, tweet).map_err(TwitterError::JsonError)?;
+                lines.push(b'\n');
+            }
+            merged
+                .write_all(&lines)
+                .await
+                .map_err(TwitterError::IoError)?;
+        }
+        merged.sync_data().await.map_err(TwitterError::IoError)?;
+        Ok(seen.len() as u64)
+    }
+
+    fn slice_path(&self, slice: usize) -> PathBuf {
+        self.work_dir.join(format!("slice-{}.jsonl", slice))
+    }
+
+    async fn read_checkpoint(&self) -> Result<Option<Checkpoint>, TwitterError> {
+        let checkpoint = match tokio::fs::read(self.work_dir.join(CHECKPOINT_FILE)).await {
+            Ok(checkpoint) => checkpoint,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(TwitterError::IoError(e)),
+        };
+        let checkpoint: Checkpoint =
+            serde_json::from_slice(&checkpoint).map_err(TwitterError::JsonError)?;
+        if checkpoint.query != self.query.to_string()
+            || checkpoint.start != self.start
+            || checkpoint.end != self.end
+        {
+            return Err(TwitterError::BadQueryError(format!(
+                "The work directory {} holds a backfill of another query or window",
+                self.work_dir.display()
+            )));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Replaces the checkpoint, through a temporary file so an interrupted write
+    /// can not leave a broken checkpoint behind
+    async fn write_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), TwitterError> {
+        let temporary = self.work_dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        let checkpoint = serde_json::to_vec(checkpoint).map_err(TwitterError::JsonError)?;
+        tokio::fs::write(&temporary, checkpoint)
+            .await
+            .map_err(TwitterError::IoError)?;
+        tokio::fs::rename(&temporary, self.work_dir.join(CHECKPOINT_FILE))
+            .await
+            .map_err(TwitterError::IoError)
+    }
+}
+
+/// Spaces out requests made from several tasks
+struct Throttle {
+    min_interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Throttle {
+    fn new(min_interval: Duration) -> Throttle {
+        Throttle {
+            min_interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits until the next request may be sent
+    async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        tokio::time::sleep_until(*next).await;
+        *next = Instant::now() + self.min_interval;
+    }
+}
+
+fn even_slices(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    slices: u32,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let slices = slices.max(1);
+    let length = (end - start) / slices as i32;
+    (0..slices)
+        .map(|i| {
+            let slice_start = start + length * i as i32;
+            let slice_end = if i + 1 == slices {
+                end
+            } else {
+                start + length * (i + 1) as i32
+            };
+            (slice_start, slice_end)
+        })
+        .collect()
+}
+
+/// Groups consecutive counts into slices of at least `tweets_per_slice` tweets, except
+/// for the last slice which gets what is left
+fn slices_by_count(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    counts: &[TweetCount],
+    tweets_per_slice: u64,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut slices = Vec::new();
+    let mut slice_start = start;
+    let mut in_slice = 0;
+    for count in counts {
+        in_slice += count.tweet_count;
+        if in_slice >= tweets_per_slice && count.end > slice_start && count.end < end {
+            slices.push((slice_start, count.end));
+            slice_start = count.end;
+            in_slice = 0;
+        }
+    }
+    slices.push((slice_start, end));
+    slices
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+    use crate::twitter::query_filters::filter::{Exact, Filter, Is};
+    use crate::twitter::query_filters::ids::Id;
+    use crate::twitter::testing::{tweet_count, Mock, MockResponse, MockServer};
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2022, 1, day, 0, 0, 0).unwrap()
+    }
+
+    fn count(start: u32, tweet_count: u64) -> TweetCount {
+        TweetCount {
+            start: day(start),
+            end: day(start + 1),
+            tweet_count,
+        }
+    }
+
+    #[test]
+    fn slices_test() {
+        assert_eq!(
+            even_slices(day(1), day(4), 3),
+            vec![(day(1), day(2)), (day(2), day(3)), (day(3), day(4))]
+        );
+        let counts = [
+            count(1, 5),
+            count(2, 0),
+            count(3, 2),
+            count(4, 4),
+            count(5, 1),
+        ];
+        assert_eq!(
+            slices_by_count(day(1), day(6), &counts, 5),
+            vec![(day(1), day(2)), (day(2), day(5)), (day(5), day(6))]
+        );
+        assert_eq!(
+            slices_by_count(day(1), day(6), &[], 5),
+            vec![(day(1), day(6))]
+        );
+    }
+
+    fn tweet(id: u64) -> serde_json::Value {
+        json!({ "id": id.to_string(), "text": "backfill" })
+    }
+
+    #[tokio::test]
+    async fn backfill_test() {
+        let server = MockServer::start().await;
+        server.mock(
+            Mock::endpoint(&Endpoint::LookupTweetsCountAll, Method::GET)
+                .expect_param("granularity", "day")
+                .respond_with(MockResponse::counts(vec![
+                    tweet_count("2022-01-01T00:00:00Z", "2022-01-02T00:00:00Z", 3),
+                    tweet_count("2022-01-02T00:00:00Z", "2022-01-03T00:00:00Z", 2),
+                ]))
+                .times(1),
+        );
+        // Two pages of the first slice and one page of the second
+        server.mock(
+            Mock::endpoint(&Endpoint::SearchTweetsAll, Method::GET)
+                .expect_param("max_results", "500")
+                .respond_with_fn(|request| {
+                    let start_time = request.query_param("start_time").unwrap_or_default();
+                    if start_time.starts_with("2022-01-02") {
+                        MockResponse::page(vec![tweet(5), tweet(4)], None)
+                    } else if request.query_param("next_token").as_deref() == Some("older") {
+                        // Tweet 2 was returned again on the second page
+                        MockResponse::page(vec![tweet(2), tweet(1)], None)
+                    } else {
+                        MockResponse::page(vec![tweet(3), tweet(2)], Some("older"))
+                    }
+                })
+                .times(3),
+        );
+        // Unique to the run, so concurrent runs do not share a checkpoint
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let work_dir = std::env::temp_dir().join(format!(
+            "twitter_request_backfill_test_{}_{}",
+            std::process::id(),
+            nanos
+        ));
+        let query = GroupList::new(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is));
+        let backfill = Backfill::new(query, day(1), day(3), &work_dir)
+            .slicing(Slicing::ByCount(3))
+            .min_interval(Duration::ZERO)
+            .base_url(server.url());
+        let auth = AuthenticationData::new_bearer_auth("token");
+
+        let summary = backfill.run(&Client::new(), &auth).await.unwrap();
+        assert_eq!(summary.slices, 2);
+        assert_eq!(summary.tweets, 5);
+        server.verify();
+        let ids = |output: &Path| -> Vec<Id> {
+            std::fs::read_to_string(output)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Tweet>(line).unwrap().id)
+                .collect()
+        };
+        let expected: Vec<Id> = (1..=5u64).map(Id::from).collect();
+        assert_eq!(ids(&summary.output), expected);
+
+        // Every slice is done, so running it again only merges the slices
+        let summary = backfill.run(&Client::new(), &auth).await.unwrap();
+        assert_eq!(ids(&summary.output), expected);
+
+        std::fs::remove_dir_all(&work_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn empty_window_test() {
+        let query = GroupList::new(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is));
+        let result = Backfill::new(query, day(2), day(2), "unused")
+            .concurrency(0)
+            .run(
+                &Client::new(),
+                &AuthenticationData::new_bearer_auth("token"),
+            )
+            .await;
+        assert!(matches!(result, Err(TwitterError::BadQueryError(_))));
+    }
+}
@@ -0,0 +1,245 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use futures::stream::{self, StreamExt};
+use http::Method;
+use reqwest::Client;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::query_params::main_options::MainOptions;
+use crate::twitter::request::TwitterRequest;
+use crate::twitter::twitter_objects::response::{Includes, ResponseError};
+use crate::twitter::twitter_objects::tweet::Tweet;
+
+/// The most ids `Endpoint::LookupTweets` accepts in one request
+pub const MAX_IDS_PER_REQUEST: usize = 100;
+
+/// The merged responses of a batched tweet lookup
+#[derive(Debug, Default, Clone)]
+pub struct BatchLookupResult {
+    /// The tweets found, in the order the ids were looked up in
+    pub tweets: Vec<Tweet>,
+    /// The expanded objects of all the responses, without duplicates
+    pub includes: Includes,
+    /// Every partial error returned, including those about expanded objects
+    pub errors: Vec<ResponseError>,
+    /// The looked up ids of tweets that do not exist, e.g. because they were deleted
+    pub not_found: Vec<Id>,
+    /// The looked up ids of tweets the authentication may not see, e.g. because
+    /// their author is protected
+    pub not_authorized: Vec<Id>,
+}
+
+impl BatchLookupResult {
+    fn merge(
+        &mut self,
+        tweets: Vec<Tweet>,
+        includes: Option<Includes>,
+        errors: Vec<ResponseError>,
+    ) {
+        self.tweets.extend(tweets);
+        if let Some(includes) = includes {
+            self.includes.merge(includes);
+        }
+        for error in &errors {
+            // Errors about expanded objects name another parameter, e.g. `referenced_tweets.id`
+            if error.parameter.as_deref() != Some("ids") {
+                continue;
+            }
+            let id = match error.resource_id.as_deref().map(Id::try_from) {
+                Some(Ok(id)) => id,
+                _ => continue,
+            };
+            if error.is_not_found() {
+                self.not_found.push(id);
+            } else if error.is_not_authorized() {
+                self.not_authorized.push(id);
+            }
+        }
+        self.errors.extend(errors);
+    }
+}
+
+/// Looks up any number of tweets by splitting the ids into requests of
+/// `MAX_IDS_PER_REQUEST` ids, of which a bounded number are sent at a time.
+/// When the rate limit is hit the requests wait for it to reset, so looking up
+/// millions of ids can take a long time.
+pub struct BatchTweetLookup {
+    concurrency: usize,
+    options: Vec<MainOptions>,
+    base_url: String,
+}
+
+impl Default for BatchTweetLookup {
+    fn default() -> Self {
+        BatchTweetLookup::new()
+    }
+}
+
+impl BatchTweetLookup {
+    /// Creates a lookup that sends 4 requests at a time
+    pub fn new() -> BatchTweetLookup {
+        BatchTweetLookup {
+            concurrency: 4,
+            options: Vec::new(),
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Sets how many requests are sent at a time, at least 1
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Adds a field or expansion option to every request
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Looks up the tweets with the ids and merges the responses. Repeated ids are
+    /// only looked up once. The ids are read and split into requests as the requests
+    /// are sent, e.g. `IDFilter` or an iterator over the lines of a file will do.
+    pub async fn lookup(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        ids: impl IntoIterator<Item = Id>,
+    ) -> Result<BatchLookupResult, TwitterError> {
+        let mut seen = HashSet::new();
+        let mut ids = ids.into_iter().filter(move |id| seen.insert(*id));
+        let chunks = std::iter::from_fn(move || {
+            let chunk: Vec<Id> = ids.by_ref().take(MAX_IDS_PER_REQUEST).collect();
+            (!chunk.is_empty()).then_some(chunk)
+        });
+        let mut responses = stream::iter(chunks)
+            .map(|chunk| async move {
+                self.request(auth, &chunk)?
+                    .send_typed_waiting::<Vec<Tweet>>(client)
+                    .await
+            })
+            .buffered(self.concurrency);
+
+        let mut result = BatchLookupResult::default();
+        while let Some(response) = responses.next().await {
+            let response = response?;
+            result.merge(
+                response.data.unwrap_or_default(),
+                response.includes,
+                response.errors,
+            );
+        }
+        Ok(result)
+    }
+
+    fn request(
+        &self,
+        auth: &AuthenticationData,
+        ids: &[Id],
+    ) -> Result<TwitterRequest, TwitterError> {
+        let mut builder = TwitterRequest::builder()
+            .set_endpoint(Endpoint::LookupTweets)
+            .set_method(Method::GET)
+            .set_authentication(auth.clone())
+            .set_base_url(&self.base_url);
+        for id in ids {
+            builder = builder.add_id((*id).into());
+        }
+        for option in &self.options {
+            builder = builder.add_option(option.clone());
+        }
+        Ok(builder.build()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::twitter::query_params::expansions::Expansions;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn batch_lookup_test() {
+        let server = MockServer::start().await;
+        // Ids ending in 7 are deleted and ids ending in 9 are protected
+        let lookup = Mock::endpoint(&Endpoint::LookupTweets, Method::GET).respond_with_fn(|request| {
+            let ids = request.query_param("ids").unwrap();
+            let mut data = Vec::new();
+            let mut errors = Vec::new();
+            for id in ids.split(',') {
+                let problem = match id.chars().last() {
+                    Some('7') => "resource-not-found",
+                    Some('9') => "not-authorized-for-resource",
+                    _ => {
+                        data.push(json!({ "id": id, "text": "hello", "author_id": "1" }));
+                        continue;
+                    }
+                };
+                errors.push(json!({
+                    "title": "Error",
+                    "value": id,
+                    "parameter": "ids",
+                    "resource_id": id,
+                    "resource_type": "tweet",
+                    "type": format!("https://api.twitter.com/2/problems/{}", problem),
+                }));
+            }
+            let body = json!({
+                "data": data,
+                "includes": { "users": [{ "id": "1", "name": "Author", "username": "author" }] },
+                "errors": errors,
+            });
+            MockResponse::json(200, &body)
+        });
+        server.mock(lookup.expect_param("expansions", "author_id").times(3));
+        let auth = AuthenticationData::new_bearer_auth("token");
+        // Repeated ids are only looked up once
+        let ids = (1..=250u64).chain(1..=10).map(Id::from);
+
+        let result = BatchTweetLookup::new()
+            .concurrency(2)
+            .add_option(MainOptions::Expansions(vec![Expansions::AuthorId]))
+            .base_url(server.url())
+            .lookup(&Client::new(), &auth, ids)
+            .await
+            .unwrap();
+        assert_eq!(result.tweets.len(), 200);
+        assert_eq!(result.tweets[0].id, Id::from(1));
+        assert_eq!(result.tweets[199].id, Id::from(250));
+        assert_eq!(result.includes.users.len(), 1);
+        assert_eq!(result.errors.len(), 50);
+        assert_eq!(result.not_found.len(), 25);
+        assert_eq!(result.not_found[0], Id::from(7));
+        assert_eq!(result.not_authorized.len(), 25);
+        assert_eq!(result.not_authorized[24], Id::from(249));
+
+        server.verify();
+    }
+}
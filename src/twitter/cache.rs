@@ -0,0 +1,732 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem::Discriminant;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::Method;
+use reqwest::{Request, Response};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::Endpoint;
+use crate::twitter::request::response_from_parts;
+
+/// A response kept in a cache
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct CachedResponse {
+    /// The URL of the request without the query
+    pub url: String,
+    /// The ids the request was about, from the URL's path and `id` or `ids` parameter,
+    /// so the response can be dropped when one of them is written to
+    pub ids: Vec<String>,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// When the response is too old to be reused
+    pub expires: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    /// True if the response is to a request for the URL or about any of the ids
+    fn is_about(&self, url: &str, ids: &[String]) -> bool {
+        self.url == url || self.ids.iter().any(|id| ids.contains(id))
+    }
+
+    /// The URL and ids the response can be invalidated by
+    fn tags(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.url).chain(&self.ids)
+    }
+}
+
+/// Where a `ResponseCache` keeps its responses
+pub trait CacheStore: Send {
+    /// Gets the response stored under the key, None if there is none
+    fn get(&mut self, key: &str) -> Result<Option<CachedResponse>, TwitterError>;
+    /// Stores the response under the key, replacing the one stored before
+    fn put(&mut self, key: &str, response: CachedResponse) -> Result<(), TwitterError>;
+    /// Removes the responses to requests for the URL and those about any of the ids
+    fn invalidate(&mut self, url: &str, ids: &[String]) -> Result<(), TwitterError>;
+}
+
+/// Keeps responses in memory, dropping the least recently used one when it is full
+#[derive(Debug, Clone)]
+pub struct MemoryCache {
+    capacity: usize,
+    responses: HashMap<String, CachedResponse>,
+    /// The keys from least to most recently used
+    order: VecDeque<String>,
+}
+
+impl MemoryCache {
+    /// Creates a cache holding at most `capacity` responses.
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> MemoryCache {
+        assert!(capacity > 0, "capacity must be at least 1");
+        MemoryCache {
+            capacity,
+            responses: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    fn touch(&mut self, key: &str) {
+        self.order.retain(|k| k != key);
+        self.order.push_back(String::from(key));
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get(&mut self, key: &str) -> Result<Option<CachedResponse>, TwitterError> {
+        let response = self.responses.get(key).cloned();
+        if response.is_some() {
+            self.touch(key);
+        }
+        Ok(response)
+    }
+
+    fn put(&mut self, key: &str, response: CachedResponse) -> Result<(), TwitterError> {
+        self.responses.insert(String::from(key), response);
+        self.touch(key);
+        while self.responses.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.responses.remove(&oldest);
+            }
+        }
+        Ok(())
+    }
+
+    fn invalidate(&mut self, url: &str, ids: &[String]) -> Result<(), TwitterError> {
+        self.responses
+            .retain(|_, response| !response.is_about(url, ids));
+        let responses = &self.responses;
+        self.order.retain(|key| responses.contains_key(key));
+        Ok(())
+    }
+}
+
+/// Keeps responses on disk as one JSON file per request in a directory, so they
+/// survive restarts. The files are indexed by URL and id the first time responses are
+/// invalidated, so the directory should not be written to by another process meanwhile.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    /// The keys of the responses stored for each URL and id
+    index: Option<HashMap<String, HashSet<String>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    response: CachedResponse,
+}
+
+impl DiskCache {
+    /// Creates a cache in the directory, which is created when the first response is stored
+    pub fn new(dir: impl Into<PathBuf>) -> DiskCache {
+        DiskCache {
+            dir: dir.into(),
+            index: None,
+        }
+    }
+
+    /// Gets the index, reading it from the files of the directory the first time
+    fn index(&mut self) -> Result<&mut HashMap<String, HashSet<String>>, TwitterError> {
+        if self.index.is_none() {
+            let mut index = HashMap::new();
+            let entries = match std::fs::read_dir(&self.dir) {
+                Ok(entries) => entries.collect::<Result<Vec<_>, _>>(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+                Err(e) => Err(e),
+            }
+            .map_err(TwitterError::IoError)?;
+            for entry in entries {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                let json = std::fs::read(&path).map_err(TwitterError::IoError)?;
+                // Files that can't be read are never used, so they need no invalidating
+                if let Ok(entry) = serde_json::from_slice::<DiskEntry>(&json) {
+                    add_to_index(&mut index, &entry.key, &entry.response);
+                }
+            }
+            self.index = Some(index);
+        }
+        Ok(self.index.get_or_insert_with(HashMap::new))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let hash: String = Sha1::digest(key.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        self.dir.join(format!("{}.json", hash))
+    }
+}
+
+impl CacheStore for DiskCache {
+    fn get(&mut self, key: &str) -> Result<Option<CachedResponse>, TwitterError> {
+        let json = match std::fs::read(self.path(key)) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(TwitterError::IoError(e)),
+        };
+        let entry: DiskEntry = serde_json::from_slice(&json).map_err(TwitterError::JsonError)?;
+        // Two keys with the same hash would be a remarkable find, but not a reason to
+        // answer with the wrong response
+        Ok(Some(entry.response).filter(|_| entry.key == key))
+    }
+
+    /// Writes through a temporary file so an interrupted write can not leave a
+    /// broken entry behind
+    fn put(&mut self, key: &str, response: CachedResponse) -> Result<(), TwitterError> {
+        std::fs::create_dir_all(&self.dir).map_err(TwitterError::IoError)?;
+        let entry = DiskEntry {
+            key: String::from(key),
+            response,
+        };
+        let json = serde_json::to_vec(&entry).map_err(TwitterError::JsonError)?;
+        let path = self.path(key);
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, json).map_err(TwitterError::IoError)?;
+        std::fs::rename(&temporary, &path).map_err(TwitterError::IoError)?;
+        if let Some(index) = &mut self.index {
+            add_to_index(index, key, &entry.response);
+        }
+        Ok(())
+    }
+
+    fn invalidate(&mut self, url: &str, ids: &[String]) -> Result<(), TwitterError> {
+        let index = self.index()?;
+        let keys: HashSet<String> = std::iter::once(url)
+            .chain(ids.iter().map(String::as_str))
+            .filter_map(|tag| index.remove(tag))
+            .flatten()
+            .collect();
+        for key in keys {
+            match std::fs::remove_file(self.path(&key)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(TwitterError::IoError(e))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds the key of the response to the index under the response's URL and ids
+fn add_to_index(
+    index: &mut HashMap<String, HashSet<String>>,
+    key: &str,
+    response: &CachedResponse,
+) {
+    for tag in response.tags() {
+        index
+            .entry(tag.clone())
+            .or_default()
+            .insert(String::from(key));
+    }
+}
+
+/// Reuses the responses to GET requests until they expire, to save on the rate limit
+/// when the same lookups are made over and over. Requests are keyed on their method,
+/// URL and sorted query parameters, without the credentials, so a cache should only be
+/// shared by requests made with the same user's credentials.
+///
+/// Only successful GET responses are cached, and never those of streaming endpoints.
+/// A successful POST, PUT or DELETE drops the cached responses for the same URL and
+/// those about any id it names, such as the lookups of a deleted tweet.
+///
+/// The store is used on a blocking thread, so stores may do blocking I/O.
+///
+/// Give requests a cache with `TwitterRequestBuilder::set_cache`, and skip it for a
+/// request with `set_bypass_cache`.
+pub struct ResponseCache {
+    store: Arc<Mutex<Box<dyn CacheStore>>>,
+    default_ttl: Duration,
+    ttls: Vec<(Discriminant<Endpoint>, Duration)>,
+}
+
+impl ResponseCache {
+    /// Creates a cache that keeps responses in the store for `default_ttl`
+    pub fn new(store: impl CacheStore + 'static, default_ttl: Duration) -> ResponseCache {
+        ResponseCache {
+            store: Arc::new(Mutex::new(Box::new(store))),
+            default_ttl,
+            ttls: Vec::new(),
+        }
+    }
+
+    /// Sets how long the responses of an endpoint are kept, whatever ids it is given,
+    /// e.g. `Endpoint::UserById(0.into())` sets it for every user lookup by id
+    pub fn ttl(mut self, endpoint: &Endpoint, ttl: Duration) -> Self {
+        let endpoint = std::mem::discriminant(endpoint);
+        self.ttls.retain(|(e, _)| *e != endpoint);
+        self.ttls.push((endpoint, ttl));
+        self
+    }
+
+    fn ttl_of(&self, endpoint: &Endpoint) -> Duration {
+        let endpoint = std::mem::discriminant(endpoint);
+        self.ttls
+            .iter()
+            .find(|(e, _)| *e == endpoint)
+            .map(|(_, ttl)| *ttl)
+            .unwrap_or(self.default_ttl)
+    }
+
+    /// Runs `f` on the store on a blocking thread, keeping its I/O and lock off the
+    /// async ones
+    async fn with_store<T, F>(&self, f: F) -> Result<T, TwitterError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn CacheStore) -> Result<T, TwitterError> + Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || f(store.lock().unwrap().as_mut()))
+            .await
+            .map_err(|e| TwitterError::CacheError(e.to_string()))?
+    }
+
+    /// Gets the cached response to the request, None if there is no fresh one
+    pub(crate) async fn lookup(&self, key: &CacheKey) -> Result<Option<Response>, TwitterError> {
+        if key.method != Method::GET {
+            return Ok(None);
+        }
+        let cache_key = key.key.clone();
+        let cached = self.with_store(move |store| store.get(&cache_key)).await?;
+        match cached {
+            Some(cached) if cached.expires > Utc::now() => Ok(Some(
+                response_from_parts(cached.status, &cached.headers, cached.body.into_bytes())
+                    .map_err(|e| TwitterError::CacheError(e.to_string()))?,
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// Caches the response to a GET request, or drops the responses a successful
+    /// write made stale. Returns the response for the caller to read.
+    pub(crate) async fn update(
+        &self,
+        endpoint: &Endpoint,
+        key: CacheKey,
+        response: Response,
+    ) -> Result<Response, TwitterError> {
+        if !response.status().is_success() {
+            return Ok(response);
+        }
+        if key.method != Method::GET {
+            self.with_store(move |store| store.invalidate(&key.url, &key.ids))
+                .await?;
+            return Ok(response);
+        }
+        let status = response.status().as_u16();
+        let headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| !matches!(name.as_str(), "content-length" | "transfer-encoding"))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let bytes = response.bytes().await.map_err(TwitterError::RequestError)?;
+        if let Ok(body) = std::str::from_utf8(&bytes) {
+            let expires = Utc::now()
+                + chrono::Duration::from_std(self.ttl_of(endpoint))
+                    .unwrap_or(chrono::Duration::MAX);
+            let cached = CachedResponse {
+                url: key.url.clone(),
+                ids: key.ids.clone(),
+                status,
+                headers: headers.clone(),
+                body: String::from(body),
+                expires,
+            };
+            self.with_store(move |store| store.put(&key.key, cached))
+                .await?;
+        }
+        response_from_parts(status, &headers, bytes.to_vec())
+            .map_err(|e| TwitterError::CacheError(e.to_string()))
+    }
+}
+
+/// What a request is cached under, and the ids it is about
+pub(crate) struct CacheKey {
+    method: Method,
+    url: String,
+    ids: Vec<String>,
+    key: String,
+}
+
+impl CacheKey {
+    pub(crate) fn of(request: &Request) -> CacheKey {
+        let mut url = request.url().clone();
+        let mut query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        query.sort();
+        url.set_query(None);
+        let url = String::from(url.as_str());
+        // The path's ids follow the API version, e.g. /2/users/12/tweets. Other query
+        // parameters such as `max_results` can be numbers without being ids.
+        let segments: Vec<&str> = url.split('/').collect();
+        let version = segments.iter().position(|segment| *segment == "2");
+        let mut ids: Vec<String> = segments[version.map_or(0, |v| v + 1)..]
+            .iter()
+            .copied()
+            .chain(
+                query
+                    .iter()
+                    .filter(|(name, _)| name == "id" || name == "ids")
+                    .flat_map(|(_, value)| value.split(',')),
+            )
+            .filter(|part| is_id(part))
+            .map(String::from)
+            .collect();
+        // The ids a write is about can also be in its body, e.g. the tweet to like
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            if let Ok(serde_json::Value::Object(fields)) = serde_json::from_slice(body) {
+                ids.extend(
+                    fields
+                        .values()
+                        .filter_map(|value| value.as_str())
+                        .filter(|value| is_id(value))
+                        .map(String::from),
+                );
+            }
+        }
+        let params: Vec<String> = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        CacheKey {
+            key: format!("{} {}?{}", request.method(), url, params.join("&")),
+            method: request.method().clone(),
+            url,
+            ids,
+        }
+    }
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::endpoints::AuthenticationData;
+    use crate::twitter::request::TwitterRequest;
+    use crate::twitter::testing::{tweet, Mock, MockResponse, MockServer};
+    use reqwest::Client;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn request(
+        server: &MockServer,
+        endpoint: Endpoint,
+        method: Method,
+        cache: &Arc<ResponseCache>,
+    ) -> crate::twitter::request::TwitterRequestBuilder {
+        let authentication = match method {
+            Method::GET => AuthenticationData::new_bearer_auth("secret-token"),
+            _ => AuthenticationData::new_oauth_auth("key", "secret", "token", "token-secret"),
+        };
+        TwitterRequest::builder()
+            .set_endpoint(endpoint)
+            .set_method(method)
+            .set_authentication(authentication)
+            .set_base_url(server.url())
+            .set_cache(cache.clone())
+    }
+
+    async fn body(request: crate::twitter::request::TwitterRequestBuilder) -> String {
+        let response = request
+            .build()
+            .unwrap()
+            .send_request(&Client::new())
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        response.text().await.unwrap()
+    }
+
+    fn lookup_mock(text: &str) -> Mock {
+        Mock::endpoint(&Endpoint::LookupTweet(20.into()), Method::GET)
+            .respond_with(MockResponse::data(tweet("20", text)))
+            .times(1)
+    }
+
+    #[tokio::test]
+    async fn repeated_request_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(lookup_mock("first"))
+            .mock(lookup_mock("second"));
+        let cache = Arc::new(ResponseCache::new(
+            MemoryCache::new(10),
+            Duration::from_secs(60),
+        ));
+        let lookup = || {
+            request(
+                &server,
+                Endpoint::LookupTweet(20.into()),
+                Method::GET,
+                &cache,
+            )
+        };
+
+        assert!(body(lookup()).await.contains("first"));
+        assert!(body(lookup()).await.contains("first"));
+        assert_eq!(server.received().len(), 1);
+
+        // Bypassing asks the server again, and the new response is cached
+        assert!(body(lookup().set_bypass_cache(true))
+            .await
+            .contains("second"));
+        assert!(body(lookup()).await.contains("second"));
+        assert_eq!(server.received().len(), 2);
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn ttl_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(lookup_mock("first"))
+            .mock(lookup_mock("second"));
+        server.mock(
+            Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+                .respond_with(MockResponse::page(vec![tweet("1", "found")], None))
+                .times(1),
+        );
+        let cache = Arc::new(
+            ResponseCache::new(MemoryCache::new(10), Duration::from_secs(60))
+                .ttl(&Endpoint::LookupTweet(0.into()), Duration::ZERO),
+        );
+        let lookup = || {
+            request(
+                &server,
+                Endpoint::LookupTweet(20.into()),
+                Method::GET,
+                &cache,
+            )
+        };
+        let search = || {
+            request(&server, Endpoint::SearchTweetsRecent, Method::GET, &cache).add_and_filter(
+                crate::twitter::query_filters::filter::Filter::From(
+                    String::from("rustlang"),
+                    true.into(),
+                ),
+            )
+        };
+
+        assert!(body(lookup()).await.contains("first"));
+        assert!(body(lookup()).await.contains("second"));
+        assert!(body(search()).await.contains("found"));
+        assert!(body(search()).await.contains("found"));
+        assert_eq!(server.received().len(), 3);
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn write_invalidation_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(lookup_mock("first"))
+            .mock(lookup_mock("second"));
+        server.mock(
+            Mock::endpoint(&Endpoint::LookupTweets, Method::GET)
+                .respond_with(MockResponse::lookup(vec![tweet("20", "first")], &[]))
+                .times(2),
+        );
+        server.mock(
+            Mock::endpoint(&Endpoint::LookupTweet(20.into()), Method::DELETE)
+                .respond_with(MockResponse::data(json!({"deleted": true})))
+                .times(2),
+        );
+        let cache = Arc::new(ResponseCache::new(
+            MemoryCache::new(10),
+            Duration::from_secs(60),
+        ));
+        let lookup = || {
+            request(
+                &server,
+                Endpoint::LookupTweet(20.into()),
+                Method::GET,
+                &cache,
+            )
+        };
+        let lookups = || {
+            request(&server, Endpoint::LookupTweets, Method::GET, &cache)
+                .add_id(20)
+                .add_id(21)
+        };
+        let delete = || {
+            request(
+                &server,
+                Endpoint::LookupTweet(20.into()),
+                Method::DELETE,
+                &cache,
+            )
+        };
+
+        body(lookup()).await;
+        body(lookups()).await;
+        body(lookups()).await;
+        assert_eq!(server.received().len(), 2);
+
+        // Deleting the tweet drops both lookups, and is itself never cached
+        body(delete()).await;
+        body(delete()).await;
+        assert!(body(lookup()).await.contains("second"));
+        body(lookups()).await;
+        assert_eq!(server.received().len(), 6);
+        server.verify();
+    }
+
+    fn cached(url: &str, ids: &[&str]) -> CachedResponse {
+        CachedResponse {
+            url: String::from(url),
+            ids: ids.iter().map(|id| String::from(*id)).collect(),
+            status: 200,
+            headers: vec![(
+                String::from("content-type"),
+                String::from("application/json"),
+            )],
+            body: String::from("{}"),
+            expires: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn memory_cache_test() {
+        let mut cache = MemoryCache::new(2);
+        cache.put("a", cached("a", &["1"])).unwrap();
+        cache.put("b", cached("b", &["2"])).unwrap();
+        // Using "a" makes "b" the least recently used
+        assert!(cache.get("a").unwrap().is_some());
+        cache.put("c", cached("c", &["3"])).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").unwrap().is_none());
+
+        cache.invalidate("x", &[String::from("1")]).unwrap();
+        assert!(cache.get("a").unwrap().is_none());
+        assert_eq!(cache.get("c").unwrap().unwrap().url, "c");
+    }
+
+    #[test]
+    fn disk_cache_test() {
+        // Unique to the run, so concurrent runs do not share the cache
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "twitter_request_disk_cache_test_{}_{}",
+            std::process::id(),
+            nanos
+        ));
+        let mut cache = DiskCache::new(&dir);
+        assert!(cache.get("a").unwrap().is_none());
+        cache.invalidate("a", &[]).unwrap();
+
+        let a = cached("a", &["1"]);
+        cache.put("a", a.clone()).unwrap();
+        cache.put("b", cached("b", &["2"])).unwrap();
+        // A new cache in the same directory sees what the first stored
+        let mut reopened = DiskCache::new(&dir);
+        assert_eq!(reopened.get("a").unwrap(), Some(a));
+        reopened.invalidate("a", &[]).unwrap();
+        assert!(cache.get("a").unwrap().is_none());
+        assert!(cache.get("b").unwrap().is_some());
+
+        // Responses stored after the index was read are indexed too
+        reopened.put("c", cached("c", &["2", "3"])).unwrap();
+        reopened.invalidate("x", &[String::from("2")]).unwrap();
+        assert!(cache.get("b").unwrap().is_none());
+        assert!(cache.get("c").unwrap().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn key_ids_test() {
+        let request = Client::new()
+            .get("https://api.twitter.com/2/users/12/tweets?max_results=100&ids=3,4&id=5")
+            .build()
+            .unwrap();
+        let key = CacheKey::of(&request);
+        assert_eq!(key.ids, vec!["12", "5", "3", "4"]);
+        assert_eq!(key.url, "https://api.twitter.com/2/users/12/tweets");
+    }
+
+    #[tokio::test]
+    async fn stream_test() {
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        // A stream that sends one line and then stays open
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let line = format!("{}\r\n", json!({ "data": tweet("1", "one") }));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                 transfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+                line.len(),
+                line
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket
+        });
+        let cache = Arc::new(ResponseCache::new(
+            MemoryCache::new(10),
+            Duration::from_secs(60),
+        ));
+        let client = Client::new();
+        let stream = TwitterRequest::builder()
+            .set_endpoint(Endpoint::StreamTweets)
+            .set_method(Method::GET)
+            .add_bearer_token("token")
+            .set_base_url(&url)
+            .set_cache(cache)
+            .build()
+            .unwrap()
+            .send_streaming::<serde_json::Value>(&client);
+        let first = tokio::time::timeout(Duration::from_secs(5), async {
+            Box::pin(stream.await.unwrap()).next().await
+        })
+        .await
+        .expect("the cache waited for the stream to end");
+        assert_eq!(first.unwrap().unwrap()["data"]["text"], "one");
+        drop(server);
+    }
+}
@@ -0,0 +1,412 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TwitterError;
+use crate::twitter::request::response_from_parts;
+
+/// What replaces the authorization header and the credentials in recordings
+const SCRUBBED: &str = "[scrubbed]";
+
+/// How a `Cassette` treats requests
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum CassetteMode {
+    /// Sends every request and records it, replacing what the cassette held before
+    Record,
+    /// Answers requests from the cassette and fails on requests it has not recorded,
+    /// so nothing is sent. Meant for CI.
+    ReplayOnly,
+    /// Answers the requests the cassette has recorded, and sends and records the rest
+    RecordNewEpisodes,
+}
+
+/// A file of recorded request/response pairs that requests are replayed from. Requests
+/// are matched on their method, URL and query parameters, with the order of the
+/// parameters ignored. The authorization header and the tokens of the credentials are
+/// scrubbed before anything is written, so cassettes can be committed.
+///
+/// Give requests a cassette with `TwitterRequestBuilder::set_cassette`. Responses are
+/// read in full before they are recorded, so streams are only recorded once they end.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+    /// Set when the interaction has been replayed, so repeated requests are answered
+    /// by the interactions in the order they were recorded
+    #[serde(skip)]
+    replayed: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct RecordedRequest {
+    method: String,
+    /// The URL without the query
+    url: String,
+    /// The query parameters, sorted
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    /// True if the body was not UTF-8, in which case it is base64 encoded
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+impl Cassette {
+    /// Opens the cassette at `path`. In `Record` mode the cassette starts out empty,
+    /// and in `ReplayOnly` mode the file must exist.
+    pub fn open(path: impl Into<PathBuf>, mode: CassetteMode) -> Result<Cassette, TwitterError> {
+        let path = path.into();
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::ReplayOnly => read(&path)?,
+            CassetteMode::RecordNewEpisodes if path.exists() => read(&path)?,
+            CassetteMode::RecordNewEpisodes => Vec::new(),
+        };
+        Ok(Cassette {
+            path,
+            mode,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// The number of recorded interactions
+    pub fn len(&self) -> usize {
+        self.interactions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replays the response to the request, or sends and records it, depending on the
+    /// mode. `secrets` are scrubbed from what is recorded.
+    pub(crate) async fn send(
+        &self,
+        client: &Client,
+        request: Request,
+        secrets: &[&str],
+    ) -> Result<Response, TwitterError> {
+        let recorded = record_request(&request, secrets);
+        if self.mode != CassetteMode::Record {
+            if let Some(response) = self.replay(&recorded) {
+                return response.into_response();
+            }
+            if self.mode == CassetteMode::ReplayOnly {
+                return Err(TwitterError::CassetteError(format!(
+                    "{} has no recording of {} {}",
+                    self.path.display(),
+                    recorded.method,
+                    recorded.url
+                )));
+            }
+        }
+        let response = client
+            .execute(request)
+            .await
+            .map_err(TwitterError::RequestError)?;
+        let status = response.status();
+        let headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| {
+                !matches!(
+                    name.as_str(),
+                    "content-length" | "transfer-encoding" | "connection" | "set-cookie"
+                )
+            })
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                (name.to_string(), scrub(&value, secrets))
+            })
+            .collect();
+        let bytes = response.bytes().await.map_err(TwitterError::RequestError)?;
+        let (body, base64) = match std::str::from_utf8(&bytes) {
+            Ok(text) => (scrub(text, secrets), false),
+            Err(_) => (
+                base64::engine::general_purpose::STANDARD.encode(&bytes),
+                true,
+            ),
+        };
+        let recorded_response = RecordedResponse {
+            status: status.as_u16(),
+            headers,
+            body,
+            base64,
+        };
+        let interactions = {
+            let mut interactions = self.interactions.lock().unwrap();
+            interactions.push(Interaction {
+                request: recorded,
+                response: recorded_response.clone(),
+                replayed: true,
+            });
+            interactions.clone()
+        };
+        write(&self.path, &interactions)?;
+        // The caller gets the scrubbed response, the same one a replay would give
+        recorded_response.into_response()
+    }
+
+    /// Finds the first interaction for the request that has not been replayed yet,
+    /// or else the last one for the request
+    fn replay(&self, request: &RecordedRequest) -> Option<RecordedResponse> {
+        let mut interactions = self.interactions.lock().unwrap();
+        let mut matching = interactions
+            .iter_mut()
+            .filter(|interaction| interaction.request.key() == request.key())
+            .peekable();
+        let mut last = None;
+        while let Some(interaction) = matching.next() {
+            if !interaction.replayed || matching.peek().is_none() {
+                interaction.replayed = true;
+                last = Some(interaction.response.clone());
+                break;
+            }
+        }
+        last
+    }
+}
+
+impl RecordedRequest {
+    fn key(&self) -> (&str, &str, &[(String, String)]) {
+        (&self.method, &self.url, &self.query)
+    }
+}
+
+impl RecordedResponse {
+    fn into_response(self) -> Result<Response, TwitterError> {
+        let body = if self.base64 {
+            base64::engine::general_purpose::STANDARD
+                .decode(&self.body)
+                .map_err(|e| TwitterError::CassetteError(e.to_string()))?
+        } else {
+            self.body.into_bytes()
+        };
+        response_from_parts(self.status, &self.headers, body)
+            .map_err(|e| TwitterError::CassetteError(e.to_string()))
+    }
+}
+
+fn record_request(request: &Request, secrets: &[&str]) -> RecordedRequest {
+    let mut url = request.url().clone();
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| (name.into_owned(), scrub(&value, secrets)))
+        .collect();
+    query.sort();
+    url.set_query(None);
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == reqwest::header::AUTHORIZATION {
+                String::from(SCRUBBED)
+            } else {
+                scrub(&String::from_utf8_lossy(value.as_bytes()), secrets)
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|bytes| scrub(&String::from_utf8_lossy(bytes), secrets));
+    RecordedRequest {
+        method: request.method().to_string(),
+        url: scrub(url.as_str(), secrets),
+        query,
+        headers,
+        body,
+    }
+}
+
+/// Replaces every secret in the text
+fn scrub(text: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(String::from(text), |text, secret| {
+            text.replace(secret, SCRUBBED)
+        })
+}
+
+fn read(path: &Path) -> Result<Vec<Interaction>, TwitterError> {
+    let file = std::fs::File::open(path).map_err(TwitterError::IoError)?;
+    serde_json::from_reader(std::io::BufReader::new(file)).map_err(TwitterError::JsonError)
+}
+
+/// Writes the cassette to a temporary file first, so a crash can't leave half a cassette
+fn write(path: &Path, interactions: &[Interaction]) -> Result<(), TwitterError> {
+    let json = serde_json::to_vec_pretty(interactions).map_err(TwitterError::JsonError)?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, json).map_err(TwitterError::IoError)?;
+    std::fs::rename(&tmp, path).map_err(TwitterError::IoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::endpoints::{AuthenticationData, Endpoint};
+    use crate::twitter::query_filters::filter::Filter;
+    use crate::twitter::request::TwitterRequest;
+    use crate::twitter::testing::{tweet, Mock, MockResponse, MockServer};
+    use crate::twitter::twitter_objects::tweet::Tweet;
+    use http::Method;
+    use std::sync::Arc;
+
+    fn search(base_url: &str, from: &str, cassette: &Arc<Cassette>) -> TwitterRequest {
+        TwitterRequest::builder()
+            .set_endpoint(Endpoint::SearchTweetsRecent)
+            .set_method(Method::GET)
+            .add_and_filter(Filter::From(String::from(from), true.into()))
+            .add_option(
+                crate::twitter::query_params::main_options::MainOptions::MaxResults(10.into()),
+            )
+            .set_authentication(AuthenticationData::new_bearer_auth("secret-token"))
+            .set_base_url(base_url)
+            .set_cassette(cassette.clone())
+            .build()
+            .unwrap()
+    }
+
+    async fn texts(request: TwitterRequest) -> Result<Vec<String>, TwitterError> {
+        let tweets = request
+            .send_typed::<Vec<Tweet>>(&Client::new())
+            .await?
+            .into_data()?;
+        Ok(tweets.into_iter().map(|tweet| tweet.text).collect())
+    }
+
+    #[tokio::test]
+    async fn record_and_replay_test() {
+        let path = std::env::temp_dir().join("twitter_request_cassette_test.json");
+        let _ = std::fs::remove_file(&path);
+        let server = MockServer::start().await;
+        let url = server.url().to_string();
+        server.mock(
+            Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+                .expect_param("query", "(from:rustlang)")
+                .respond_with(MockResponse::page(
+                    vec![tweet("1", "echoing secret-token")],
+                    None,
+                ))
+                .times(1),
+        );
+
+        let cassette = Arc::new(Cassette::open(&path, CassetteMode::Record).unwrap());
+        assert_eq!(
+            texts(search(&url, "rustlang", &cassette)).await.unwrap(),
+            ["echoing [scrubbed]"]
+        );
+        server.verify();
+        let recorded = std::fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains("secret-token"));
+        let interactions: Vec<Interaction> = serde_json::from_str(&recorded).unwrap();
+        assert!(interactions[0]
+            .request
+            .headers
+            .contains(&(String::from("authorization"), String::from(SCRUBBED))));
+        assert_eq!(
+            interactions[0].request.query,
+            [
+                (String::from("max_results"), String::from("10")),
+                (String::from("query"), String::from("(from:rustlang)")),
+            ]
+        );
+        drop(server);
+
+        // Replaying sends nothing, so the server being gone does not matter
+        let cassette = Arc::new(Cassette::open(&path, CassetteMode::ReplayOnly).unwrap());
+        assert_eq!(cassette.len(), 1);
+        assert_eq!(
+            texts(search(&url, "rustlang", &cassette)).await.unwrap(),
+            ["echoing [scrubbed]"]
+        );
+        assert!(matches!(
+            texts(search(&url, "someone_else", &cassette)).await,
+            Err(TwitterError::CassetteError(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn record_new_episodes_test() {
+        let path = std::env::temp_dir().join("twitter_request_cassette_episodes_test.json");
+        let _ = std::fs::remove_file(&path);
+        let server = MockServer::start().await;
+        let url = server.url().to_string();
+        for from in ["a", "b"] {
+            server.mock(
+                Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+                    .expect_param("query", &format!("(from:{})", from))
+                    .respond_with(MockResponse::page(vec![tweet("1", from)], None))
+                    .times(1),
+            );
+        }
+
+        let cassette = Arc::new(Cassette::open(&path, CassetteMode::RecordNewEpisodes).unwrap());
+        assert_eq!(texts(search(&url, "a", &cassette)).await.unwrap(), ["a"]);
+        let cassette = Arc::new(Cassette::open(&path, CassetteMode::RecordNewEpisodes).unwrap());
+        // The first search is replayed and only the new one reaches the server
+        assert_eq!(texts(search(&url, "a", &cassette)).await.unwrap(), ["a"]);
+        assert_eq!(texts(search(&url, "b", &cassette)).await.unwrap(), ["b"]);
+        server.verify();
+        assert_eq!(server.received().len(), 2);
+        assert_eq!(cassette.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn normalized_query_test() {
+        let client = Client::new();
+        let request = |url: &str| client.get(url).bearer_auth("secret-token").build().unwrap();
+        let first = record_request(&request("http://x/2/tweets?b=2&a=1"), &["secret-token"]);
+        let second = record_request(&request("http://x/2/tweets?a=1&b=2"), &["secret-token"]);
+        assert_eq!(first.key(), second.key());
+        assert_eq!(first.url, "http://x/2/tweets");
+        assert_eq!(
+            first.headers,
+            [(String::from("authorization"), String::from(SCRUBBED))]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use futures::stream::{Stream, StreamExt};
+use http::Method;
+use reqwest::Client;
+use serde_json::json;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, TWITTER_URL};
+use crate::twitter::query_filters::ids::IDFilter;
+use crate::twitter::request::{check_response, json_lines, TwitterRequest};
+use crate::twitter::twitter_objects::compliance::{
+    ComplianceJob, ComplianceJobStatus, ComplianceJobType, ComplianceResult,
+};
+use crate::twitter::Endpoint;
+
+/// How long to wait between checks of whether a job is done, twitter suggests
+/// jobs can take several minutes for large uploads
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How long to wait for a job to complete before giving up on it
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Runs a batch compliance job: the job is created, the ids are uploaded to the job's
+/// signed upload URL, the job is polled until it is complete and the results are
+/// streamed from its download URL as they are read.
+///
+/// Compliance jobs need bearer token authentication.
+pub struct ComplianceJobRunner {
+    job_type: ComplianceJobType,
+    name: Option<String>,
+    poll_interval: Duration,
+    timeout: Duration,
+    base_url: String,
+}
+
+impl ComplianceJobRunner {
+    pub fn new(job_type: ComplianceJobType) -> ComplianceJobRunner {
+        ComplianceJobRunner {
+            job_type,
+            name: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Sets the name of the job, to tell it apart when listing jobs
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    /// Sets how long to wait between checks of the job status
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets how long to wait for the job to complete, after which running it fails
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Runs the job for the ids and returns a stream of the results. Errors creating the
+    /// job, uploading the ids or waiting for the job are returned before any result, errors
+    /// reading the results are returned in the stream.
+    pub async fn run(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        ids: &IDFilter,
+    ) -> Result<impl Stream<Item = Result<ComplianceResult, TwitterError>>, TwitterError> {
+        if ids.is_empty() {
+            return Err(TwitterError::BadQueryError(String::from(
+                "No ids provided, cannot run a compliance job",
+            )));
+        }
+        let mut body = json!({ "type": self.job_type });
+        if let Some(name) = &self.name {
+            body["name"] = json!(name);
+        }
+        let job: ComplianceJob = TwitterRequest::builder()
+            .set_endpoint(Endpoint::ComplianceJobs)
+            .set_method(Method::POST)
+            .set_authentication(auth.clone())
+            .set_base_url(&self.base_url)
+            .set_body(body)
+            .build()?
+            .send_typed(client)
+            .await?
+            .into_data()?;
+
+        // The upload url is signed, so it takes no authentication
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        let upload = client
+            .put(&job.upload_url)
+            .header(http::header::CONTENT_TYPE, "text/plain")
+            .body(ids.join("\n"))
+            .send()
+            .await
+            .map_err(TwitterError::RequestError)?;
+        check_response(upload).await?;
+
+        let job = self.wait_for(client, auth, job).await?;
+        let download = client
+            .get(&job.download_url)
+            .send()
+            .await
+            .map_err(TwitterError::RequestError)?;
+        let download = check_response(download).await?;
+        Ok(json_lines(download.bytes_stream().boxed()))
+    }
+
+    /// Polls the job until it is complete, or the timeout has passed
+    async fn wait_for(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        mut job: ComplianceJob,
+    ) -> Result<ComplianceJob, TwitterError> {
+        let started = Instant::now();
+        loop {
+            match job.status {
+                ComplianceJobStatus::Complete => return Ok(job),
+                ComplianceJobStatus::Failed | ComplianceJobStatus::Expired => {
+                    return Err(TwitterError::ComplianceJobError(format!(
+                        "job {} {}",
+                        job.id, job.status
+                    )))
+                }
+                ComplianceJobStatus::Created | ComplianceJobStatus::InProgress => {}
+            }
+            if started.elapsed() >= self.timeout {
+                return Err(TwitterError::ComplianceJobError(format!(
+                    "job {} was not complete after {} seconds",
+                    job.id,
+                    self.timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+            job = TwitterRequest::builder()
+                .set_endpoint(Endpoint::ComplianceJob(job.id))
+                .set_method(Method::GET)
+                .set_authentication(auth.clone())
+                .set_base_url(&self.base_url)
+                .build()?
+                .send_typed(client)
+                .await?
+                .into_data()?;
+        }
+    }
+}
+
+/// Runs a compliance job of `job_type` for the ids with the default settings of
+/// `ComplianceJobRunner`, returning a stream of the results
+pub async fn run_compliance_job(
+    client: &Client,
+    auth: &AuthenticationData,
+    job_type: ComplianceJobType,
+    ids: &IDFilter,
+) -> Result<impl Stream<Item = Result<ComplianceResult, TwitterError>>, TwitterError> {
+    ComplianceJobRunner::new(job_type)
+        .run(client, auth, ids)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::query_filters::ids::Id;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    fn job(server_url: &str, status: &str) -> MockResponse {
+        MockResponse::data(json!({
+            "id": "1382081613278814209",
+            "type": "tweets",
+            "name": "deletions",
+            "upload_url": format!("{}/upload", server_url),
+            "upload_expires_at": "2021-04-13T21:28:32.000Z",
+            "download_url": format!("{}/download", server_url),
+            "download_expires_at": "2021-04-20T21:13:32.000Z",
+            "status": status,
+            "created_at": "2021-04-13T21:13:32.000Z",
+            "resumable": false,
+        }))
+    }
+
+    /// A successful response with a body that is not JSON
+    fn text(body: &str) -> MockResponse {
+        MockResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: String::from(body),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_compliance_job_test() {
+        let server = MockServer::start().await;
+        let url = server.url();
+        let job_endpoint = Endpoint::ComplianceJob(1382081613278814209.into());
+        // Create, upload, two polls and the download
+        server
+            .mock(
+                Mock::endpoint(&Endpoint::ComplianceJobs, Method::POST)
+                    .respond_with(job(url, "created"))
+                    .times(1),
+            )
+            .mock(
+                Mock::new(Method::PUT, "/upload")
+                    .respond_with(text(""))
+                    .times(1),
+            )
+            .mock(
+                Mock::endpoint(&job_endpoint, Method::GET)
+                    .respond_with(job(url, "in_progress"))
+                    .times(1),
+            )
+            .mock(
+                Mock::endpoint(&job_endpoint, Method::GET)
+                    .respond_with(job(url, "complete"))
+                    .times(1),
+            )
+            .mock(
+                Mock::new(Method::GET, "/download")
+                    .respond_with(text(concat!(
+                        r#"{"id":"1","action":"delete","created_at":"2021-04-01T10:00:00.000Z","redacted_at":"2021-04-02T10:00:00.000Z","reason":"deleted"}"#,
+                        "\n",
+                        r#"{"id":"3","action":"scrub_geo","created_at":"2021-04-01T11:00:00.000Z"}"#,
+                    )))
+                    .times(1),
+            );
+        let auth = AuthenticationData::new_bearer_auth("token");
+        let ids = IDFilter::from_iterator([1u64, 2, 3]);
+
+        let results: Vec<ComplianceResult> = ComplianceJobRunner::new(ComplianceJobType::Tweets)
+            .name("deletions")
+            .poll_interval(Duration::ZERO)
+            .base_url(url)
+            .run(&Client::new(), &auth, &ids)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, Id::from(1));
+        assert_eq!(results[0].reason.as_deref(), Some("deleted"));
+        assert_eq!(results[1].action, "scrub_geo");
+        assert_eq!(results[1].redacted_at, None);
+
+        server.verify();
+        let received = server.received();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&received[0].body).unwrap(),
+            json!({ "type": "tweets", "name": "deletions" })
+        );
+        assert_eq!(received[0].header("authorization"), Some("Bearer token"));
+        assert_eq!(received[1].body, b"1\n2\n3");
+        assert_eq!(received[1].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn failed_compliance_job_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(
+                Mock::endpoint(&Endpoint::ComplianceJobs, Method::POST)
+                    .respond_with(job(server.url(), "failed"))
+                    .times(1),
+            )
+            .mock(Mock::any().respond_with(text("")).times(1));
+        let auth = AuthenticationData::new_bearer_auth("token");
+        let result = ComplianceJobRunner::new(ComplianceJobType::Users)
+            .base_url(server.url())
+            .run(&Client::new(), &auth, &IDFilter::new(1))
+            .await;
+        assert!(matches!(result, Err(TwitterError::ComplianceJobError(_))));
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn compliance_job_timeout_test() {
+        let server = MockServer::start().await;
+        // Create and upload, then the job is given up on without polling it
+        server
+            .mock(
+                Mock::endpoint(&Endpoint::ComplianceJobs, Method::POST)
+                    .respond_with(job(server.url(), "in_progress"))
+                    .times(1),
+            )
+            .mock(
+                Mock::new(Method::PUT, "/upload")
+                    .respond_with(text(""))
+                    .times(1),
+            );
+        let auth = AuthenticationData::new_bearer_auth("token");
+        let result = ComplianceJobRunner::new(ComplianceJobType::Tweets)
+            .timeout(Duration::ZERO)
+            .base_url(server.url())
+            .run(&Client::new(), &auth, &IDFilter::new(1))
+            .await;
+        assert!(matches!(result, Err(TwitterError::ComplianceJobError(_))));
+        server.verify();
+    }
+}
@@ -0,0 +1,535 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+use http::Method;
+use serde::Deserialize;
+
+use crate::errors::TwitterError;
+use crate::twitter::access_level::AccessLevel;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::query_params::expansions::Expansions;
+use crate::twitter::query_params::main_options::MainOptions;
+use crate::twitter::request::{TwitterRequest, TwitterRequestBuilder};
+use crate::twitter::twitter_objects::{tweet::TweetField, user::UserField};
+
+/// The environment variable holding the path of the config file to load
+pub const CONFIG_PATH_VAR: &str = "TWITTER_REQUEST_CONFIG";
+/// The environment variable naming the profile to use
+pub const PROFILE_VAR: &str = "TWITTER_PROFILE";
+/// The profile used when no profile is named anywhere
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A value that is never shown by `Debug`, for tokens and secrets
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: &str) -> Secret {
+        Secret(String::from(secret))
+    }
+
+    /// Gets the secret itself
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+/// The credentials and settings of one app, read from a profile of the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub bearer_token: Option<Secret>,
+    pub consumer_key: Option<Secret>,
+    pub consumer_secret: Option<Secret>,
+    pub access_token: Option<Secret>,
+    pub access_token_secret: Option<Secret>,
+    pub oauth2_user_token: Option<Secret>,
+    /// The URL of the API, `TWITTER_URL` unless the profile points at another server
+    pub base_url: String,
+    /// The access tier of the app, None if it is not known
+    pub access_level: Option<AccessLevel>,
+    /// Tweet fields added to every request that accepts them
+    pub tweet_fields: Vec<TweetField>,
+    /// User fields added to every request that accepts them
+    pub user_fields: Vec<UserField>,
+    /// Expansions added to every request that accepts them
+    pub expansions: Vec<Expansions>,
+}
+
+/// A config file of named profiles, e.g.
+///
+/// ```toml
+/// default_profile = "research"
+///
+/// [profiles.research]
+/// bearer_token = "AAAA..."
+/// access_level = "academic"
+/// tweet_fields = ["author_id", "created_at"]
+/// expansions = ["author_id"]
+///
+/// [profiles.bot]
+/// consumer_key = "..."
+/// consumer_secret = "..."
+/// access_token = "..."
+/// access_token_secret = "..."
+/// access_level = "basic"
+/// ```
+///
+/// The credentials of a profile can be overridden with the environment variables
+/// `BEARER_TOKEN`, `CONSUMER_KEY`, `CONSUMER_SECRET`, `ACCESS_TOKEN`, `ACCESS_TOKEN_SECRET`
+/// and `OAUTH2_USER_TOKEN`, and its settings with `TWITTER_BASE_URL` and
+/// `TWITTER_ACCESS_LEVEL`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Config {
+    default_profile: Option<String>,
+    profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, RawProfile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawProfile {
+    bearer_token: Option<String>,
+    consumer_key: Option<String>,
+    consumer_secret: Option<String>,
+    access_token: Option<String>,
+    access_token_secret: Option<String>,
+    oauth2_user_token: Option<String>,
+    base_url: Option<String>,
+    access_level: Option<AccessLevel>,
+    #[serde(default)]
+    tweet_fields: Vec<String>,
+    #[serde(default)]
+    user_fields: Vec<String>,
+    #[serde(default)]
+    expansions: Vec<String>,
+}
+
+impl Config {
+    /// Reads the config file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Config, TwitterError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(TwitterError::IoError)?;
+        Config::from_toml(&text)
+            .map_err(|e| TwitterError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Reads the config file at `default_path`, or returns an empty config if there is none
+    pub fn load_default() -> Result<Config, TwitterError> {
+        match Config::default_path() {
+            Some(path) if path.exists() => Config::load(path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// The path in `TWITTER_REQUEST_CONFIG`, or else `twitter_request/config.toml` in the
+    /// user's config directory
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_PATH_VAR) {
+            return Some(PathBuf::from(path));
+        }
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_dir.join("twitter_request").join("config.toml"))
+    }
+
+    /// Parses a config from TOML
+    pub fn from_toml(text: &str) -> Result<Config, TwitterError> {
+        let raw: RawConfig =
+            toml::from_str(text).map_err(|e| TwitterError::ConfigError(e.to_string()))?;
+        let profiles = raw
+            .profiles
+            .into_iter()
+            .map(|(name, profile)| Ok((name.clone(), profile.parse(name)?)))
+            .collect::<Result<_, TwitterError>>()?;
+        Ok(Config {
+            default_profile: raw.default_profile,
+            profiles,
+        })
+    }
+
+    /// The names of the profiles in the config
+    pub fn profile_names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Gets a profile with the environment variable overrides applied. Without a
+    /// `name` the profile in `TWITTER_PROFILE` is used, or else the config's
+    /// `default_profile`, or else the profile called "default". A profile that is not
+    /// in the config is only an error if it was named, as the default profile can be
+    /// made up entirely of environment variables.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, TwitterError> {
+        self.profile_with(name, |var| env::var(var).ok())
+    }
+
+    fn profile_with(
+        &self,
+        name: Option<&str>,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Profile, TwitterError> {
+        let named = name
+            .map(String::from)
+            .or_else(|| var(PROFILE_VAR))
+            .or_else(|| self.default_profile.clone());
+        let mut profile = match &named {
+            Some(name) => self.profiles.get(name).cloned().ok_or_else(|| {
+                TwitterError::ConfigError(format!("there is no profile called \"{}\"", name))
+            })?,
+            None => self
+                .profiles
+                .get(DEFAULT_PROFILE)
+                .cloned()
+                .unwrap_or_else(|| Profile::new(DEFAULT_PROFILE)),
+        };
+        profile.override_with(var)?;
+        Ok(profile)
+    }
+}
+
+impl RawProfile {
+    fn parse(self, name: String) -> Result<Profile, TwitterError> {
+        let error = |e: crate::errors::TwitterBuilderError| {
+            TwitterError::ConfigError(format!("profile \"{}\": {}", name, e))
+        };
+        let secret = |value: Option<String>| value.map(Secret);
+        Ok(Profile {
+            bearer_token: secret(self.bearer_token),
+            consumer_key: secret(self.consumer_key),
+            consumer_secret: secret(self.consumer_secret),
+            access_token: secret(self.access_token),
+            access_token_secret: secret(self.access_token_secret),
+            oauth2_user_token: secret(self.oauth2_user_token),
+            base_url: self.base_url.unwrap_or_else(|| String::from(TWITTER_URL)),
+            access_level: self.access_level,
+            tweet_fields: self
+                .tweet_fields
+                .iter()
+                .map(|f| TweetField::try_from(f.as_str()))
+                .collect::<Result<_, _>>()
+                .map_err(error)?,
+            user_fields: self
+                .user_fields
+                .iter()
+                .map(|f| UserField::try_from(f.as_str()))
+                .collect::<Result<_, _>>()
+                .map_err(error)?,
+            expansions: self
+                .expansions
+                .iter()
+                .map(|e| Expansions::try_from(e.as_str()))
+                .collect::<Result<_, _>>()
+                .map_err(error)?,
+            name,
+        })
+    }
+}
+
+impl Profile {
+    /// Creates a profile without credentials that uses `TWITTER_URL`
+    pub fn new(name: &str) -> Profile {
+        Profile {
+            name: String::from(name),
+            bearer_token: None,
+            consumer_key: None,
+            consumer_secret: None,
+            access_token: None,
+            access_token_secret: None,
+            oauth2_user_token: None,
+            base_url: String::from(TWITTER_URL),
+            access_level: None,
+            tweet_fields: Vec::new(),
+            user_fields: Vec::new(),
+            expansions: Vec::new(),
+        }
+    }
+
+    /// Replaces the settings that have an environment variable set
+    fn override_with(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), TwitterError> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        let secrets = [
+            ("BEARER_TOKEN", &mut self.bearer_token),
+            ("CONSUMER_KEY", &mut self.consumer_key),
+            ("CONSUMER_SECRET", &mut self.consumer_secret),
+            ("ACCESS_TOKEN", &mut self.access_token),
+            ("ACCESS_TOKEN_SECRET", &mut self.access_token_secret),
+            ("OAUTH2_USER_TOKEN", &mut self.oauth2_user_token),
+        ];
+        for (name, secret) in secrets {
+            if let Some(value) = var(name) {
+                *secret = Some(Secret(value));
+            }
+        }
+        if let Some(base_url) = var("TWITTER_BASE_URL") {
+            self.base_url = base_url;
+        }
+        if let Some(access_level) = var("TWITTER_ACCESS_LEVEL") {
+            self.access_level = Some(
+                AccessLevel::try_from(access_level.as_str())
+                    .map_err(|e| TwitterError::ConfigError(e.to_string()))?,
+            );
+        }
+        Ok(())
+    }
+
+    /// App only authentication with the bearer token
+    pub fn app_auth(&self) -> Option<AuthenticationData> {
+        let token = self.bearer_token.as_ref()?;
+        Some(self.with_access_level(AuthenticationData::new_bearer_auth(token.expose())))
+    }
+
+    /// OAuth 1.0a user context authentication, if all four of its credentials are set
+    pub fn oauth1_auth(&self) -> Option<AuthenticationData> {
+        Some(self.with_access_level(AuthenticationData::new_oauth_auth(
+            self.consumer_key.as_ref()?.expose(),
+            self.consumer_secret.as_ref()?.expose(),
+            self.access_token.as_ref()?.expose(),
+            self.access_token_secret.as_ref()?.expose(),
+        )))
+    }
+
+    /// OAuth 2.0 user context authentication with the user token
+    pub fn oauth2_auth(&self) -> Option<AuthenticationData> {
+        let token = self.oauth2_user_token.as_ref()?;
+        Some(self.with_access_level(AuthenticationData::new_oauth2_user_auth(token.expose())))
+    }
+
+    /// Gives the authentication the profile's access level, if it has one
+    fn with_access_level(&self, auth: AuthenticationData) -> AuthenticationData {
+        match self.access_level {
+            Some(access_level) => auth.with_access_level(access_level),
+            None => auth,
+        }
+    }
+
+    /// User context authentication, with OAuth 1.0a if it is set up and otherwise
+    /// with the OAuth 2.0 user token
+    pub fn user_auth(&self) -> Option<AuthenticationData> {
+        self.oauth1_auth().or_else(|| self.oauth2_auth())
+    }
+
+    /// The field and expansion options to add to every request
+    pub fn default_options(&self) -> Vec<MainOptions> {
+        let mut options = Vec::new();
+        if !self.tweet_fields.is_empty() {
+            options.push(MainOptions::TweetFields(self.tweet_fields.clone()));
+        }
+        if !self.user_fields.is_empty() {
+            options.push(MainOptions::UserFields(self.user_fields.clone()));
+        }
+        if !self.expansions.is_empty() {
+            options.push(MainOptions::Expansions(self.expansions.clone()));
+        }
+        options
+    }
+
+    /// Starts a request to the endpoint with the profile's base url, the default
+    /// options the endpoint accepts, and the credentials the endpoint requires for
+    /// the method. Fails with `NoAuthError` if the profile has no such credentials.
+    pub fn request(
+        &self,
+        endpoint: Endpoint,
+        method: Method,
+    ) -> Result<TwitterRequestBuilder, TwitterError> {
+        let required = endpoint
+            .get_auth_type(&method)
+            .map_err(|_| TwitterError::BadMethod(endpoint.clone(), method.clone()))?;
+        let authentication = [self.app_auth(), self.oauth1_auth(), self.oauth2_auth()]
+            .into_iter()
+            .flatten()
+            .find(|auth| auth.get_type().satisfies(&required))
+            .ok_or(TwitterError::NoAuthError)?;
+        let mut builder = TwitterRequest::builder();
+        for option in self.default_options() {
+            if endpoint.supports_option(&option) {
+                builder = builder.add_option(option);
+            }
+        }
+        Ok(builder
+            .set_endpoint(endpoint)
+            .set_method(method)
+            .set_authentication(authentication)
+            .set_base_url(&self.base_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+    use crate::twitter::twitter_objects::tweet::Tweet;
+    use reqwest::Client;
+    use std::collections::HashMap;
+
+    const CONFIG: &str = r#"
+default_profile = "research"
+
+[profiles.research]
+bearer_token = "research-bearer"
+access_level = "academic"
+tweet_fields = ["author_id", "created_at"]
+expansions = ["author_id"]
+
+[profiles.bot]
+consumer_key = "key"
+consumer_secret = "key-secret"
+access_token = "token"
+access_token_secret = "token-secret"
+base_url = "http://localhost:8080"
+access_level = "basic"
+"#;
+
+    fn vars<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        move |name| vars.get(name).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn profile_test() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        assert_eq!(
+            config.profile_names().collect::<Vec<_>>(),
+            ["bot", "research"]
+        );
+
+        let research = config.profile_with(None, vars(&[])).unwrap();
+        assert_eq!(research.name, "research");
+        assert_eq!(research.access_level, Some(AccessLevel::Academic));
+        assert_eq!(research.base_url, TWITTER_URL);
+        assert_eq!(
+            research.default_options(),
+            vec![
+                MainOptions::TweetFields(vec![TweetField::AuthorId, TweetField::CreatedAt]),
+                MainOptions::Expansions(vec![Expansions::AuthorId]),
+            ]
+        );
+        assert_eq!(
+            research.app_auth().unwrap().get_auth_token(),
+            "research-bearer"
+        );
+        assert!(research.user_auth().is_none());
+        assert_eq!(
+            research.app_auth().unwrap().get_access_level(),
+            Some(AccessLevel::Academic)
+        );
+
+        let bot = config
+            .profile_with(None, vars(&[(PROFILE_VAR, "bot")]))
+            .unwrap();
+        assert_eq!(bot.base_url, "http://localhost:8080");
+        assert!(bot.app_auth().is_none());
+        assert_eq!(
+            bot.user_auth().unwrap().get_type(),
+            crate::twitter::endpoints::AuthenticationType::OauthSignature
+        );
+
+        let overridden = config
+            .profile_with(
+                Some("bot"),
+                vars(&[
+                    (PROFILE_VAR, "research"),
+                    ("BEARER_TOKEN", "env-bearer"),
+                    ("TWITTER_ACCESS_LEVEL", "Pro"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(overridden.name, "bot");
+        assert_eq!(overridden.bearer_token, Some(Secret::new("env-bearer")));
+        assert_eq!(overridden.access_level, Some(AccessLevel::Pro));
+
+        assert!(config.profile_with(Some("missing"), vars(&[])).is_err());
+        // Without a config file the default profile comes from the environment alone
+        let from_env = Config::default()
+            .profile_with(None, vars(&[("BEARER_TOKEN", "env-bearer")]))
+            .unwrap();
+        assert_eq!(from_env.name, DEFAULT_PROFILE);
+        assert!(from_env.app_auth().is_some());
+    }
+
+    #[test]
+    fn bad_config_test() {
+        assert!(Config::from_toml("[profiles.a]\nbearer = \"typo\"").is_err());
+        assert!(Config::from_toml("[profiles.a]\ntweet_fields = [\"nonsense\"]").is_err());
+        assert!(Config::from_toml("[profiles.a]\naccess_level = \"gold\"").is_err());
+    }
+
+    #[test]
+    fn debug_redacts_secrets_test() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        let bot = config.profile_with(Some("bot"), vars(&[])).unwrap();
+        let debug = format!("{:?} {:?} {:?}", config, bot, bot.user_auth().unwrap());
+        for secret in ["research-bearer", "key-secret", "token-secret"] {
+            assert!(!debug.contains(secret), "{} was printed", secret);
+        }
+        assert!(debug.contains("Secret(***)"));
+    }
+
+    #[tokio::test]
+    async fn request_test() {
+        let config = Config::from_toml(CONFIG).unwrap();
+        let server = MockServer::start().await;
+        server.mock(
+            Mock::endpoint(&Endpoint::LookupTweets, Method::GET)
+                .expect_param("tweet.fields", "author_id,created_at")
+                .respond_with(MockResponse::data(serde_json::json!([])))
+                .times(1),
+        );
+        let research = config
+            .profile_with(None, vars(&[("TWITTER_BASE_URL", server.url())]))
+            .unwrap();
+        research
+            .request(Endpoint::LookupTweets, Method::GET)
+            .unwrap()
+            .add_id(1)
+            .build()
+            .unwrap()
+            .send_typed::<Vec<Tweet>>(&Client::new())
+            .await
+            .unwrap();
+        server.verify();
+        assert_eq!(
+            server.received()[0].header("authorization"),
+            Some("Bearer research-bearer")
+        );
+
+        // Posting a tweet needs user context, which the research profile lacks
+        assert!(matches!(
+            research.request(Endpoint::LookupTweets, Method::POST),
+            Err(TwitterError::NoAuthError)
+        ));
+        let bot = config.profile_with(Some("bot"), vars(&[])).unwrap();
+        assert!(bot.request(Endpoint::LookupTweets, Method::POST).is_ok());
+    }
+}
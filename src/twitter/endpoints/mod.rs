@@ -19,15 +19,17 @@
 
 mod twitter_auth;
 
-use std::fmt::{Display, Formatter};
+use std::fmt::Formatter;
 
 use http::Method;
 use reqwest::{Client, Response};
 
 pub use twitter_auth::{AuthenticationData, AuthenticationType};
 
-use crate::twitter::query_filters::{group::GroupList, ids::Id};
-use crate::{errors::TwitterError, Filter};
+use crate::errors::{TwitterBuilderError, TwitterError};
+use crate::twitter::access_level::AccessLevel;
+use crate::twitter::query_filters::{ids::Id, usernames::Username, QueryFilters};
+use crate::twitter::query_params::main_options::{Exclude, MainOptions};
 
 pub const TWITTER_URL: &str = "https://api.twitter.com";
 
@@ -55,25 +57,24 @@ pub const TWITTER_URL: &str = "https://api.twitter.com";
 ///   query parameters.
 /// - **DELETE** deletes the tweet.
 ///
-/// ## LookupTweetQuoteTweets(tweet_id: String)
-/// Returns a path that needs to be formatted to replace a placeholder with a single tweet ID.
+/// ## LookupTweetQuoteTweets(tweet_id: Id)
 /// Gets the quote tweet of the tweet id in the endpoint path.
 ///
 /// ### HTTP Methods
 /// This method only supports the GET method.
-/// - **GET** gets the quote tweets
+/// - **GET** gets the quote tweets, paginated. Retweets and replies can be excluded.
 ///
-/// ## LookupTweetRetweetedBy(tweet_id: String)
-/// Returns a path that needs to be formatted to replace a placeholder with a single tweet ID.
+/// ## LookupTweetRetweetedBy(tweet_id: Id)
 /// Gets the users who retweeted the specified tweet.
 ///
 /// ### HTTP Methods
 /// This method only supports the GET method.
-///  - **GET** gets the users that have retweeted this tweet.
+///  - **GET** gets the users that have retweeted this tweet, paginated.
 ///
 /// ## LookupTweetsCountRecent
 /// This method only supports the GET method.
 ///  - **GET** Gets tweets from the last 7 days that match the query filters.
+///    The counts are per hour unless another `Granularity` is set.
 ///
 /// ## LookupTweetsCountAll
 /// This endpoint only supports the GET method.
@@ -90,20 +91,249 @@ pub const TWITTER_URL: &str = "https://api.twitter.com";
 /// ## SearchTweetsAll
 /// ### HTTP Methods
 ///
-/// ## TimelineUserTweets(user_id: String)
+/// ## TimelineUserTweets(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the tweets posted by the user. Only the 3200 most recent tweets can be
+///    paginated through, or the 800 most recent if replies are excluded.
+///
+/// ## TimelineUserMentions(user_id: Id)
 /// ### HTTP Methods
+///  - **GET** Gets the tweets mentioning the user. Only the 800 most recent mentions can be
+///    paginated through.
 ///
-/// ## TimelineUserMentions(user_id: String)
+/// ## TimelineReverseChronological(user_id: Id)
 /// ### HTTP Methods
+///  - **GET** Gets the home timeline of the authenticating user, whose id must be passed.
+///    Only the 3200 most recent tweets can be paginated through, or the 800 most recent if
+///    replies are excluded. Requires user context authentication.
 ///
 /// ## StreamTweets
 /// ### HTTP Methods
+///  - **GET** Streams the tweets matching the rules of the filtered stream, read it with
+///    `TwitterRequest::send_streaming`.
 ///
 /// ## StreamRules
 /// ### HTTP Methods
+///  - **GET** Gets the rules of the filtered stream.
+///  - **POST** Adds the rules in the `add` of the JSON body, or deletes the rules whose ids
+///    are in the `delete` of the JSON body.
+///
+/// ## UserById(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the user with the given id.
+///
+/// ## UsersByIds
+/// ### HTTP Methods
+///  - **GET** Gets up to 100 users by passing comma-separated user ids in the query.
+///
+/// ## UserByUsername(username: Username)
+/// ### HTTP Methods
+///  - **GET** Gets the user with the given username.
 ///
 /// ## UsersByUsernames
 /// ### HTTP Methods
+///  - **GET** Gets up to 100 users by passing comma-separated usernames in the query.
+///
+/// ## Me
+/// ### HTTP Methods
+///  - **GET** Gets the user whose user context the request is authenticated with.
+///
+/// ## UserFollowers(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the users following the user, paginated.
+///
+/// ## UserFollowing(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the users the user follows, paginated.
+///  - **POST** Makes the user follow the user whose id is in the `target_user_id` of the
+///    JSON body. Requires user context authentication.
+///
+/// ## UserUnfollow(source_user_id: Id, target_user_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Makes the source user unfollow the target user. Requires user context
+///    authentication.
+///
+/// ## LookupTweetLikingUsers(tweet_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the users who liked the tweet, paginated.
+///
+/// ## UserLikedTweets(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the tweets the user has liked, paginated.
+///
+/// ## UserLikes(user_id: Id)
+/// ### HTTP Methods
+///  - **POST** Makes the user like the tweet whose id is in the `tweet_id` of the JSON
+///    body. Requires user context authentication.
+///
+/// ## UserUnlike(user_id: Id, tweet_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Makes the user unlike the tweet. Requires user context authentication.
+///
+/// ## UserRetweets(user_id: Id)
+/// ### HTTP Methods
+///  - **POST** Makes the user retweet the tweet whose id is in the `tweet_id` of the JSON
+///    body. Requires user context authentication.
+///
+/// ## UserUnretweet(user_id: Id, source_tweet_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Undoes the user's retweet of the source tweet. Requires user context
+///    authentication.
+///
+/// ## LookupList(list_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the list.
+///  - **PUT** Updates the name, description or privacy of the list with the JSON body.
+///    Requires user context authentication.
+///  - **DELETE** Deletes the list. Requires user context authentication.
+///
+/// ## CreateList
+/// ### HTTP Methods
+///  - **POST** Creates a list with the `name`, `description` and `private` of the JSON body.
+///    Requires user context authentication.
+///
+/// ## ListTweets(list_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the tweets of the list's members, paginated.
+///
+/// ## ListMembers(list_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the members of the list, paginated.
+///  - **POST** Adds the user whose id is in the `user_id` of the JSON body to the list.
+///    Requires user context authentication.
+///
+/// ## ListRemoveMember(list_id: Id, user_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Removes the user from the list. Requires user context authentication.
+///
+/// ## ListFollowers(list_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the followers of the list, paginated.
+///
+/// ## UserOwnedLists(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the lists the user owns, paginated.
+///
+/// ## UserListMemberships(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the lists the user is a member of, paginated.
+///
+/// ## UserPinnedLists(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the lists the user has pinned. Requires user context authentication.
+///  - **POST** Pins the list whose id is in the `list_id` of the JSON body. Requires user
+///    context authentication.
+///
+/// ## UserUnpinList(user_id: Id, list_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Unpins the list. Requires user context authentication.
+///
+/// ## UserBookmarks(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the tweets the user has bookmarked, paginated. Requires an OAuth 2.0
+///    user token.
+///  - **POST** Bookmarks the tweet whose id is in the `tweet_id` of the JSON body. Requires
+///    an OAuth 2.0 user token.
+///
+/// ## UserRemoveBookmark(user_id: Id, tweet_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Removes the tweet from the user's bookmarks. Requires an OAuth 2.0 user
+///    token.
+///
+/// ## LookupTweetHidden(tweet_id: Id)
+/// ### HTTP Methods
+///  - **PUT** Hides or unhides the reply depending on the `hidden` of the JSON body.
+///    Requires user context authentication of the author of the conversation.
+///
+/// ## DmEvents
+/// ### HTTP Methods
+///  - **GET** Gets the direct message events of the authenticating user, paginated.
+///    Requires user context authentication.
+///
+/// ## DmConversationEvents(dm_conversation_id: String)
+/// ### HTTP Methods
+///  - **GET** Gets the events of the direct message conversation, paginated. Requires user
+///    context authentication.
+///
+/// ## DmConversationWithEvents(participant_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the events of the one to one conversation with the participant,
+///    paginated. Requires user context authentication.
+///
+/// ## DmConversationMessages(dm_conversation_id: String)
+/// ### HTTP Methods
+///  - **POST** Sends the message in the JSON body to the conversation. Requires user
+///    context authentication.
+///
+/// ## DmConversationWithMessages(participant_id: Id)
+/// ### HTTP Methods
+///  - **POST** Sends the message in the JSON body to the participant, starting a one to
+///    one conversation if there is none. Requires user context authentication.
+///
+/// ## DmConversations
+/// ### HTTP Methods
+///  - **POST** Creates a group conversation with the `participant_ids` and first `message`
+///    of the JSON body. Requires user context authentication.
+///
+/// ## LookupSpace(space_id: String)
+/// ### HTTP Methods
+///  - **GET** Gets the space.
+///
+/// ## LookupSpaces
+/// ### HTTP Methods
+///  - **GET** Gets the spaces whose ids are given with the `SpaceIds` option.
+///
+/// ## SpacesByCreatorIds
+/// ### HTTP Methods
+///  - **GET** Gets the spaces created by the users whose ids are given in the query filters.
+///
+/// ## SearchSpaces
+/// ### HTTP Methods
+///  - **GET** Gets the spaces whose title matches the keywords of the query filters.
+///
+/// ## SpaceBuyers(space_id: String)
+/// ### HTTP Methods
+///  - **GET** Gets the users who bought a ticket to the space, paginated. Requires an
+///    OAuth 2.0 user token of the creator of the space.
+///
+/// ## SpaceTweets(space_id: String)
+/// ### HTTP Methods
+///  - **GET** Gets the tweets shared in the space.
+///
+/// ## ComplianceJobs
+/// ### HTTP Methods
+///  - **GET** Gets the compliance jobs of the type given with the `ComplianceType` option,
+///    optionally only those with the status given with the `ComplianceStatus` option.
+///  - **POST** Creates a compliance job with the `type`, and optionally `name` and
+///    `resumable`, of the JSON body.
+///
+/// ## ComplianceJob(job_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the compliance job, used to poll its status.
+///
+/// ## UserBlocking(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the users the user blocks, paginated. Requires user context
+///    authentication.
+///  - **POST** Blocks the user whose id is in the `target_user_id` of the JSON body. Requires
+///    user context authentication.
+///
+/// ## UserUnblock(source_user_id: Id, target_user_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Makes the source user unblock the target user. Requires user context
+///    authentication.
+///
+/// ## UserMuting(user_id: Id)
+/// ### HTTP Methods
+///  - **GET** Gets the users the user mutes, paginated. Requires user context
+///    authentication.
+///  - **POST** Mutes the user whose id is in the `target_user_id` of the JSON body. Requires
+///    user context authentication.
+///
+/// ## UserUnmute(source_user_id: Id, target_user_id: Id)
+/// ### HTTP Methods
+///  - **DELETE** Makes the source user unmute the target user. Requires user context
+///    authentication.
 ///
 /// # Methods
 /// The Endpoints enum has methods for getting the supported HTTP methods as well as getting
@@ -115,6 +345,10 @@ pub const TWITTER_URL: &str = "https://api.twitter.com";
 /// ## `get_auth_type(method: http::Method) -> Option<String>`
 /// Gets an option containing either a Some(String) with the name of the authentication type,
 /// or None if the http method was not supported by the endpoint.
+///
+/// ## `get_access_level(method: http::Method) -> Result<AccessLevel, ()>`
+/// Gets the lowest access tier (Free, Basic, Pro, Enterprise or Academic) that can use
+/// the endpoint with the method, or Err(()) if the method was not supported by the endpoint.
 #[derive(Debug, Clone)]
 pub enum Endpoint {
     /// Used to look up tweets by passing a comma-separated list of tweet ids
@@ -124,26 +358,121 @@ pub enum Endpoint {
     /// The ID is given as the String arg going with this variant
     LookupTweet(Id),
     /// Used to look up tweets quoting the tweet with the passed ID
-    /// The ID is given as the String arg going with this variant
     LookupTweetQuoteTweets(Id),
     /// Used to look up who have retweeted the tweet with the passed ID
-    /// The ID is given as the String arg going with this variant
     LookupTweetRetweetedBy(Id),
     /// Used to look up how many tweets in the last X days match the accompanying query parameters
     LookupTweetsCountRecent,
     /// Used to look up how many tweets match the accompanying query parameters in the entire
-    /// history of twitter. Can only be used with Academic access.
+    /// history of twitter. Requires Pro, Enterprise or Academic access.
     LookupTweetsCountAll,
     /// Used to look up all tweets matching the accompanying query parameters in the last X days
     SearchTweetsRecent,
     /// Used to look up all tweets that match the accompanying query parameters in the entire
-    /// history of twitter. Can only be used with Academic access.
+    /// history of twitter. Requires Pro, Enterprise or Academic access.
     SearchTweetsAll,
-    TimelineUserTweets(String), // TODO can't find these endpoints right now??
-    TimelineUserMentions(String), // TODO can't find these endpoints right now??
+    /// Used to look up the tweets posted by the user with the passed ID
+    TimelineUserTweets(Id),
+    /// Used to look up the tweets mentioning the user with the passed ID
+    TimelineUserMentions(Id),
+    /// Used to look up the home timeline of the user with the passed ID, which must be the
+    /// authenticating user
+    TimelineReverseChronological(Id),
     StreamTweets,
     StreamRules,
-    UsersByUsernames, // TODO expand to include more endpoints
+    /// Used to look up the user with the given ID
+    UserById(Id),
+    /// Used to look up users by passing a comma-separated list of user ids
+    UsersByIds,
+    /// Used to look up the user with the given username
+    UserByUsername(Username),
+    /// Used to look up users by passing a comma-separated list of usernames
+    UsersByUsernames,
+    /// Used to look up the authenticating user, requires user context authentication
+    Me,
+    /// Used to look up the followers of the user with the passed ID
+    UserFollowers(Id),
+    /// Used to look up who the user with the passed ID follows, or to follow someone as them
+    UserFollowing(Id),
+    /// Used to make the user with the first ID unfollow the user with the second ID
+    UserUnfollow(Id, Id),
+    /// Used to look up the users who liked the tweet with the passed ID
+    LookupTweetLikingUsers(Id),
+    /// Used to look up the tweets liked by the user with the passed ID
+    UserLikedTweets(Id),
+    /// Used to like a tweet as the user with the passed ID
+    UserLikes(Id),
+    /// Used to make the user with the first ID unlike the tweet with the second ID
+    UserUnlike(Id, Id),
+    /// Used to retweet a tweet as the user with the passed ID
+    UserRetweets(Id),
+    /// Used to make the user with the first ID undo their retweet of the tweet with the second ID
+    UserUnretweet(Id, Id),
+    /// Used to look up, update or delete the list with the passed ID
+    LookupList(Id),
+    /// Used to create a list as the authenticating user
+    CreateList,
+    /// Used to look up the tweets of the members of the list with the passed ID
+    ListTweets(Id),
+    /// Used to look up the members of the list with the passed ID, or to add a member to it
+    ListMembers(Id),
+    /// Used to remove the user with the second ID from the list with the first ID
+    ListRemoveMember(Id, Id),
+    /// Used to look up the followers of the list with the passed ID
+    ListFollowers(Id),
+    /// Used to look up the lists owned by the user with the passed ID
+    UserOwnedLists(Id),
+    /// Used to look up the lists the user with the passed ID is a member of
+    UserListMemberships(Id),
+    /// Used to look up the lists pinned by the user with the passed ID, or to pin one
+    UserPinnedLists(Id),
+    /// Used to make the user with the first ID unpin the list with the second ID
+    UserUnpinList(Id, Id),
+    /// Used to look up the bookmarks of the user with the passed ID, or to bookmark a tweet
+    /// as them. Only OAuth 2.0 user tokens are accepted.
+    UserBookmarks(Id),
+    /// Used to remove the tweet with the second ID from the bookmarks of the user with the
+    /// first ID. Only OAuth 2.0 user tokens are accepted.
+    UserRemoveBookmark(Id, Id),
+    /// Used to look up who the user with the passed ID blocks, or to block someone as them
+    UserBlocking(Id),
+    /// Used to make the user with the first ID unblock the user with the second ID
+    UserUnblock(Id, Id),
+    /// Used to look up who the user with the passed ID mutes, or to mute someone as them
+    UserMuting(Id),
+    /// Used to make the user with the first ID unmute the user with the second ID
+    UserUnmute(Id, Id),
+    /// Used to hide or unhide the reply with the passed ID
+    LookupTweetHidden(Id),
+    /// Used to look up the direct message events of the authenticating user
+    DmEvents,
+    /// Used to look up the events of the direct message conversation with the passed ID
+    DmConversationEvents(String),
+    /// Used to look up the events of the one to one conversation with the user with the
+    /// passed ID
+    DmConversationWithEvents(Id),
+    /// Used to send a message to the direct message conversation with the passed ID
+    DmConversationMessages(String),
+    /// Used to send a message to the user with the passed ID
+    DmConversationWithMessages(Id),
+    /// Used to create a group direct message conversation
+    DmConversations,
+    /// Used to look up the space with the passed ID
+    LookupSpace(String),
+    /// Used to look up spaces by passing a list of space ids with the `SpaceIds` option
+    LookupSpaces,
+    /// Used to look up the spaces created by the users with the passed user ids
+    SpacesByCreatorIds,
+    /// Used to search for spaces by their title
+    SearchSpaces,
+    /// Used to look up who bought tickets to the space with the passed ID
+    SpaceBuyers(String),
+    /// Used to look up the tweets shared in the space with the passed ID
+    SpaceTweets(String),
+    /// Used to list and create batch compliance jobs
+    ComplianceJobs,
+    /// Used to look up the compliance job with the passed ID
+    ComplianceJob(Id),
 }
 
 impl Endpoint {
@@ -153,12 +482,44 @@ impl Endpoint {
     /// `Vec<http::Method>` a vector containing the supported HTTP methods.
     pub fn get_methods(&self) -> Vec<Method> {
         match self {
-            Endpoint::LookupTweets | Endpoint::StreamRules => {
+            Endpoint::LookupTweets | Endpoint::StreamRules | Endpoint::ComplianceJobs => {
                 vec![Method::GET, Method::POST]
             }
             Endpoint::LookupTweet(_) => {
                 vec![Method::GET, Method::DELETE]
             }
+            Endpoint::LookupList(_) => {
+                vec![Method::GET, Method::PUT, Method::DELETE]
+            }
+            Endpoint::UserFollowing(_)
+            | Endpoint::ListMembers(_)
+            | Endpoint::UserPinnedLists(_)
+            | Endpoint::UserBookmarks(_)
+            | Endpoint::UserBlocking(_)
+            | Endpoint::UserMuting(_) => {
+                vec![Method::GET, Method::POST]
+            }
+            Endpoint::UserUnfollow(_, _)
+            | Endpoint::UserUnlike(_, _)
+            | Endpoint::UserUnretweet(_, _)
+            | Endpoint::ListRemoveMember(_, _)
+            | Endpoint::UserUnpinList(_, _)
+            | Endpoint::UserRemoveBookmark(_, _)
+            | Endpoint::UserUnblock(_, _)
+            | Endpoint::UserUnmute(_, _) => {
+                vec![Method::DELETE]
+            }
+            Endpoint::UserLikes(_)
+            | Endpoint::UserRetweets(_)
+            | Endpoint::CreateList
+            | Endpoint::DmConversationMessages(_)
+            | Endpoint::DmConversationWithMessages(_)
+            | Endpoint::DmConversations => {
+                vec![Method::POST]
+            }
+            Endpoint::LookupTweetHidden(_) => {
+                vec![Method::PUT]
+            }
             Endpoint::LookupTweetQuoteTweets(_)
             | Endpoint::LookupTweetRetweetedBy(_)
             | Endpoint::LookupTweetsCountRecent
@@ -167,7 +528,29 @@ impl Endpoint {
             | Endpoint::SearchTweetsAll
             | Endpoint::TimelineUserTweets(_)
             | Endpoint::TimelineUserMentions(_)
+            | Endpoint::TimelineReverseChronological(_)
+            | Endpoint::UserById(_)
+            | Endpoint::UsersByIds
+            | Endpoint::UserByUsername(_)
             | Endpoint::UsersByUsernames
+            | Endpoint::Me
+            | Endpoint::UserFollowers(_)
+            | Endpoint::LookupTweetLikingUsers(_)
+            | Endpoint::UserLikedTweets(_)
+            | Endpoint::ListTweets(_)
+            | Endpoint::ListFollowers(_)
+            | Endpoint::UserOwnedLists(_)
+            | Endpoint::UserListMemberships(_)
+            | Endpoint::DmEvents
+            | Endpoint::DmConversationEvents(_)
+            | Endpoint::DmConversationWithEvents(_)
+            | Endpoint::LookupSpace(_)
+            | Endpoint::LookupSpaces
+            | Endpoint::SpacesByCreatorIds
+            | Endpoint::SearchSpaces
+            | Endpoint::SpaceBuyers(_)
+            | Endpoint::SpaceTweets(_)
+            | Endpoint::ComplianceJob(_)
             | Endpoint::StreamTweets => {
                 vec![Method::GET]
             }
@@ -184,6 +567,7 @@ impl Endpoint {
     /// # Returns
     /// `Some(AuthenticationType)` if the endpoint supports the method
     /// `Err(())` if the endpoint does not support the method
+    #[allow(clippy::result_unit_err)]
     pub fn get_auth_type(&self, method: &Method) -> Result<AuthenticationType, ()> {
         if !self.get_methods().contains(method) {
             // This if-check makes exhaustive checks of method unnecessary
@@ -191,9 +575,15 @@ impl Endpoint {
             return Err(());
         }
         match self {
-            Endpoint::LookupTweets | Endpoint::LookupTweet(_) => match *method {
+            Endpoint::LookupTweets
+            | Endpoint::LookupTweet(_)
+            | Endpoint::UserFollowing(_)
+            | Endpoint::LookupList(_)
+            | Endpoint::ListMembers(_) => match *method {
                 Method::GET => Ok(AuthenticationType::BearerToken),
-                Method::DELETE | Method::POST => Ok(AuthenticationType::OauthSignature),
+                Method::DELETE | Method::POST | Method::PUT => {
+                    Ok(AuthenticationType::OauthSignature)
+                }
                 _ => unreachable!(),
             },
             Endpoint::LookupTweetQuoteTweets(_)
@@ -204,110 +594,615 @@ impl Endpoint {
             | Endpoint::SearchTweetsAll
             | Endpoint::TimelineUserTweets(_)
             | Endpoint::TimelineUserMentions(_)
+            | Endpoint::UserById(_)
+            | Endpoint::UsersByIds
+            | Endpoint::UserByUsername(_)
             | Endpoint::UsersByUsernames
+            | Endpoint::UserFollowers(_)
+            | Endpoint::LookupTweetLikingUsers(_)
+            | Endpoint::UserLikedTweets(_)
+            | Endpoint::ListTweets(_)
+            | Endpoint::ListFollowers(_)
+            | Endpoint::UserOwnedLists(_)
+            | Endpoint::UserListMemberships(_)
+            | Endpoint::LookupSpace(_)
+            | Endpoint::LookupSpaces
+            | Endpoint::SpacesByCreatorIds
+            | Endpoint::SearchSpaces
+            | Endpoint::SpaceTweets(_)
+            | Endpoint::ComplianceJobs
+            | Endpoint::ComplianceJob(_)
             | Endpoint::StreamTweets
             | Endpoint::StreamRules => Ok(AuthenticationType::BearerToken),
+            Endpoint::Me
+            | Endpoint::TimelineReverseChronological(_)
+            | Endpoint::UserUnfollow(_, _)
+            | Endpoint::UserLikes(_)
+            | Endpoint::UserUnlike(_, _)
+            | Endpoint::UserRetweets(_)
+            | Endpoint::UserUnretweet(_, _)
+            | Endpoint::CreateList
+            | Endpoint::ListRemoveMember(_, _)
+            | Endpoint::UserPinnedLists(_)
+            | Endpoint::UserUnpinList(_, _)
+            | Endpoint::UserBlocking(_)
+            | Endpoint::UserUnblock(_, _)
+            | Endpoint::UserMuting(_)
+            | Endpoint::UserUnmute(_, _)
+            | Endpoint::LookupTweetHidden(_)
+            | Endpoint::DmEvents
+            | Endpoint::DmConversationEvents(_)
+            | Endpoint::DmConversationWithEvents(_)
+            | Endpoint::DmConversationMessages(_)
+            | Endpoint::DmConversationWithMessages(_)
+            | Endpoint::DmConversations => Ok(AuthenticationType::OauthSignature),
+            Endpoint::UserBookmarks(_)
+            | Endpoint::UserRemoveBookmark(_, _)
+            | Endpoint::SpaceBuyers(_) => Ok(AuthenticationType::OAuth2UserToken),
         }
     }
 
-    pub async fn send_request(
-        &self,
-        client: &Client,
-        method: Method,
-        query_filters: super::query_filters::QueryFilters,
-        auth: AuthenticationData,
-    ) -> Result<Response, TwitterError> {
-        if auth.get_type() != self.get_auth_type(&method).unwrap() {
-            return Err(TwitterError::WrongAuthError(
-                self.clone().to_owned(),
-                auth.get_type(),
-                method,
-            ));
+    /// Gets the lowest access tier that can use the endpoint with the method.
+    /// The Free tier can only post and delete tweets and look up the authenticated user,
+    /// full-archive search, tweet counts, the filtered stream and batch compliance need
+    /// Pro, Enterprise or Academic access, and everything else needs Basic.
+    ///
+    /// # Returns
+    /// `Ok(AccessLevel)` if the endpoint supports the method
+    /// `Err(())` if the endpoint does not support the method
+    #[allow(clippy::result_unit_err)]
+    pub fn get_access_level(&self, method: &Method) -> Result<AccessLevel, ()> {
+        if !self.get_methods().contains(method) {
+            return Err(());
         }
-        let req = client.request(method, &self.to_string());
-        let req = match auth.get_type() {
-            AuthenticationType::BearerToken => req.bearer_auth(auth.get_auth_token()),
-            AuthenticationType::OauthSignature => {
-                todo!()
-            } // Temporary match arm, might not be necessary when OAuth is implemented
-        };
-        let req = match self {
-            Endpoint::LookupTweets => {
-                // This endpoint looks up tweets using provided IDS
-                let ids = query_filters.ids();
-                if ids.is_none() {
-                    return Err(TwitterError::BadQueryError(String::from(
-                        "No ids provided, cannot use lookup tweets endpoint",
-                    )));
+        Ok(match self {
+            Endpoint::LookupTweets if *method == Method::POST => AccessLevel::Free,
+            Endpoint::LookupTweet(_) if *method == Method::DELETE => AccessLevel::Free,
+            Endpoint::Me => AccessLevel::Free,
+            Endpoint::SearchTweetsAll
+            | Endpoint::LookupTweetsCountRecent
+            | Endpoint::LookupTweetsCountAll
+            | Endpoint::StreamTweets
+            | Endpoint::StreamRules
+            | Endpoint::ComplianceJobs
+            | Endpoint::ComplianceJob(_) => AccessLevel::Pro,
+            _ => AccessLevel::Basic,
+        })
+    }
+
+    /// Checks if the endpoint accepts the option as a query parameter
+    pub fn supports_option(&self, option: &MainOptions) -> bool {
+        if let MainOptions::DmEventFields(_) | MainOptions::EventTypes(_) = option {
+            return matches!(
+                self,
+                Endpoint::DmEvents
+                    | Endpoint::DmConversationEvents(_)
+                    | Endpoint::DmConversationWithEvents(_)
+            );
+        }
+        if let MainOptions::SpaceFields(_) = option {
+            return matches!(
+                self,
+                Endpoint::LookupSpace(_)
+                    | Endpoint::LookupSpaces
+                    | Endpoint::SpacesByCreatorIds
+                    | Endpoint::SearchSpaces
+            );
+        }
+        if let MainOptions::ListFields(_) = option {
+            return matches!(
+                self,
+                Endpoint::LookupList(_)
+                    | Endpoint::UserOwnedLists(_)
+                    | Endpoint::UserListMemberships(_)
+                    | Endpoint::UserPinnedLists(_)
+            );
+        }
+        if matches!(
+            option,
+            MainOptions::TweetFields(_) | MainOptions::UserFields(_) | MainOptions::Expansions(_)
+        ) {
+            // Every endpoint that can be used to look up tweets or users takes field options
+            return self.get_methods().contains(&Method::GET)
+                && !matches!(
+                    self,
+                    Endpoint::LookupTweetsCountRecent
+                        | Endpoint::LookupTweetsCountAll
+                        | Endpoint::StreamRules
+                        | Endpoint::ComplianceJobs
+                        | Endpoint::ComplianceJob(_)
+                );
+        }
+        match self {
+            Endpoint::TimelineUserTweets(_) | Endpoint::TimelineReverseChronological(_) => {
+                matches!(
+                    option,
+                    MainOptions::StartTime(_)
+                        | MainOptions::EndTime(_)
+                        | MainOptions::SinceId(_)
+                        | MainOptions::UntilId(_)
+                        | MainOptions::MaxResults(_)
+                        | MainOptions::PaginationToken(_)
+                        | MainOptions::Exclude(_)
+                )
+            }
+            Endpoint::UserFollowers(_)
+            | Endpoint::UserFollowing(_)
+            | Endpoint::LookupTweetLikingUsers(_)
+            | Endpoint::UserLikedTweets(_)
+            | Endpoint::LookupTweetRetweetedBy(_)
+            | Endpoint::ListTweets(_)
+            | Endpoint::ListMembers(_)
+            | Endpoint::ListFollowers(_)
+            | Endpoint::UserOwnedLists(_)
+            | Endpoint::UserListMemberships(_)
+            | Endpoint::UserBookmarks(_)
+            | Endpoint::UserBlocking(_)
+            | Endpoint::UserMuting(_)
+            | Endpoint::DmEvents
+            | Endpoint::DmConversationEvents(_)
+            | Endpoint::DmConversationWithEvents(_)
+            | Endpoint::SpaceBuyers(_) => matches!(
+                option,
+                MainOptions::MaxResults(_) | MainOptions::PaginationToken(_)
+            ),
+            Endpoint::LookupSpaces => matches!(option, MainOptions::SpaceIds(_)),
+            Endpoint::SearchSpaces => {
+                matches!(option, MainOptions::State(_) | MainOptions::MaxResults(_))
+            }
+            Endpoint::SpaceTweets(_) => matches!(option, MainOptions::MaxResults(_)),
+            Endpoint::ComplianceJobs => matches!(
+                option,
+                MainOptions::ComplianceType(_) | MainOptions::ComplianceStatus(_)
+            ),
+            Endpoint::LookupTweetQuoteTweets(_) => matches!(
+                option,
+                MainOptions::MaxResults(_)
+                    | MainOptions::PaginationToken(_)
+                    | MainOptions::Exclude(_)
+            ),
+            Endpoint::TimelineUserMentions(_) => matches!(
+                option,
+                MainOptions::StartTime(_)
+                    | MainOptions::EndTime(_)
+                    | MainOptions::SinceId(_)
+                    | MainOptions::UntilId(_)
+                    | MainOptions::MaxResults(_)
+                    | MainOptions::PaginationToken(_)
+            ),
+            Endpoint::SearchTweetsRecent | Endpoint::SearchTweetsAll => matches!(
+                option,
+                MainOptions::StartTime(_)
+                    | MainOptions::EndTime(_)
+                    | MainOptions::SinceId(_)
+                    | MainOptions::UntilId(_)
+                    | MainOptions::MaxResults(_)
+                    | MainOptions::SortOrder(_)
+                    | MainOptions::NextToken(_)
+            ),
+            Endpoint::LookupTweetsCountRecent | Endpoint::LookupTweetsCountAll => matches!(
+                option,
+                MainOptions::StartTime(_)
+                    | MainOptions::EndTime(_)
+                    | MainOptions::SinceId(_)
+                    | MainOptions::UntilId(_)
+                    | MainOptions::NextToken(_)
+                    | MainOptions::Granularity(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Gets the option used to request the page a pagination token points to.
+    /// Search and count endpoints call it `next_token`, the rest `pagination_token`.
+    pub fn pagination_option(&self, token: &str) -> MainOptions {
+        match self {
+            Endpoint::SearchTweetsRecent
+            | Endpoint::SearchTweetsAll
+            | Endpoint::LookupTweetsCountRecent
+            | Endpoint::LookupTweetsCountAll => MainOptions::NextToken(String::from(token)),
+            _ => MainOptions::PaginationToken(String::from(token)),
+        }
+    }
+
+    /// True if the endpoint answers with a stream that never ends, read with `send_streaming`
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, Endpoint::StreamTweets)
+    }
+
+    /// Gets how many of the most recent tweets a timeline endpoint can paginate through,
+    /// or None if the endpoint has no such limit.
+    pub fn history_limit(&self, options: &[MainOptions]) -> Option<u32> {
+        let excludes_replies = options.iter().any(|o| match o {
+            MainOptions::Exclude(excludes) => excludes.contains(&Exclude::Replies),
+            _ => false,
+        });
+        match self {
+            Endpoint::TimelineUserTweets(_) | Endpoint::TimelineReverseChronological(_) => {
+                if excludes_replies {
+                    Some(800)
+                } else {
+                    Some(3200)
+                }
+            }
+            Endpoint::TimelineUserMentions(_) => Some(800),
+            _ => None,
+        }
+    }
+
+    /// The range the `max_results` option must be within for this endpoint
+    fn max_results_range(&self) -> (u16, u16) {
+        match self {
+            Endpoint::TimelineUserTweets(_)
+            | Endpoint::TimelineUserMentions(_)
+            | Endpoint::TimelineReverseChronological(_)
+            | Endpoint::UserLikedTweets(_) => (5, 100),
+            Endpoint::LookupTweetLikingUsers(_)
+            | Endpoint::LookupTweetRetweetedBy(_)
+            | Endpoint::ListTweets(_)
+            | Endpoint::ListMembers(_)
+            | Endpoint::ListFollowers(_)
+            | Endpoint::UserOwnedLists(_)
+            | Endpoint::UserListMemberships(_)
+            | Endpoint::UserBookmarks(_)
+            | Endpoint::DmEvents
+            | Endpoint::DmConversationEvents(_)
+            | Endpoint::DmConversationWithEvents(_)
+            | Endpoint::SearchSpaces
+            | Endpoint::SpaceBuyers(_)
+            | Endpoint::SpaceTweets(_) => (1, 100),
+            Endpoint::UserFollowers(_)
+            | Endpoint::UserFollowing(_)
+            | Endpoint::UserBlocking(_)
+            | Endpoint::UserMuting(_) => (1, 1000),
+            Endpoint::SearchTweetsAll => (10, 500),
+            _ => (10, 100),
+        }
+    }
+
+    /// Turns the query filters into the query parameters this endpoint expects.
+    /// Returns an Err if the filters this endpoint needs for the method are missing.
+    pub fn get_query_params(
+        &self,
+        method: &Method,
+        query_filters: &QueryFilters,
+    ) -> Result<Vec<(String, String)>, TwitterBuilderError> {
+        let mut params = Vec::new();
+        match self {
+            Endpoint::LookupTweets | Endpoint::UsersByIds if *method == Method::GET => {
+                // These endpoints look up tweets or users using provided IDS
+                match &query_filters.ids {
+                    Some(ids) if !ids.is_empty() => {
+                        params.push((String::from("ids"), ids.to_string()))
+                    }
+                    _ => {
+                        return Err(TwitterBuilderError::BadQueryError(format!(
+                            "No ids provided, cannot use {} endpoint",
+                            self
+                        )))
+                    }
+                }
+            }
+            Endpoint::SpacesByCreatorIds => match &query_filters.ids {
+                Some(ids) if !ids.is_empty() => {
+                    params.push((String::from("user_ids"), ids.to_string()))
+                }
+                _ => {
+                    return Err(TwitterBuilderError::BadQueryError(String::from(
+                        "No user ids provided, cannot look up spaces by creator ids",
+                    )))
                 }
-                let ids = ids.unwrap();
-                req.query(&[("ids", &ids.to_string())])
-            }
-            Endpoint::SearchTweetsRecent | Endpoint::SearchTweetsAll => {
-                let groups = query_filters.groups();
-                if groups.is_none() {
-                    return Err(TwitterError::BadQueryError(String::from(
-                        "No filters provided, cannot use search tweets endpoint",
+            },
+            Endpoint::LookupSpaces => {
+                let space_ids = query_filters.options().iter().find_map(|o| match o {
+                    MainOptions::SpaceIds(ids) => Some(ids),
+                    _ => None,
+                });
+                match space_ids {
+                    Some(ids) if !ids.is_empty() && ids.len() <= 100 => {}
+                    _ => {
+                        return Err(TwitterBuilderError::BadQueryError(String::from(
+                            "Between 1 and 100 space ids must be provided to look up spaces",
+                        )))
+                    }
+                }
+            }
+            // Space search takes plain keywords rather than the grouped query tweet search uses
+            Endpoint::SearchSpaces => match &query_filters.groups {
+                Some(groups) if !groups.no_filters() => {
+                    let keywords: Vec<String> = groups.into_iter().map(|f| f.to_string()).collect();
+                    params.push((String::from("query"), keywords.join(" ")))
+                }
+                _ => {
+                    return Err(TwitterBuilderError::BadQueryError(String::from(
+                        "No filters provided, cannot search spaces",
+                    )))
+                }
+            },
+            Endpoint::ComplianceJobs if *method == Method::GET => {
+                let has_type = query_filters
+                    .options()
+                    .iter()
+                    .any(|o| matches!(o, MainOptions::ComplianceType(_)));
+                if !has_type {
+                    return Err(TwitterBuilderError::BadQueryError(String::from(
+                        "The ComplianceType option is needed to list compliance jobs",
                     )));
                 }
-                let groups = groups.unwrap();
-                req.query(&[("query", &groups.to_string())])
-            }
-            _ => req, // Endpoint::LookupTweet(_) => {}
-                      // Endpoint::LookupTweetQuoteTweets(_) => {}
-                      // Endpoint::LookupTweetRetweetedBy(_) => {}
-                      // Endpoint::LookupTweetsCountRecent => {}
-                      // Endpoint::LookupTweetsCountAll => {}
-                      // Endpoint::TimelineUserTweets(_) => {}
-                      // Endpoint::TimelineUserMentions(_) => {}
-                      // Endpoint::StreamTweets => {}
-                      // Endpoint::StreamRules => {}
-                      // Endpoint::UsersByUsernames => {}
+            }
+            Endpoint::UsersByUsernames => match query_filters.usernames() {
+                Some(usernames) if !usernames.is_empty() => {
+                    params.push((String::from("usernames"), usernames.to_string()))
+                }
+                _ => {
+                    return Err(TwitterBuilderError::BadQueryError(String::from(
+                        "No usernames provided, cannot use users by usernames endpoint",
+                    )))
+                }
+            },
+            Endpoint::SearchTweetsRecent
+            | Endpoint::SearchTweetsAll
+            | Endpoint::LookupTweetsCountRecent
+            | Endpoint::LookupTweetsCountAll => match &query_filters.groups {
+                Some(groups) if !groups.no_filters() => {
+                    params.push((String::from("query"), groups.to_string()))
+                }
+                _ => {
+                    return Err(TwitterBuilderError::BadQueryError(format!(
+                        "No filters provided, cannot use {} endpoint",
+                        self
+                    )))
+                }
+            },
+            _ => {}
         };
-        // TODO add handling for different Endpoint variants, currently only does those that store filter groups in "query"
-        println!("######");
-        println!("{:?}", &req);
-        println!("######");
-        let req = req.send().await;
-        match req {
-            Ok(r) => Ok(r),
-            Err(e) => Err(TwitterError::RequestError(e)),
+        for option in query_filters.options() {
+            if !self.supports_option(option) {
+                return Err(TwitterBuilderError::BadQueryError(format!(
+                    "{} does not accept the {} option",
+                    self,
+                    option.get_query_tuple().0
+                )));
+            }
+            if let MainOptions::MaxResults(max) = option {
+                let (min_max, max_max) = self.max_results_range();
+                if !(min_max..=max_max).contains(&max.get()) {
+                    return Err(TwitterBuilderError::BadQueryError(format!(
+                        "max_results for {} must be between {} and {}",
+                        self, min_max, max_max
+                    )));
+                }
+            }
+            params.push(option.get_query_tuple());
         }
+        Ok(params)
     }
-}
 
-impl std::fmt::Display for Endpoint {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let url = match self {
-            Endpoint::LookupTweets => format!("{}/2/tweets", TWITTER_URL),
-            Endpoint::LookupTweet(tweet_id) => format!("{}/2/tweets/{}", TWITTER_URL, tweet_id),
+    /// Gets the URL of the endpoint on the API at `base_url`. Twitter's own API is at
+    /// `TWITTER_URL`, other base URLs are used to send requests to a mock server.
+    pub fn url(&self, base_url: &str) -> String {
+        match self {
+            Endpoint::LookupTweets => format!("{}/2/tweets", base_url),
+            Endpoint::LookupTweet(tweet_id) => format!("{}/2/tweets/{}", base_url, tweet_id),
             Endpoint::LookupTweetQuoteTweets(tweet_id) => {
-                format!("{}/2/tweets/{}/quote_tweets", TWITTER_URL, tweet_id)
+                format!("{}/2/tweets/{}/quote_tweets", base_url, tweet_id)
             }
             Endpoint::LookupTweetRetweetedBy(tweet_id) => {
-                format!("{}/2/tweets/{}/retweeted_by", TWITTER_URL, tweet_id)
+                format!("{}/2/tweets/{}/retweeted_by", base_url, tweet_id)
             }
-            Endpoint::LookupTweetsCountRecent => format!("{}/2/tweets/counts/recent", TWITTER_URL),
-            Endpoint::LookupTweetsCountAll => format!("{}/2/tweets/counts/all", TWITTER_URL),
-            Endpoint::SearchTweetsRecent => format!("{}/2/tweets/search/recent", TWITTER_URL),
-            Endpoint::SearchTweetsAll => format!("{}/2/tweets/search/all", TWITTER_URL),
+            Endpoint::LookupTweetsCountRecent => format!("{}/2/tweets/counts/recent", base_url),
+            Endpoint::LookupTweetsCountAll => format!("{}/2/tweets/counts/all", base_url),
+            Endpoint::SearchTweetsRecent => format!("{}/2/tweets/search/recent", base_url),
+            Endpoint::SearchTweetsAll => format!("{}/2/tweets/search/all", base_url),
             Endpoint::TimelineUserTweets(user_id) => {
-                format!("{}/2/users/{}/tweets", TWITTER_URL, user_id)
+                format!("{}/2/users/{}/tweets", base_url, user_id)
             }
             Endpoint::TimelineUserMentions(user_id) => {
-                format!("{}/2/users/{}/mentions", TWITTER_URL, user_id)
+                format!("{}/2/users/{}/mentions", base_url, user_id)
+            }
+            Endpoint::TimelineReverseChronological(user_id) => {
+                format!(
+                    "{}/2/users/{}/timelines/reverse_chronological",
+                    base_url, user_id
+                )
+            }
+            Endpoint::StreamTweets => format!("{}/2/tweets/search/stream", base_url),
+            Endpoint::StreamRules => format!("{}/2/tweets/search/stream/rules", base_url),
+            Endpoint::UserById(user_id) => format!("{}/2/users/{}", base_url, user_id),
+            Endpoint::UsersByIds => format!("{}/2/users", base_url),
+            Endpoint::UserByUsername(username) => {
+                format!("{}/2/users/by/username/{}", base_url, username)
+            }
+            Endpoint::UsersByUsernames => format!("{}/2/users/by", base_url),
+            Endpoint::Me => format!("{}/2/users/me", base_url),
+            Endpoint::UserFollowers(user_id) => {
+                format!("{}/2/users/{}/followers", base_url, user_id)
+            }
+            Endpoint::UserFollowing(user_id) => {
+                format!("{}/2/users/{}/following", base_url, user_id)
+            }
+            Endpoint::UserUnfollow(source_user_id, target_user_id) => format!(
+                "{}/2/users/{}/following/{}",
+                base_url, source_user_id, target_user_id
+            ),
+            Endpoint::LookupTweetLikingUsers(tweet_id) => {
+                format!("{}/2/tweets/{}/liking_users", base_url, tweet_id)
+            }
+            Endpoint::UserLikedTweets(user_id) => {
+                format!("{}/2/users/{}/liked_tweets", base_url, user_id)
+            }
+            Endpoint::UserLikes(user_id) => format!("{}/2/users/{}/likes", base_url, user_id),
+            Endpoint::UserUnlike(user_id, tweet_id) => {
+                format!("{}/2/users/{}/likes/{}", base_url, user_id, tweet_id)
+            }
+            Endpoint::UserRetweets(user_id) => {
+                format!("{}/2/users/{}/retweets", base_url, user_id)
+            }
+            Endpoint::UserUnretweet(user_id, source_tweet_id) => format!(
+                "{}/2/users/{}/retweets/{}",
+                base_url, user_id, source_tweet_id
+            ),
+            Endpoint::LookupList(list_id) => format!("{}/2/lists/{}", base_url, list_id),
+            Endpoint::CreateList => format!("{}/2/lists", base_url),
+            Endpoint::ListTweets(list_id) => format!("{}/2/lists/{}/tweets", base_url, list_id),
+            Endpoint::ListMembers(list_id) => {
+                format!("{}/2/lists/{}/members", base_url, list_id)
             }
-            Endpoint::StreamTweets => format!("{}/2/tweets/search/stream", TWITTER_URL),
-            Endpoint::StreamRules => format!("{}/2/tweets/search/stream/rules", TWITTER_URL),
-            Endpoint::UsersByUsernames => format!("{}/2/users/by", TWITTER_URL),
+            Endpoint::ListRemoveMember(list_id, user_id) => {
+                format!("{}/2/lists/{}/members/{}", base_url, list_id, user_id)
+            }
+            Endpoint::ListFollowers(list_id) => {
+                format!("{}/2/lists/{}/followers", base_url, list_id)
+            }
+            Endpoint::UserOwnedLists(user_id) => {
+                format!("{}/2/users/{}/owned_lists", base_url, user_id)
+            }
+            Endpoint::UserListMemberships(user_id) => {
+                format!("{}/2/users/{}/list_memberships", base_url, user_id)
+            }
+            Endpoint::UserPinnedLists(user_id) => {
+                format!("{}/2/users/{}/pinned_lists", base_url, user_id)
+            }
+            Endpoint::UserUnpinList(user_id, list_id) => {
+                format!("{}/2/users/{}/pinned_lists/{}", base_url, user_id, list_id)
+            }
+            Endpoint::UserBookmarks(user_id) => {
+                format!("{}/2/users/{}/bookmarks", base_url, user_id)
+            }
+            Endpoint::UserRemoveBookmark(user_id, tweet_id) => {
+                format!("{}/2/users/{}/bookmarks/{}", base_url, user_id, tweet_id)
+            }
+            Endpoint::UserBlocking(user_id) => {
+                format!("{}/2/users/{}/blocking", base_url, user_id)
+            }
+            Endpoint::UserUnblock(source_user_id, target_user_id) => format!(
+                "{}/2/users/{}/blocking/{}",
+                base_url, source_user_id, target_user_id
+            ),
+            Endpoint::UserMuting(user_id) => format!("{}/2/users/{}/muting", base_url, user_id),
+            Endpoint::UserUnmute(source_user_id, target_user_id) => format!(
+                "{}/2/users/{}/muting/{}",
+                base_url, source_user_id, target_user_id
+            ),
+            Endpoint::LookupTweetHidden(tweet_id) => {
+                format!("{}/2/tweets/{}/hidden", base_url, tweet_id)
+            }
+            Endpoint::DmEvents => format!("{}/2/dm_events", base_url),
+            Endpoint::DmConversationEvents(dm_conversation_id) => format!(
+                "{}/2/dm_conversations/{}/dm_events",
+                base_url, dm_conversation_id
+            ),
+            Endpoint::DmConversationWithEvents(participant_id) => format!(
+                "{}/2/dm_conversations/with/{}/dm_events",
+                base_url, participant_id
+            ),
+            Endpoint::DmConversationMessages(dm_conversation_id) => format!(
+                "{}/2/dm_conversations/{}/messages",
+                base_url, dm_conversation_id
+            ),
+            Endpoint::DmConversationWithMessages(participant_id) => format!(
+                "{}/2/dm_conversations/with/{}/messages",
+                base_url, participant_id
+            ),
+            Endpoint::DmConversations => format!("{}/2/dm_conversations", base_url),
+            Endpoint::LookupSpace(space_id) => format!("{}/2/spaces/{}", base_url, space_id),
+            Endpoint::LookupSpaces => format!("{}/2/spaces", base_url),
+            Endpoint::SpacesByCreatorIds => format!("{}/2/spaces/by/creator_ids", base_url),
+            Endpoint::SearchSpaces => format!("{}/2/spaces/search", base_url),
+            Endpoint::SpaceBuyers(space_id) => {
+                format!("{}/2/spaces/{}/buyers", base_url, space_id)
+            }
+            Endpoint::SpaceTweets(space_id) => {
+                format!("{}/2/spaces/{}/tweets", base_url, space_id)
+            }
+            Endpoint::ComplianceJobs => format!("{}/2/compliance/jobs", base_url),
+            Endpoint::ComplianceJob(job_id) => {
+                format!("{}/2/compliance/jobs/{}", base_url, job_id)
+            }
+        }
+    }
+
+    pub async fn send_request(
+        &self,
+        client: &Client,
+        method: Method,
+        query_filters: QueryFilters,
+        auth: AuthenticationData,
+        body: Option<serde_json::Value>,
+    ) -> Result<Response, TwitterError> {
+        self.send_request_to(TWITTER_URL, client, method, query_filters, auth, body)
+            .await
+    }
+
+    /// Like `send_request`, but sends the request to the API at `base_url` instead of twitter
+    pub async fn send_request_to(
+        &self,
+        base_url: &str,
+        client: &Client,
+        method: Method,
+        query_filters: QueryFilters,
+        auth: AuthenticationData,
+        body: Option<serde_json::Value>,
+    ) -> Result<Response, TwitterError> {
+        let request =
+            self.build_request_to(base_url, client, method, &query_filters, &auth, body)?;
+        client
+            .execute(request)
+            .await
+            .map_err(TwitterError::RequestError)
+    }
+
+    /// Builds the authorized HTTP request `send_request_to` sends
+    pub(crate) fn build_request_to(
+        &self,
+        base_url: &str,
+        client: &Client,
+        method: Method,
+        query_filters: &QueryFilters,
+        auth: &AuthenticationData,
+        body: Option<serde_json::Value>,
+    ) -> Result<reqwest::Request, TwitterError> {
+        let required_auth = match self.get_auth_type(&method) {
+            Ok(auth_type) => auth_type,
+            Err(_) => return Err(TwitterError::BadMethod(self.clone(), method)),
+        };
+        if !auth.get_type().satisfies(&required_auth) {
+            return Err(TwitterError::WrongAuthError(
+                self.clone(),
+                auth.get_type(),
+                method,
+            ));
+        }
+        let params = self
+            .get_query_params(&method, query_filters)
+            .map_err(|e| TwitterError::BadQueryError(e.to_string()))?;
+        let url = self.url(base_url);
+        let req = client.request(method.clone(), &url).query(&params);
+        let req = auth.authorize(req, &method, &url, &params);
+        let req = match body {
+            Some(body) => req.json(&body),
+            None => req,
         };
-        write!(f, "{}", url)
+        req.build().map_err(TwitterError::RequestError)
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url(TWITTER_URL))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::twitter::query_filters::filter::{Exact, Filter, Is};
+    use crate::twitter::query_params::expansions::Expansions;
+    use crate::twitter::query_params::main_options::{Granularity, SearchState};
+    use crate::twitter::twitter_objects::compliance::{ComplianceJobStatus, ComplianceJobType};
+    use crate::twitter::twitter_objects::dm::{DmEventField, DmEventType};
+    use crate::twitter::twitter_objects::space::SpaceField;
+    use crate::twitter::twitter_objects::{list::ListField, tweet::TweetField, user::UserField};
 
     #[test]
     fn lookup_tweets_test() {
@@ -334,6 +1229,9 @@ mod tests {
                 AuthenticationType::OauthSignature => {
                     panic!("Should return Some(BearerToken), not OAuthSignature");
                 }
+                AuthenticationType::OAuth2UserToken => {
+                    panic!("Should return Some(BearerToken), not OAuth2UserToken");
+                }
             },
         }
         match endpoint.get_auth_type(&Method::POST) {
@@ -345,6 +1243,9 @@ mod tests {
                     panic!("Should have returned a OAuthSignature, not a BearerToken.")
                 }
                 AuthenticationType::OauthSignature => {}
+                AuthenticationType::OAuth2UserToken => {
+                    panic!("Should have returned a OAuthSignature, not a OAuth2UserToken.")
+                }
             },
         }
     }
@@ -372,59 +1273,917 @@ mod tests {
             AuthenticationType::BearerToken
         );
     }
-    // TODO fix tests below
     #[test]
     fn lookup_retweeted_by_test() {
         let endpoint = Endpoint::LookupTweetRetweetedBy(1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/1/retweeted_by")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
     }
 
     #[test]
     fn lookup_tweets_count_recent_test() {
         let endpoint = Endpoint::LookupTweetsCountRecent;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/counts/recent")
+        );
+        check_methods(&endpoint, &[Method::GET]);
     }
 
     #[test]
     fn lookup_tweets_count_all_test() {
         let endpoint = Endpoint::LookupTweetsCountAll;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/counts/all")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        let filters = QueryFilters::new()
+            .add_and_filter(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is))
+            .add_option(MainOptions::Granularity(Granularity::Day))
+            .build()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("query"), String::from("(rust)")),
+                (String::from("granularity"), String::from("day")),
+            ]
+        );
+        assert!(
+            !Endpoint::SearchTweetsAll.supports_option(&MainOptions::Granularity(Granularity::Day))
+        );
     }
 
     #[test]
     fn search_tweets_recent_test() {
         let endpoint = Endpoint::SearchTweetsRecent;
-    }
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/search/recent")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert!(QueryFilters::new().build().is_err());
+        let empty = QueryFilters::new().build_for_request().unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &empty).is_err());
+    }
 
     #[test]
     fn search_tweets_all_test() {
         let endpoint = Endpoint::SearchTweetsAll;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/search/all")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        let filters = QueryFilters::new()
+            .add_and_filter(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is))
+            .add_option(MainOptions::MaxResults(500.into()))
+            .build()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_ok());
+        assert!(Endpoint::SearchTweetsRecent
+            .get_query_params(&Method::GET, &filters)
+            .is_err());
     }
 
     #[test]
     fn timeline_user_tweets_test() {
-        let endpoint = Endpoint::TimelineUserTweets(String::from("test_user"));
+        let endpoint = Endpoint::TimelineUserTweets(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2244994945/tweets")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(endpoint.history_limit(&[]), Some(3200));
+        let exclude = MainOptions::Exclude(vec![Exclude::Retweets, Exclude::Replies]);
+        assert_eq!(
+            endpoint.history_limit(std::slice::from_ref(&exclude)),
+            Some(800)
+        );
+
+        let filters = QueryFilters::new()
+            .add_option(exclude)
+            .add_option(MainOptions::SinceId(1.into()))
+            .add_option(MainOptions::MaxResults(5.into()))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("exclude"), String::from("retweets,replies")),
+                (String::from("since_id"), String::from("1")),
+                (String::from("max_results"), String::from("5")),
+            ]
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::NextToken(String::from("token")))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_err());
     }
 
     #[test]
     fn timeline_user_mentions_test() {
-        let endpoint = Endpoint::TimelineUserMentions(String::from("test_user"));
+        let endpoint = Endpoint::TimelineUserMentions(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2244994945/mentions")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(endpoint.history_limit(&[]), Some(800));
+        assert!(!endpoint.supports_option(&MainOptions::Exclude(vec![Exclude::Replies])));
+    }
+
+    #[test]
+    fn timeline_reverse_chronological_test() {
+        let endpoint = Endpoint::TimelineReverseChronological(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from(
+                "https://api.twitter.com/2/users/2244994945/timelines/reverse_chronological"
+            )
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let start =
+            chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2022, 10, 1, 12, 0, 0).unwrap();
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::StartTime(start))
+            .add_option(MainOptions::PaginationToken(String::from("abc")))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (
+                    String::from("start_time"),
+                    String::from("2022-10-01T12:00:00Z")
+                ),
+                (String::from("pagination_token"), String::from("abc")),
+            ]
+        );
     }
 
     #[test]
     fn stream_tweets_test() {
         let endpoint = Endpoint::StreamTweets;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/search/stream")
+        );
+        check_methods(&endpoint, &[Method::GET]);
     }
 
     #[test]
     fn stream_tweets_rules_test() {
         let endpoint = Endpoint::StreamRules;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/search/stream/rules")
+        );
+        check_methods(&endpoint, &[Method::GET, Method::POST]);
     }
 
     #[test]
     fn users_by_usernames_test() {
         let endpoint = Endpoint::UsersByUsernames;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/by")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+
+        let filters = QueryFilters::new()
+            .add_username("jack")
+            .add_username("@TwitterDev")
+            .build()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![(String::from("usernames"), String::from("jack,TwitterDev"))]
+        );
+        let empty = QueryFilters::new().build_for_request().unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &empty).is_err());
+        assert!(QueryFilters::new()
+            .add_username("not a handle")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn user_lookup_test() {
+        let endpoint = Endpoint::UserById(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2244994945")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+
+        let endpoint = Endpoint::UsersByIds;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users")
+        );
+        let filters = QueryFilters::new().add_id(1).add_id(2).build().unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![(String::from("ids"), String::from("1,2"))]
+        );
+
+        let endpoint = Endpoint::UserByUsername(Username::try_from("TwitterDev").unwrap());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/by/username/TwitterDev")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+    }
+
+    #[test]
+    fn me_test() {
+        let endpoint = Endpoint::Me;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/me")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn follows_test() {
+        let endpoint = Endpoint::UserFollowers(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2244994945/followers")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::MaxResults(1000.into()))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_ok());
+
+        let endpoint = Endpoint::UserFollowing(2244994945.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2244994945/following")
+        );
+        check_methods(&endpoint, &[Method::GET, Method::POST]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::UserUnfollow(1.into(), 2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/1/following/2")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn likes_test() {
+        let endpoint = Endpoint::LookupTweetLikingUsers(1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/1/liking_users")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+
+        let endpoint = Endpoint::UserLikedTweets(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/liked_tweets")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::MaxResults(200.into()))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_err());
+
+        let endpoint = Endpoint::UserLikes(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/likes")
+        );
+        assert_eq!(endpoint.get_methods(), vec![Method::POST]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::UserUnlike(2.into(), 1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/likes/1")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn retweets_test() {
+        let endpoint = Endpoint::LookupTweetQuoteTweets(1.into());
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::Exclude(vec![Exclude::Retweets]))
+            .add_option(MainOptions::TweetFields(vec![
+                TweetField::CreatedAt,
+                TweetField::Entities,
+            ]))
+            .add_option(MainOptions::Expansions(vec![Expansions::AuthorId]))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("exclude"), String::from("retweets")),
+                (
+                    String::from("tweet.fields"),
+                    String::from("created_at,entities")
+                ),
+                (String::from("expansions"), String::from("author_id")),
+            ]
+        );
+
+        let endpoint = Endpoint::LookupTweetRetweetedBy(1.into());
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::UserFields(vec![UserField::PublicMetrics]))
+            .add_option(MainOptions::PaginationToken(String::from("next")))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("user.fields"), String::from("public_metrics")),
+                (String::from("pagination_token"), String::from("next")),
+            ]
+        );
+
+        let endpoint = Endpoint::UserRetweets(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/retweets")
+        );
+        assert_eq!(endpoint.get_methods(), vec![Method::POST]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        assert!(!endpoint.supports_option(&MainOptions::Expansions(vec![])));
+
+        let endpoint = Endpoint::UserUnretweet(2.into(), 1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/retweets/1")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn lists_test() {
+        let endpoint = Endpoint::LookupList(3.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists/3")
+        );
+        check_methods(&endpoint, &[Method::GET, Method::PUT, Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+        assert_eq!(
+            endpoint.get_auth_type(&Method::PUT).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::ListFields(vec![
+                ListField::OwnerId,
+                ListField::MemberCount,
+            ]))
+            .add_option(MainOptions::Expansions(vec![Expansions::OwnerId]))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (
+                    String::from("list.fields"),
+                    String::from("owner_id,member_count")
+                ),
+                (String::from("expansions"), String::from("owner_id")),
+            ]
+        );
+
+        let endpoint = Endpoint::CreateList;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists")
+        );
+        assert_eq!(endpoint.get_methods(), vec![Method::POST]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::ListTweets(3.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists/3/tweets")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_err());
+
+        let endpoint = Endpoint::ListMembers(3.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists/3/members")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::MaxResults(5.into()))
+            .add_option(MainOptions::PaginationToken(String::from("token")))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_ok());
+
+        let endpoint = Endpoint::ListRemoveMember(3.into(), 2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists/3/members/2")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+
+        let endpoint = Endpoint::ListFollowers(3.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/lists/3/followers")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+
+        let endpoint = Endpoint::UserOwnedLists(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/owned_lists")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+
+        let endpoint = Endpoint::UserListMemberships(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/list_memberships")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+
+        let endpoint = Endpoint::UserPinnedLists(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/pinned_lists")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::UserUnpinList(2.into(), 3.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/pinned_lists/3")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn bookmarks_test() {
+        let endpoint = Endpoint::UserBookmarks(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/bookmarks")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        for method in [Method::GET, Method::POST] {
+            assert_eq!(
+                endpoint.get_auth_type(&method).unwrap(),
+                AuthenticationType::OAuth2UserToken
+            );
+        }
+        assert!(!AuthenticationType::OauthSignature.satisfies(&AuthenticationType::OAuth2UserToken));
+        assert!(!AuthenticationType::BearerToken.satisfies(&AuthenticationType::OAuth2UserToken));
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::MaxResults(100.into()))
+            .add_option(MainOptions::TweetFields(vec![TweetField::CreatedAt]))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_ok());
+
+        let endpoint = Endpoint::UserRemoveBookmark(2.into(), 1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/bookmarks/1")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OAuth2UserToken
+        );
+    }
+
+    #[test]
+    fn blocks_and_mutes_test() {
+        let endpoint = Endpoint::UserBlocking(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/blocking")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::MaxResults(1000.into()))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_ok());
+
+        let endpoint = Endpoint::UserUnblock(2.into(), 1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/blocking/1")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+
+        let endpoint = Endpoint::UserMuting(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/muting")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::UserUnmute(2.into(), 1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/users/2/muting/1")
+        );
+        check_methods(&endpoint, &[Method::DELETE]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::DELETE).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+    }
+
+    #[test]
+    fn hide_replies_test() {
+        let endpoint = Endpoint::LookupTweetHidden(1.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/tweets/1/hidden")
+        );
+        check_methods(&endpoint, &[Method::PUT]);
+        assert!(endpoint.get_auth_type(&Method::POST).is_err());
+        assert_eq!(
+            endpoint.get_auth_type(&Method::PUT).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::TweetFields(vec![TweetField::CreatedAt]))
+            .build_for_request()
+            .unwrap();
+        assert!(endpoint.get_query_params(&Method::PUT, &filters).is_err());
+    }
+
+    #[test]
+    fn direct_messages_test() {
+        let endpoint = Endpoint::DmEvents;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_events")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::EventTypes(vec![DmEventType::MessageCreate]))
+            .add_option(MainOptions::DmEventFields(vec![
+                DmEventField::SenderId,
+                DmEventField::Text,
+            ]))
+            .add_option(MainOptions::Expansions(vec![Expansions::SenderId]))
+            .add_option(MainOptions::MaxResults(100.into()))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("event_types"), String::from("MessageCreate")),
+                (
+                    String::from("dm_event.fields"),
+                    String::from("sender_id,text")
+                ),
+                (String::from("expansions"), String::from("sender_id")),
+                (String::from("max_results"), String::from("100")),
+            ]
+        );
+
+        let endpoint = Endpoint::DmConversationEvents(String::from("1-2"));
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_conversations/1-2/dm_events")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+
+        let endpoint = Endpoint::DmConversationWithEvents(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_conversations/with/2/dm_events")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+
+        let endpoint = Endpoint::DmConversationMessages(String::from("1-2"));
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_conversations/1-2/messages")
+        );
+        assert_eq!(endpoint.get_methods(), vec![Method::POST]);
+        assert!(endpoint.get_query_params(&Method::POST, &filters).is_err());
+
+        let endpoint = Endpoint::DmConversationWithMessages(2.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_conversations/with/2/messages")
+        );
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::OauthSignature
+        );
+
+        let endpoint = Endpoint::DmConversations;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/dm_conversations")
+        );
+        assert_eq!(endpoint.get_methods(), vec![Method::POST]);
+    }
+
+    #[test]
+    fn spaces_test() {
+        let endpoint = Endpoint::LookupSpace(String::from("1DXxyRYNejbKM"));
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces/1DXxyRYNejbKM")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::SpaceFields(vec![
+                SpaceField::Title,
+                SpaceField::HostIds,
+            ]))
+            .add_option(MainOptions::Expansions(vec![Expansions::HostIds]))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("space.fields"), String::from("title,host_ids")),
+                (String::from("expansions"), String::from("host_ids")),
+            ]
+        );
+        assert!(Endpoint::SpaceTweets(String::from("1DXxyRYNejbKM"))
+            .get_query_params(&Method::GET, &filters)
+            .is_err());
+
+        let endpoint = Endpoint::LookupSpaces;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces")
+        );
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_err());
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::SpaceIds(vec![
+                String::from("1DXxyRYNejbKM"),
+                String::from("1nAJELYEEPvGL"),
+            ]))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![(
+                String::from("ids"),
+                String::from("1DXxyRYNejbKM,1nAJELYEEPvGL")
+            )]
+        );
+
+        let endpoint = Endpoint::SpacesByCreatorIds;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces/by/creator_ids")
+        );
+        let filters = QueryFilters::new().add_id(2).build().unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![(String::from("user_ids"), String::from("2"))]
+        );
+
+        let endpoint = Endpoint::SearchSpaces;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces/search")
+        );
+        let filters = QueryFilters::new()
+            .add_and_filter(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is))
+            .add_option(MainOptions::State(SearchState::Live))
+            .build()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("query"), String::from("rust")),
+                (String::from("state"), String::from("live")),
+            ]
+        );
+
+        let endpoint = Endpoint::SpaceBuyers(String::from("1DXxyRYNejbKM"));
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces/1DXxyRYNejbKM/buyers")
+        );
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::OAuth2UserToken
+        );
+        // OAuth 1.0a is user context too, but the endpoint does not accept it
+        assert!(!AuthenticationType::OauthSignature
+            .satisfies(&endpoint.get_auth_type(&Method::GET).unwrap()));
+
+        let endpoint = Endpoint::SpaceTweets(String::from("1DXxyRYNejbKM"));
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/spaces/1DXxyRYNejbKM/tweets")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+    }
+
+    #[test]
+    fn compliance_jobs_test() {
+        let endpoint = Endpoint::ComplianceJobs;
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/compliance/jobs")
+        );
+        assert_eq!(
+            endpoint.url("http://127.0.0.1:8080"),
+            String::from("http://127.0.0.1:8080/2/compliance/jobs")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert!(endpoint.get_methods().contains(&Method::POST));
+        assert_eq!(
+            endpoint.get_auth_type(&Method::POST).unwrap(),
+            AuthenticationType::BearerToken
+        );
+        let filters = QueryFilters::new().build_for_request().unwrap();
+        assert!(endpoint.get_query_params(&Method::GET, &filters).is_err());
+        assert!(endpoint.get_query_params(&Method::POST, &filters).is_ok());
+        let filters = QueryFilters::new()
+            .add_option(MainOptions::ComplianceType(ComplianceJobType::Tweets))
+            .add_option(MainOptions::ComplianceStatus(
+                ComplianceJobStatus::InProgress,
+            ))
+            .build_for_request()
+            .unwrap();
+        assert_eq!(
+            endpoint.get_query_params(&Method::GET, &filters).unwrap(),
+            vec![
+                (String::from("type"), String::from("tweets")),
+                (String::from("status"), String::from("in_progress")),
+            ]
+        );
+        assert!(!endpoint.supports_option(&MainOptions::TweetFields(vec![TweetField::Id])));
+
+        let endpoint = Endpoint::ComplianceJob(1382081613278814209.into());
+        assert_eq!(
+            endpoint.to_string(),
+            String::from("https://api.twitter.com/2/compliance/jobs/1382081613278814209")
+        );
+        check_methods(&endpoint, &[Method::GET]);
+        assert_eq!(
+            endpoint.get_auth_type(&Method::GET).unwrap(),
+            AuthenticationType::BearerToken
+        );
+    }
+
+    #[test]
+    fn url_test() {
+        let endpoint = Endpoint::LookupTweet(1460323737035677698.into());
+        assert_eq!(
+            endpoint.url("http://127.0.0.1:8080"),
+            String::from("http://127.0.0.1:8080/2/tweets/1460323737035677698")
+        );
+        assert_eq!(endpoint.url(TWITTER_URL), endpoint.to_string());
+    }
+
+    #[test]
+    fn access_level_test() {
+        assert_eq!(
+            Endpoint::LookupTweets.get_access_level(&Method::POST),
+            Ok(AccessLevel::Free)
+        );
+        assert_eq!(
+            Endpoint::LookupTweets.get_access_level(&Method::GET),
+            Ok(AccessLevel::Basic)
+        );
+        assert_eq!(
+            Endpoint::LookupTweet(1.into()).get_access_level(&Method::DELETE),
+            Ok(AccessLevel::Free)
+        );
+        assert_eq!(
+            Endpoint::Me.get_access_level(&Method::GET),
+            Ok(AccessLevel::Free)
+        );
+        assert_eq!(
+            Endpoint::SearchTweetsRecent.get_access_level(&Method::GET),
+            Ok(AccessLevel::Basic)
+        );
+        for endpoint in [
+            Endpoint::SearchTweetsAll,
+            Endpoint::LookupTweetsCountAll,
+            Endpoint::LookupTweetsCountRecent,
+            Endpoint::StreamTweets,
+        ] {
+            assert_eq!(
+                endpoint.get_access_level(&Method::GET),
+                Ok(AccessLevel::Pro)
+            );
+        }
+        assert_eq!(Endpoint::Me.get_access_level(&Method::DELETE), Err(()));
     }
 
-    fn check_methods(endpoint: &Endpoint, expected_methods: &Vec<Method>) {
-        let other_methods = vec![Method::GET, Method::DELETE, Method::PUT, Method::HEAD];
+    fn check_methods(endpoint: &Endpoint, expected_methods: &[Method]) {
+        let other_methods = [Method::GET, Method::DELETE, Method::PUT, Method::HEAD];
         let other_methods: Vec<&Method> = other_methods
             .iter()
             .filter(|element| !expected_methods.contains(element))
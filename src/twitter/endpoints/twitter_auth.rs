@@ -18,11 +18,42 @@
 */
 
 use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(PartialEq, Debug)]
+use base64::Engine as _;
+use hmac::{Hmac, Mac};
+use http::Method;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::RequestBuilder;
+use sha1::Sha1;
+
+use crate::twitter::access_level::AccessLevel;
+
+/// The characters OAuth 1.0a requires to be percent encoded, everything but the
+/// RFC 3986 unreserved characters.
+const OAUTH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum AuthenticationType {
     BearerToken,
     OauthSignature,
+    OAuth2UserToken,
+}
+impl AuthenticationType {
+    /// Checks if credentials of this type can be used where `required` is asked for.
+    /// Both OAuth 1.0a and OAuth 2.0 user tokens act in a user's context, so an
+    /// OAuth 2.0 user token is accepted where an OAuth 1.0a signature is required.
+    pub fn satisfies(&self, required: &AuthenticationType) -> bool {
+        self == required
+            || (*self == AuthenticationType::OAuth2UserToken
+                && *required == AuthenticationType::OauthSignature)
+    }
 }
 impl Display for AuthenticationType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -33,34 +64,245 @@ impl Display for AuthenticationType {
             AuthenticationType::OauthSignature => {
                 write!(f, "OAuth1.0")
             }
+            AuthenticationType::OAuth2UserToken => {
+                write!(f, "OAuth2.0 user token")
+            }
         }
     }
 }
 
+/// The four keys needed to sign a request with OAuth 1.0a
+#[derive(Clone)]
+struct OAuthKeys {
+    consumer_key: String,
+    consumer_secret: String,
+    access_token: String,
+    access_token_secret: String,
+}
+
+#[derive(Clone)]
 pub struct AuthenticationData {
     auth_token: String,
     auth_type: AuthenticationType,
+    oauth_keys: Option<OAuthKeys>,
+    access_level: Option<AccessLevel>,
+}
+/// Shows only the type of authentication, so tokens don't end up in logs
+impl std::fmt::Debug for AuthenticationData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthenticationData")
+            .field("auth_type", &self.auth_type)
+            .field("access_level", &self.access_level)
+            .finish_non_exhaustive()
+    }
 }
 impl AuthenticationData {
     pub fn new_bearer_auth(token: &str) -> AuthenticationData {
         AuthenticationData {
             auth_token: String::from(token),
             auth_type: AuthenticationType::BearerToken,
+            oauth_keys: None,
+            access_level: None,
         }
     }
-    pub fn get_oauth_data() -> ! {
-        // TODO find out how to construct oauth data
-        todo!()
+    /// Creates OAuth 1.0a user context authentication from the app's consumer keys
+    /// and the user's access token and secret
+    pub fn new_oauth_auth(
+        consumer_key: &str,
+        consumer_secret: &str,
+        access_token: &str,
+        access_token_secret: &str,
+    ) -> AuthenticationData {
+        AuthenticationData {
+            auth_token: String::from(access_token),
+            auth_type: AuthenticationType::OauthSignature,
+            oauth_keys: Some(OAuthKeys {
+                consumer_key: String::from(consumer_key),
+                consumer_secret: String::from(consumer_secret),
+                access_token: String::from(access_token),
+                access_token_secret: String::from(access_token_secret),
+            }),
+            access_level: None,
+        }
+    }
+    /// Creates OAuth 2.0 user context authentication from a user access token
+    pub fn new_oauth2_user_auth(token: &str) -> AuthenticationData {
+        AuthenticationData {
+            auth_token: String::from(token),
+            auth_type: AuthenticationType::OAuth2UserToken,
+            oauth_keys: None,
+            access_level: None,
+        }
     }
     /// Gets the authentication type to check that the correct type is being used
     pub fn get_type(&self) -> AuthenticationType {
-        match self.auth_type {
-            AuthenticationType::BearerToken => AuthenticationType::BearerToken,
-            AuthenticationType::OauthSignature => AuthenticationType::OauthSignature,
+        self.auth_type
+    }
+    /// Sets the access tier of the app the credentials belong to, so requests the
+    /// tier does not give access to fail when they are built
+    pub fn with_access_level(mut self, access_level: AccessLevel) -> Self {
+        self.access_level = Some(access_level);
+        self
+    }
+    /// Gets the access tier of the app, None if it is not known
+    pub fn get_access_level(&self) -> Option<AccessLevel> {
+        self.access_level
+    }
+    /// Gets every token and secret of the credentials, to scrub them from recordings
+    pub(crate) fn secrets(&self) -> Vec<&str> {
+        let mut secrets = vec![self.auth_token.as_str()];
+        if let Some(keys) = &self.oauth_keys {
+            secrets.extend([
+                keys.consumer_key.as_str(),
+                keys.consumer_secret.as_str(),
+                keys.access_token.as_str(),
+                keys.access_token_secret.as_str(),
+            ]);
         }
+        secrets
     }
     /// Get the auth token to put into the request header
     pub fn get_auth_token(&self) -> &str {
         &self.auth_token
     }
+    /// Adds the authorization header to the request. `params` must hold all the query
+    /// parameters of the request, as OAuth 1.0a signs them along with the method and url.
+    pub(crate) fn authorize(
+        &self,
+        req: RequestBuilder,
+        method: &Method,
+        url: &str,
+        params: &[(String, String)],
+    ) -> RequestBuilder {
+        match &self.oauth_keys {
+            None => req.bearer_auth(&self.auth_token),
+            Some(keys) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("System time is before the unix epoch")
+                    .as_secs()
+                    .to_string();
+                let nonce: String = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect();
+                let header = oauth_header(keys, method, url, params, &nonce, &timestamp);
+                req.header(reqwest::header::AUTHORIZATION, header)
+            }
+        }
+    }
+}
+
+fn encode(s: &str) -> String {
+    utf8_percent_encode(s, OAUTH_ENCODE_SET).to_string()
+}
+
+/// Builds the `OAuth ...` authorization header value for a request
+fn oauth_header(
+    keys: &OAuthKeys,
+    method: &Method,
+    url: &str,
+    params: &[(String, String)],
+    nonce: &str,
+    timestamp: &str,
+) -> String {
+    let mut oauth_params = vec![
+        ("oauth_consumer_key", keys.consumer_key.as_str()),
+        ("oauth_nonce", nonce),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", timestamp),
+        ("oauth_token", keys.access_token.as_str()),
+        ("oauth_version", "1.0"),
+    ];
+    let signature = oauth_signature(keys, method, url, params, &oauth_params);
+    oauth_params.push(("oauth_signature", &signature));
+    let header_params: Vec<String> = oauth_params
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", encode(k), encode(v)))
+        .collect();
+    format!("OAuth {}", header_params.join(", "))
+}
+
+/// Creates the HMAC-SHA1 signature of the request as described in
+/// https://developer.twitter.com/en/docs/authentication/oauth-1-0a/creating-a-signature
+fn oauth_signature(
+    keys: &OAuthKeys,
+    method: &Method,
+    url: &str,
+    params: &[(String, String)],
+    oauth_params: &[(&str, &str)],
+) -> String {
+    let mut encoded: Vec<(String, String)> = params
+        .iter()
+        .map(|(k, v)| (encode(k), encode(v)))
+        .chain(oauth_params.iter().map(|(k, v)| (encode(k), encode(v))))
+        .collect();
+    encoded.sort();
+    let param_string = encoded
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<String>>()
+        .join("&");
+    let base_string = format!(
+        "{}&{}&{}",
+        method.as_str(),
+        encode(url),
+        encode(&param_string)
+    );
+    let signing_key = format!(
+        "{}&{}",
+        encode(&keys.consumer_secret),
+        encode(&keys.access_token_secret)
+    );
+    let mut mac = Hmac::<Sha1>::new_from_slice(signing_key.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(base_string.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oauth_signature_test() {
+        // The example from twitter's "Creating a signature" documentation
+        let keys = OAuthKeys {
+            consumer_key: String::from("xvz1evFS4wEEPTGEFPHBog"),
+            consumer_secret: String::from("kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw"),
+            access_token: String::from("370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb"),
+            access_token_secret: String::from("LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE"),
+        };
+        let params = vec![
+            (String::from("include_entities"), String::from("true")),
+            (
+                String::from("status"),
+                String::from("Hello Ladies + Gentlemen, a signed OAuth request!"),
+            ),
+        ];
+        let oauth_params = vec![
+            ("oauth_consumer_key", keys.consumer_key.as_str()),
+            ("oauth_nonce", "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg"),
+            ("oauth_signature_method", "HMAC-SHA1"),
+            ("oauth_timestamp", "1318622958"),
+            ("oauth_token", keys.access_token.as_str()),
+            ("oauth_version", "1.0"),
+        ];
+        let signature = oauth_signature(
+            &keys,
+            &Method::POST,
+            "https://api.twitter.com/1.1/statuses/update.json",
+            &params,
+            &oauth_params,
+        );
+        assert_eq!(signature, "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+    }
+
+    #[test]
+    fn user_token_satisfies_user_context() {
+        assert!(AuthenticationType::OAuth2UserToken.satisfies(&AuthenticationType::OauthSignature));
+        assert!(!AuthenticationType::BearerToken.satisfies(&AuthenticationType::OauthSignature));
+        assert!(!AuthenticationType::OauthSignature.satisfies(&AuthenticationType::OAuth2UserToken));
+    }
 }
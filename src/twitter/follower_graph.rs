@@ -0,0 +1,255 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use http::{Method, StatusCode};
+use reqwest::Client;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::query_params::main_options::MainOptions;
+use crate::twitter::request::TwitterRequest;
+use crate::twitter::twitter_objects::user::User;
+
+/// The users reached while walking a follower graph, and who follows whom
+#[derive(Debug, Default, Clone)]
+pub struct FollowerGraph {
+    /// Every follower found, by id
+    pub users: HashMap<Id, User>,
+    /// `(follower, followed)` pairs
+    pub edges: Vec<(Id, Id)>,
+    /// The followers whose own followers could not be fetched because the
+    /// authentication may not see them, e.g. protected users
+    pub skipped: Vec<Id>,
+}
+
+/// Walks the followers of a user breadth-first, then their followers and so on until
+/// `depth` levels have been visited. When the rate limit of the followers endpoint is hit
+/// the walker waits for it to reset, so large walks can take a long time.
+pub struct FollowerGraphWalker {
+    depth: u32,
+    max_followers: Option<u64>,
+    base_url: String,
+}
+
+impl FollowerGraphWalker {
+    /// Creates a walker that fetches `depth` levels of followers, 1 being only the
+    /// followers of the user the walk starts at
+    pub fn new(depth: u32) -> FollowerGraphWalker {
+        FollowerGraphWalker {
+            depth,
+            max_followers: None,
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Stops paginating the followers of a user after `max` followers, which keeps
+    /// accounts with millions of followers from using up the whole rate limit
+    pub fn max_followers_per_user(mut self, max: u64) -> Self {
+        self.max_followers = Some(max);
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Walks the follower graph starting at `root`. Followers whose followers the
+    /// authentication may not see are listed in `FollowerGraph::skipped` instead of
+    /// failing the walk, but the walk fails if that is the case for `root`.
+    pub async fn walk(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        root: Id,
+    ) -> Result<FollowerGraph, TwitterError> {
+        let mut graph = FollowerGraph::default();
+        let mut visited = HashSet::from([root]);
+        let mut queue = VecDeque::from([(root, 0)]);
+        while let Some((user_id, level)) = queue.pop_front() {
+            if level >= self.depth {
+                continue;
+            }
+            let followers = match self.followers(client, auth, user_id).await {
+                Ok(followers) => followers,
+                Err(e) if user_id != root && is_not_authorized(&e) => {
+                    graph.skipped.push(user_id);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            for follower in followers {
+                graph.edges.push((follower.id, user_id));
+                if visited.insert(follower.id) {
+                    queue.push_back((follower.id, level + 1));
+                }
+                graph.users.insert(follower.id, follower);
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Fetches all followers of the user, or the first `max_followers` of them
+    async fn followers(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        user_id: Id,
+    ) -> Result<Vec<User>, TwitterError> {
+        let mut followers = Vec::new();
+        let page_size = self.max_followers.map_or(1000, |max| max.clamp(1, 1000)) as u16;
+        let mut request = Some(
+            TwitterRequest::builder()
+                .set_endpoint(Endpoint::UserFollowers(user_id))
+                .set_method(Method::GET)
+                .set_authentication(auth.clone())
+                .add_option(MainOptions::MaxResults(page_size.into()))
+                .set_base_url(&self.base_url)
+                .build()?,
+        );
+        while let Some(page) = request {
+            let response = page.clone().send_typed_waiting::<Vec<User>>(client).await?;
+            if response.data.is_none() && !response.errors.is_empty() {
+                return Err(TwitterError::ResponseErrors(response.errors));
+            }
+            followers.extend(response.data.unwrap_or_default());
+            if let Some(max) = self.max_followers {
+                if followers.len() as u64 >= max {
+                    followers.truncate(max as usize);
+                    break;
+                }
+            }
+            request = match response.meta {
+                Some(meta) => page.next_page(&meta)?,
+                None => None,
+            };
+        }
+        Ok(followers)
+    }
+}
+
+/// True if the error means the authentication may not see the user's followers
+fn is_not_authorized(error: &TwitterError) -> bool {
+    match error {
+        TwitterError::ApiError(status, _) => {
+            *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN
+        }
+        TwitterError::ResponseErrors(errors) => errors.iter().any(|e| e.is_not_authorized()),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    fn users(ids: &[u64]) -> Vec<serde_json::Value> {
+        ids.iter()
+            .map(|id| json!({ "id": id.to_string(), "name": "User", "username": format!("user{}", id) }))
+            .collect()
+    }
+
+    fn followers(user_id: u64) -> Mock {
+        Mock::endpoint(&Endpoint::UserFollowers(user_id.into()), Method::GET)
+            .expect_param("max_results", "2")
+    }
+
+    #[tokio::test]
+    async fn walk_test() {
+        let server = MockServer::start().await;
+        // 1 <- 2, 3 over two pages, 2 <- 4, 5, 6, 3 is protected, 4 <- 7 is too deep
+        server
+            .mock(
+                followers(1)
+                    .expect_no_param("pagination_token")
+                    .respond_with(MockResponse::json(
+                        200,
+                        &json!({ "data": users(&[2]), "meta": { "result_count": 1, "next_token": "p2" } }),
+                    ))
+                    .times(1),
+            )
+            .mock(
+                followers(1)
+                    .expect_param("pagination_token", "p2")
+                    .respond_with(MockResponse::json(
+                        200,
+                        &json!({ "data": users(&[3]), "meta": { "result_count": 1 } }),
+                    ))
+                    .times(1),
+            )
+            .mock(
+                followers(2)
+                    .respond_with(MockResponse::json(
+                        200,
+                        &json!({ "data": users(&[4, 5, 6]), "meta": { "result_count": 3 } }),
+                    ))
+                    .times(1),
+            )
+            .mock(
+                followers(3)
+                    .respond_with(MockResponse::error(403, "Forbidden", "Protected"))
+                    .times(1),
+            );
+
+        let graph = FollowerGraphWalker::new(2)
+            .max_followers_per_user(2)
+            .base_url(server.url())
+            .walk(
+                &Client::new(),
+                &AuthenticationData::new_bearer_auth("token"),
+                1.into(),
+            )
+            .await
+            .unwrap();
+        server.verify();
+
+        assert_eq!(
+            graph.edges,
+            [(2, 1), (3, 1), (4, 2), (5, 2)].map(|(a, b)| (a.into(), b.into()))
+        );
+        assert_eq!(graph.users.len(), 4);
+        assert_eq!(graph.skipped, [Id::from(3)]);
+    }
+
+    #[tokio::test]
+    async fn protected_root_test() {
+        let server = MockServer::start().await;
+        server.mock(
+            Mock::any()
+                .respond_with(MockResponse::error(401, "Unauthorized", "Unauthorized"))
+                .times(1),
+        );
+
+        let walk = FollowerGraphWalker::new(1)
+            .base_url(server.url())
+            .walk(
+                &Client::new(),
+                &AuthenticationData::new_bearer_auth("token"),
+                1.into(),
+            )
+            .await;
+        server.verify();
+        assert!(matches!(walk, Err(TwitterError::ApiError(..))));
+    }
+}
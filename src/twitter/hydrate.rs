@@ -0,0 +1,416 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use flate2::read::MultiGzDecoder;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::errors::TwitterError;
+use crate::twitter::batch_lookup::BatchTweetLookup;
+use crate::twitter::endpoints::AuthenticationData;
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::query_params::main_options::MainOptions;
+use crate::twitter::twitter_objects::response::TweetsResponse;
+
+/// How the ids are laid out in an id file
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum IdFormat {
+    /// One id per line
+    Text,
+    /// Comma separated values with the ids in one of the columns
+    Csv(CsvColumn),
+}
+
+/// The column of a CSV file the ids are in
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum CsvColumn {
+    /// The zero based index of the column, for files without a header row
+    Index(usize),
+    /// The name of the column in the header row
+    Name(String),
+}
+
+/// Reads the ids in the file at `path`. Files that are gzip compressed are decompressed
+/// whatever their name is. Blank lines are skipped, any other line that is not an id
+/// makes the read fail with a `BadIdError`.
+pub fn read_ids(path: impl AsRef<Path>, format: &IdFormat) -> Result<Vec<Id>, TwitterError> {
+    let mut file = BufReader::new(File::open(path).map_err(TwitterError::IoError)?);
+    let is_gzip = file
+        .fill_buf()
+        .map_err(TwitterError::IoError)?
+        .starts_with(&[0x1f, 0x8b]);
+    let reader: Box<dyn Read> = if is_gzip {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    match format {
+        IdFormat::Text => read_text_ids(BufReader::new(reader)),
+        IdFormat::Csv(column) => read_csv_ids(reader, column),
+    }
+}
+
+fn read_text_ids(reader: impl BufRead) -> Result<Vec<Id>, TwitterError> {
+    let mut ids = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(TwitterError::IoError)?;
+        let line = line.trim();
+        if !line.is_empty() {
+            ids.push(parse_id(line, index as u64 + 1)?);
+        }
+    }
+    Ok(ids)
+}
+
+fn read_csv_ids(reader: impl Read, column: &CsvColumn) -> Result<Vec<Id>, TwitterError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(matches!(column, CsvColumn::Name(_)))
+        .flexible(true)
+        .from_reader(reader);
+    let index = match column {
+        CsvColumn::Index(index) => *index,
+        CsvColumn::Name(name) => {
+            let headers = reader
+                .headers()
+                .map_err(|e| TwitterError::IoError(e.into()))?;
+            headers.iter().position(|h| h == name).ok_or_else(|| {
+                TwitterError::BadQueryError(format!("The CSV file has no \"{}\" column", name))
+            })?
+        }
+    };
+    let mut ids = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| TwitterError::IoError(e.into()))?;
+        let line = record.position().map_or(0, |p| p.line());
+        match record.get(index).map(str::trim) {
+            Some("") | None => {}
+            Some(value) => ids.push(parse_id(value, line)?),
+        }
+    }
+    Ok(ids)
+}
+
+fn parse_id(value: &str, line: u64) -> Result<Id, TwitterError> {
+    Id::try_from(value).map_err(|_| TwitterError::BadIdError(line, String::from(value)))
+}
+
+/// How far a hydration has come, written next to the output so it can be resumed
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+struct Checkpoint {
+    /// The number of ids from the start of the input that have been hydrated
+    ids_done: u64,
+    /// The length of the output once those ids were written, anything after it
+    /// was written by an interrupted run and is thrown away
+    output_bytes: u64,
+}
+
+/// What a hydration run did
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct HydrationSummary {
+    /// The ids hydrated by this run, not counting those done before it was resumed
+    pub ids: u64,
+    pub tweets: u64,
+    pub not_found: u64,
+    pub not_authorized: u64,
+}
+
+/// Hydrates a list of tweet ids into a JSONL file. The ids are looked up in steps of
+/// `ids_per_checkpoint` ids, and each step is written as one line holding a tweets lookup
+/// response, with the `data`, `includes` and `errors` of the step. After every step a
+/// checkpoint file is written, so when the hydration is run again with the same ids it
+/// continues after the last finished step instead of starting over.
+pub struct Hydrator {
+    output: PathBuf,
+    checkpoint: PathBuf,
+    ids_per_checkpoint: usize,
+    lookup: BatchTweetLookup,
+}
+
+impl Hydrator {
+    /// Creates a hydration writing to `output`, checkpointed to `output` with
+    /// `.checkpoint` appended
+    pub fn new(output: impl Into<PathBuf>) -> Hydrator {
+        let output = output.into();
+        let mut checkpoint = output.clone().into_os_string();
+        checkpoint.push(".checkpoint");
+        Hydrator {
+            output,
+            checkpoint: checkpoint.into(),
+            ids_per_checkpoint: 1000,
+            lookup: BatchTweetLookup::new(),
+        }
+    }
+
+    /// Sets the file the checkpoint is written to
+    pub fn checkpoint(mut self, checkpoint: impl Into<PathBuf>) -> Self {
+        self.checkpoint = checkpoint.into();
+        self
+    }
+
+    /// Sets how many ids are looked up between checkpoints, at least 1
+    pub fn ids_per_checkpoint(mut self, ids_per_checkpoint: usize) -> Self {
+        self.ids_per_checkpoint = ids_per_checkpoint.max(1);
+        self
+    }
+
+    /// Adds a field or expansion option to the lookups
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        self.lookup = self.lookup.add_option(option);
+        self
+    }
+
+    /// Sets how many lookup requests are sent at a time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.lookup = self.lookup.concurrency(concurrency);
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.lookup = self.lookup.base_url(base_url);
+        self
+    }
+
+    /// Reads the ids of the id file at `input` and hydrates them
+    pub async fn hydrate_file(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        input: impl AsRef<Path>,
+        format: &IdFormat,
+    ) -> Result<HydrationSummary, TwitterError> {
+        let ids = read_ids(input, format)?;
+        self.hydrate(client, auth, &ids).await
+    }
+
+    /// Hydrates the ids, continuing from the checkpoint if there is one
+    pub async fn hydrate(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        ids: &[Id],
+    ) -> Result<HydrationSummary, TwitterError> {
+        let mut checkpoint = match tokio::fs::read(&self.checkpoint).await {
+            Ok(checkpoint) => {
+                serde_json::from_slice(&checkpoint).map_err(TwitterError::JsonError)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Checkpoint::default(),
+            Err(e) => return Err(TwitterError::IoError(e)),
+        };
+        if checkpoint.ids_done > ids.len() as u64 {
+            return Err(TwitterError::BadQueryError(format!(
+                "The checkpoint is at id {} but there are only {} ids, was it made for other ids?",
+                checkpoint.ids_done,
+                ids.len()
+            )));
+        }
+        let mut output = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&self.output)
+            .await
+            .map_err(TwitterError::IoError)?;
+        output
+            .set_len(checkpoint.output_bytes)
+            .await
+            .map_err(TwitterError::IoError)?;
+        output
+            .seek(SeekFrom::End(0))
+            .await
+            .map_err(TwitterError::IoError)?;
+
+        let mut summary = HydrationSummary::default();
+        for step in ids[checkpoint.ids_done as usize..].chunks(self.ids_per_checkpoint) {
+            let result = self
+                .lookup
+                .lookup(client, auth, step.iter().copied())
+                .await?;
+            summary.ids += step.len() as u64;
+            summary.tweets += result.tweets.len() as u64;
+            summary.not_found += result.not_found.len() as u64;
+            summary.not_authorized += result.not_authorized.len() as u64;
+
+            let response = TweetsResponse {
+                data: Some(result.tweets),
+                includes: Some(result.includes),
+                errors: result.errors,
+                meta: None,
+            };
+            let mut line = serde_json::to_vec(&response).map_err(TwitterError::JsonError)?;
+            line.push(b'\n');
+            output
+                .write_all(&line)
+                .await
+                .map_err(TwitterError::IoError)?;
+            output.sync_data().await.map_err(TwitterError::IoError)?;
+
+            checkpoint.ids_done += step.len() as u64;
+            checkpoint.output_bytes += line.len() as u64;
+            self.write_checkpoint(&checkpoint).await?;
+        }
+        Ok(summary)
+    }
+
+    /// Replaces the checkpoint file, through a temporary file so an interrupted write
+    /// can not leave a broken checkpoint behind
+    async fn write_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), TwitterError> {
+        let mut temporary = self.checkpoint.clone().into_os_string();
+        temporary.push(".tmp");
+        let checkpoint = serde_json::to_vec(checkpoint).map_err(TwitterError::JsonError)?;
+        tokio::fs::write(&temporary, checkpoint)
+            .await
+            .map_err(TwitterError::IoError)?;
+        tokio::fs::rename(&temporary, &self.checkpoint)
+            .await
+            .map_err(TwitterError::IoError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use http::Method;
+    use serde_json::json;
+
+    use super::*;
+    use crate::twitter::endpoints::Endpoint;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    /// Creates a directory for the files of a test, unique to the test and the run
+    fn temp_dir(test: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "twitter_request_{}_{}_{}",
+            test,
+            std::process::id(),
+            nanos
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn read_ids_test() {
+        let dir = temp_dir("read_ids_test");
+        let text = dir.join("ids.txt");
+        std::fs::write(&text, "1\n 2 \n\n3\n").unwrap();
+        assert_eq!(
+            read_ids(&text, &IdFormat::Text).unwrap(),
+            vec![Id::from(1), Id::from(2), Id::from(3)]
+        );
+
+        let csv = dir.join("ids.csv");
+        std::fs::write(&csv, "text,tweet_id\n\"hi, there\",4\nbye,5\n").unwrap();
+        let column = IdFormat::Csv(CsvColumn::Name(String::from("tweet_id")));
+        assert_eq!(
+            read_ids(&csv, &column).unwrap(),
+            vec![Id::from(4), Id::from(5)]
+        );
+        let missing = IdFormat::Csv(CsvColumn::Name(String::from("id")));
+        assert!(matches!(
+            read_ids(&csv, &missing),
+            Err(TwitterError::BadQueryError(_))
+        ));
+        match read_ids(&csv, &IdFormat::Csv(CsvColumn::Index(1))) {
+            Err(TwitterError::BadIdError(line, value)) => {
+                assert_eq!((line, value.as_str()), (1, "tweet_id"))
+            }
+            _ => panic!("The header row is not an id"),
+        }
+
+        let gzip = dir.join("ids.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"6\n7\n").unwrap();
+        std::fs::write(&gzip, encoder.finish().unwrap()).unwrap();
+        assert_eq!(
+            read_ids(&gzip, &IdFormat::Text).unwrap(),
+            vec![Id::from(6), Id::from(7)]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resume_hydration_test() {
+        let server = MockServer::start().await;
+        // Ids 1 to 100 were hydrated before, so only 101 to 250 are looked up
+        server.mock(
+            Mock::endpoint(&Endpoint::LookupTweets, Method::GET)
+                .respond_with_fn(|request| {
+                    let data: Vec<serde_json::Value> = request
+                        .query_param("ids")
+                        .unwrap()
+                        .split(',')
+                        .map(|id| json!({ "id": id, "text": "hello" }))
+                        .collect();
+                    MockResponse::json(200, &json!({ "data": data }))
+                })
+                .times(2),
+        );
+        let dir = temp_dir("resume_hydration_test");
+        let output = dir.join("resume.jsonl");
+        let checkpoint = dir.join("resume.jsonl.checkpoint");
+        let first_line = "{\"data\":[]}\n";
+        // The second line was cut off when the earlier run was interrupted
+        std::fs::write(&output, format!("{}{{\"data\":[{{\"id", first_line)).unwrap();
+        let done = Checkpoint {
+            ids_done: 100,
+            output_bytes: first_line.len() as u64,
+        };
+        std::fs::write(&checkpoint, serde_json::to_vec(&done).unwrap()).unwrap();
+
+        let ids: Vec<Id> = (1..=250u64).map(Id::from).collect();
+        let summary = Hydrator::new(&output)
+            .ids_per_checkpoint(100)
+            .base_url(server.url())
+            .hydrate(
+                &Client::new(),
+                &AuthenticationData::new_bearer_auth("t"),
+                &ids,
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.ids, 150);
+        assert_eq!(summary.tweets, 150);
+        server.verify();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let last: TweetsResponse = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(last.data.unwrap()[0].id, Id::from(201));
+        let done: Checkpoint =
+            serde_json::from_slice(&std::fs::read(&checkpoint).unwrap()).unwrap();
+        assert_eq!(done.ids_done, 250);
+        assert_eq!(done.output_bytes, written.len() as u64);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use http::Method;
+use reqwest::multipart::{Form, Part};
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::io::AsyncReadExt;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, AuthenticationType};
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::request::check_response;
+
+pub const UPLOAD_URL: &str = "https://upload.twitter.com/1.1";
+/// The largest chunk twitter accepts in a single APPEND
+pub const MAX_CHUNK_SIZE: usize = 5 * 1024 * 1024;
+/// The longest alt text twitter accepts
+pub const MAX_ALT_TEXT_LENGTH: usize = 1000;
+/// How many times `STATUS` is checked by default before giving up on the processing
+pub const MAX_STATUS_CHECKS: u32 = 100;
+
+/// What the media will be used for, which decides the size limits twitter applies
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MediaCategory {
+    TweetImage,
+    TweetGif,
+    TweetVideo,
+    AmplifyVideo,
+}
+
+impl Display for MediaCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            MediaCategory::TweetImage => "tweet_image",
+            MediaCategory::TweetGif => "tweet_gif",
+            MediaCategory::TweetVideo => "tweet_video",
+            MediaCategory::AmplifyVideo => "amplify_video",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Uploads a file with the chunked v1.1 media upload flow. The file is streamed to
+/// twitter in chunks with INIT/APPEND/FINALIZE, and if twitter processes the media
+/// asynchronously (e.g. videos) `STATUS` is polled until it is done. The returned
+/// media id can be attached to a tweet with `NewTweetBuilder::add_media_id`.
+///
+/// Media upload only supports OAuth 1.0a user context authentication.
+pub struct MediaUpload {
+    path: PathBuf,
+    media_type: String,
+    media_category: Option<MediaCategory>,
+    alt_text: Option<String>,
+    chunk_size: usize,
+    max_status_checks: u32,
+    upload_url: String,
+}
+
+impl MediaUpload {
+    /// Creates an upload of the file at `path`, where `media_type` is its MIME type,
+    /// e.g. "image/png" or "video/mp4"
+    pub fn new(path: impl Into<PathBuf>, media_type: &str) -> MediaUpload {
+        MediaUpload {
+            path: path.into(),
+            media_type: String::from(media_type),
+            media_category: None,
+            alt_text: None,
+            chunk_size: MAX_CHUNK_SIZE,
+            max_status_checks: MAX_STATUS_CHECKS,
+            upload_url: String::from(UPLOAD_URL),
+        }
+    }
+
+    pub fn media_category(mut self, media_category: MediaCategory) -> Self {
+        self.media_category = Some(media_category);
+        self
+    }
+
+    /// Sets the alt text of the media once it has been uploaded
+    pub fn alt_text(mut self, alt_text: &str) -> Self {
+        self.alt_text = Some(String::from(alt_text));
+        self
+    }
+
+    /// Sets how many bytes are sent in each APPEND, clamped to between 1 and
+    /// `MAX_CHUNK_SIZE`
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_CHUNK_SIZE);
+        self
+    }
+
+    /// Sets how many times `STATUS` is checked while twitter processes the media
+    /// before the upload fails, instead of `MAX_STATUS_CHECKS`
+    pub fn max_status_checks(mut self, max_status_checks: u32) -> Self {
+        self.max_status_checks = max_status_checks;
+        self
+    }
+
+    /// Sets the base url to upload to instead of `UPLOAD_URL`
+    pub fn upload_url(mut self, upload_url: &str) -> Self {
+        self.upload_url = String::from(upload_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Uploads the file and returns its media id
+    pub async fn upload(
+        self,
+        client: &Client,
+        auth: &AuthenticationData,
+    ) -> Result<Id, TwitterError> {
+        if auth.get_type() != AuthenticationType::OauthSignature {
+            return Err(TwitterError::BadAuthError(auth.get_type()));
+        }
+        if let Some(alt_text) = &self.alt_text {
+            if alt_text.chars().count() > MAX_ALT_TEXT_LENGTH {
+                return Err(TwitterError::BadQueryError(format!(
+                    "alt text can at most be {} characters",
+                    MAX_ALT_TEXT_LENGTH
+                )));
+            }
+        }
+        let mut file = tokio::fs::File::open(&self.path)
+            .await
+            .map_err(TwitterError::IoError)?;
+        let total_bytes = file.metadata().await.map_err(TwitterError::IoError)?.len();
+
+        let mut params = vec![
+            (String::from("command"), String::from("INIT")),
+            (String::from("total_bytes"), total_bytes.to_string()),
+            (String::from("media_type"), self.media_type.clone()),
+        ];
+        if let Some(category) = self.media_category {
+            params.push((String::from("media_category"), category.to_string()));
+        }
+        let init: UploadResponse = self.send(client, auth, Method::POST, &params).await?;
+        let media_id = init.media_id_string;
+
+        let mut buffer = vec![0; self.chunk_size];
+        let mut segment_index = 0;
+        loop {
+            let read = read_chunk(&mut file, &mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            self.append(client, auth, &media_id, segment_index, &buffer[..read])
+                .await?;
+            segment_index += 1;
+        }
+
+        let params = vec![
+            (String::from("command"), String::from("FINALIZE")),
+            (String::from("media_id"), media_id.to_string()),
+        ];
+        let mut response: UploadResponse = self.send(client, auth, Method::POST, &params).await?;
+        let mut status_checks = 0;
+        while let Some(info) = response.processing_info {
+            match info.state {
+                ProcessingState::Succeeded => break,
+                ProcessingState::Failed => {
+                    return Err(TwitterError::MediaProcessingError(
+                        info.error
+                            .map(|e| e.message)
+                            .unwrap_or_else(|| String::from("no reason given")),
+                    ))
+                }
+                ProcessingState::Pending | ProcessingState::InProgress => {
+                    if status_checks == self.max_status_checks {
+                        return Err(TwitterError::MediaProcessingError(format!(
+                            "still processing after {} status checks",
+                            status_checks
+                        )));
+                    }
+                    status_checks += 1;
+                    tokio::time::sleep(Duration::from_secs(info.check_after_secs.unwrap_or(1)))
+                        .await;
+                    let params = vec![
+                        (String::from("command"), String::from("STATUS")),
+                        (String::from("media_id"), media_id.to_string()),
+                    ];
+                    response = self.send(client, auth, Method::GET, &params).await?;
+                }
+            }
+        }
+
+        if let Some(alt_text) = &self.alt_text {
+            let url = format!("{}/media/metadata/create.json", self.upload_url);
+            let req = auth.authorize(client.post(&url), &Method::POST, &url, &[]);
+            let body = serde_json::json!({
+                "media_id": media_id.to_string(),
+                "alt_text": { "text": alt_text },
+            });
+            let response = req
+                .json(&body)
+                .send()
+                .await
+                .map_err(TwitterError::RequestError)?;
+            check_response(response).await?;
+        }
+        Ok(media_id)
+    }
+
+    /// Sends a command to the upload endpoint with its parameters in the query string,
+    /// where they are covered by the OAuth signature
+    async fn send(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        method: Method,
+        params: &[(String, String)],
+    ) -> Result<UploadResponse, TwitterError> {
+        let url = self.upload_json_url();
+        let req = client.request(method.clone(), &url).query(params);
+        let response = auth
+            .authorize(req, &method, &url, params)
+            .send()
+            .await
+            .map_err(TwitterError::RequestError)?;
+        check_response(response)
+            .await?
+            .json()
+            .await
+            .map_err(TwitterError::RequestError)
+    }
+
+    /// Sends a chunk of the file. The chunk goes in a multipart body, which is not
+    /// part of the OAuth signature, so only the query parameters are signed.
+    async fn append(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        media_id: &Id,
+        segment_index: u32,
+        chunk: &[u8],
+    ) -> Result<(), TwitterError> {
+        let url = self.upload_json_url();
+        let params = vec![
+            (String::from("command"), String::from("APPEND")),
+            (String::from("media_id"), media_id.to_string()),
+            (String::from("segment_index"), segment_index.to_string()),
+        ];
+        let form = Form::new().part("media", Part::bytes(chunk.to_vec()));
+        let req = client.post(&url).query(&params);
+        let response = auth
+            .authorize(req, &Method::POST, &url, &params)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(TwitterError::RequestError)?;
+        check_response(response).await?;
+        Ok(())
+    }
+
+    fn upload_json_url(&self) -> String {
+        format!("{}/media/upload.json", self.upload_url)
+    }
+}
+
+/// Fills the buffer as far as the file allows, returning how many bytes were read
+async fn read_chunk(file: &mut tokio::fs::File, buffer: &mut [u8]) -> Result<usize, TwitterError> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file
+            .read(&mut buffer[filled..])
+            .await
+            .map_err(TwitterError::IoError)?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+#[derive(Deserialize, Debug)]
+struct UploadResponse {
+    media_id_string: Id,
+    processing_info: Option<ProcessingInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProcessingInfo {
+    state: ProcessingState,
+    check_after_secs: Option<u64>,
+    error: Option<ProcessingError>,
+}
+
+#[derive(Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+enum ProcessingState {
+    Pending,
+    InProgress,
+    Failed,
+    Succeeded,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProcessingError {
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn chunked_upload_test() {
+        let path = std::env::temp_dir().join("twitter_request_media_upload_test.bin");
+        std::fs::write(&path, [7u8; 10]).unwrap();
+        // INIT, three APPENDs, FINALIZE, STATUS and the alt text
+        let server = MockServer::start().await;
+        let url = format!("{}/1.1", server.url());
+        server.mock(Mock::any().times(7).respond_with_fn(|request| {
+            let body = if request.path.contains("command=INIT") {
+                r#"{"media_id": 710511363345354753, "media_id_string": "710511363345354753"}"#
+            } else if request.path.contains("command=FINALIZE") {
+                r#"{"media_id_string": "710511363345354753", "processing_info": {"state": "pending", "check_after_secs": 0}}"#
+            } else if request.path.contains("command=STATUS") {
+                r#"{"media_id_string": "710511363345354753", "processing_info": {"state": "succeeded"}}"#
+            } else {
+                ""
+            };
+            MockResponse {
+                status: if body.is_empty() { 204 } else { 200 },
+                headers: Vec::new(),
+                body: String::from(body),
+            }
+        }));
+        let auth = AuthenticationData::new_oauth_auth("ck", "cs", "at", "ats");
+
+        let media_id = MediaUpload::new(&path, "video/mp4")
+            .media_category(MediaCategory::TweetVideo)
+            .alt_text("A test video")
+            .chunk_size(4)
+            .upload_url(&url)
+            .upload(&Client::new(), &auth)
+            .await
+            .unwrap();
+        assert_eq!(media_id, Id::from(710511363345354753));
+
+        server.verify();
+        let received = server.received();
+        std::fs::remove_file(&path).unwrap();
+        assert!(received.iter().all(|request| request
+            .header("authorization")
+            .is_some_and(|a| a.starts_with("OAuth "))));
+        let commands: Vec<&str> = received
+            .iter()
+            .map(|request| {
+                ["INIT", "APPEND", "FINALIZE", "STATUS"]
+                    .into_iter()
+                    .find(|c| request.path.contains(&format!("command={}", c)))
+                    .unwrap_or(request.path.as_str())
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "INIT",
+                "APPEND",
+                "APPEND",
+                "APPEND",
+                "FINALIZE",
+                "STATUS",
+                "/1.1/media/metadata/create.json"
+            ]
+        );
+        assert_eq!(received[5].method, "GET");
+        assert!(received[..5].iter().all(|request| request.method == "POST"));
+        assert!(received[0].path.contains("total_bytes=10"));
+        assert!(received[0].path.contains("media_category=tweet_video"));
+        assert!(received[3].path.contains("segment_index=2"));
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&received[6].body).unwrap(),
+            serde_json::json!({
+                "media_id": "710511363345354753",
+                "alt_text": { "text": "A test video" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn status_checks_test() {
+        let path = std::env::temp_dir().join(format!(
+            "twitter_request_status_checks_test_{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, [7u8; 4]).unwrap();
+        // INIT, an APPEND per byte, FINALIZE and two STATUS checks that never finish
+        let server = MockServer::start().await;
+        server.mock(Mock::any().times(8).respond_with_fn(|request| {
+            let body = if request.path.contains("command=INIT") {
+                r#"{"media_id_string": "710511363345354753"}"#
+            } else if request.path.contains("command=APPEND") {
+                ""
+            } else {
+                r#"{"media_id_string": "710511363345354753", "processing_info": {"state": "in_progress", "check_after_secs": 0}}"#
+            };
+            MockResponse {
+                status: if body.is_empty() { 204 } else { 200 },
+                headers: Vec::new(),
+                body: String::from(body),
+            }
+        }));
+        let auth = AuthenticationData::new_oauth_auth("ck", "cs", "at", "ats");
+
+        let result = MediaUpload::new(&path, "video/mp4")
+            .chunk_size(0)
+            .max_status_checks(2)
+            .upload_url(&format!("{}/1.1", server.url()))
+            .upload(&Client::new(), &auth)
+            .await;
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(TwitterError::MediaProcessingError(_))));
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn upload_needs_oauth1_test() {
+        let auth = AuthenticationData::new_bearer_auth("token");
+        let result = MediaUpload::new("missing.png", "image/png")
+            .upload(&Client::new(), &auth)
+            .await;
+        assert!(matches!(result, Err(TwitterError::BadAuthError(_))));
+    }
+}
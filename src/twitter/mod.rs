@@ -17,10 +17,28 @@
   along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
+pub mod access_level;
+pub mod actions;
+pub mod backfill;
+pub mod batch_lookup;
+pub mod cache;
+pub mod cassette;
+pub mod compliance;
+pub mod config;
 pub mod endpoints;
+pub mod follower_graph;
+pub mod hydrate;
+pub mod media_upload;
+pub mod new_tweet;
 pub mod query_filters;
 pub mod query_params;
+pub mod rate_limit;
 pub mod request;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod tlds;
+pub mod tweet_length;
 pub mod twitter_objects;
+pub mod watcher;
 
 pub use endpoints::Endpoint;
@@ -0,0 +1,367 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::RangeInclusive;
+
+use serde::Serialize;
+
+use crate::errors::TwitterBuilderError;
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::tweet_length::{self, MAX_WEIGHTED_LENGTH};
+
+/// How many options a poll can have
+pub const POLL_OPTIONS: RangeInclusive<usize> = 2..=4;
+/// How long a poll option can be
+pub const MAX_POLL_OPTION_LENGTH: usize = 25;
+/// How many minutes a poll can be open for
+pub const POLL_DURATION_MINUTES: RangeInclusive<u32> = 5..=10080;
+/// How many media items can be attached to a tweet
+pub const MAX_MEDIA: usize = 4;
+
+/// The JSON body of a request to create a tweet through `Endpoint::LookupTweets` with POST.
+/// Constructed through `NewTweet::builder()`, which checks the tweet is valid before
+/// it is sent.
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct NewTweet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply: Option<Reply>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quote_tweet_id: Option<Id>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll: Option<Poll>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media: Option<Media>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_settings: Option<ReplySettings>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    for_super_followers_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direct_message_deep_link: Option<String>,
+}
+
+impl NewTweet {
+    /// Returns an empty builder
+    pub fn builder() -> NewTweetBuilder {
+        NewTweetBuilder {
+            text: None,
+            in_reply_to_tweet_id: None,
+            exclude_reply_user_ids: Vec::new(),
+            quote_tweet_id: None,
+            poll: None,
+            media_ids: Vec::new(),
+            tagged_user_ids: Vec::new(),
+            reply_settings: None,
+            for_super_followers_only: false,
+            direct_message_deep_link: None,
+        }
+    }
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+struct Reply {
+    in_reply_to_tweet_id: Id,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    exclude_reply_user_ids: Vec<Id>,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+struct Poll {
+    options: Vec<String>,
+    duration_minutes: u32,
+}
+
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+struct Media {
+    media_ids: Vec<Id>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tagged_user_ids: Vec<Id>,
+}
+
+/// Who can reply to a tweet, everyone can if it is not set
+#[derive(Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ReplySettings {
+    #[serde(rename = "following")]
+    Following,
+    #[serde(rename = "mentionedUsers")]
+    MentionedUsers,
+}
+
+pub struct NewTweetBuilder {
+    text: Option<String>,
+    in_reply_to_tweet_id: Option<Id>,
+    exclude_reply_user_ids: Vec<Id>,
+    quote_tweet_id: Option<Id>,
+    poll: Option<Poll>,
+    media_ids: Vec<Id>,
+    tagged_user_ids: Vec<Id>,
+    reply_settings: Option<ReplySettings>,
+    for_super_followers_only: bool,
+    direct_message_deep_link: Option<String>,
+}
+
+impl NewTweetBuilder {
+    /// Sets the text of the tweet
+    pub fn set_text(mut self, text: &str) -> Self {
+        self.text = Some(String::from(text));
+        self
+    }
+
+    /// Makes the tweet a reply to the tweet with the passed id
+    pub fn set_in_reply_to(mut self, tweet_id: impl Into<Id>) -> Self {
+        self.in_reply_to_tweet_id = Some(tweet_id.into());
+        self
+    }
+
+    /// Leaves out a user mentioned in the replied to thread from the reply's mentions
+    pub fn add_exclude_reply_user_id(mut self, user_id: impl Into<Id>) -> Self {
+        self.exclude_reply_user_ids.push(user_id.into());
+        self
+    }
+
+    /// Makes the tweet quote the tweet with the passed id
+    pub fn set_quote_tweet_id(mut self, tweet_id: impl Into<Id>) -> Self {
+        self.quote_tweet_id = Some(tweet_id.into());
+        self
+    }
+
+    /// Attaches a poll with the passed options, open for `duration_minutes`
+    pub fn set_poll<I, S>(mut self, options: I, duration_minutes: u32) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.poll = Some(Poll {
+            options: options.into_iter().map(|o| o.into()).collect(),
+            duration_minutes,
+        });
+        self
+    }
+
+    /// Attaches uploaded media to the tweet
+    pub fn add_media_id(mut self, media_id: impl Into<Id>) -> Self {
+        self.media_ids.push(media_id.into());
+        self
+    }
+
+    /// Tags a user in the attached media
+    pub fn add_tagged_user_id(mut self, user_id: impl Into<Id>) -> Self {
+        self.tagged_user_ids.push(user_id.into());
+        self
+    }
+
+    /// Limits who can reply to the tweet
+    pub fn set_reply_settings(mut self, reply_settings: ReplySettings) -> Self {
+        self.reply_settings = Some(reply_settings);
+        self
+    }
+
+    /// Makes the tweet only visible to the author's super followers
+    pub fn set_for_super_followers_only(mut self, for_super_followers_only: bool) -> Self {
+        self.for_super_followers_only = for_super_followers_only;
+        self
+    }
+
+    /// Adds a deep link into a direct message conversation with the author
+    pub fn set_direct_message_deep_link(mut self, link: &str) -> Self {
+        self.direct_message_deep_link = Some(String::from(link));
+        self
+    }
+
+    /// Builds the NewTweet, checking that the parts of the tweet can go together
+    /// and that the text fits in a tweet.
+    pub fn build(self) -> Result<NewTweet, TwitterBuilderError> {
+        let has_media = !self.media_ids.is_empty();
+        let exclusive = [
+            ("a poll", self.poll.is_some()),
+            ("media", has_media),
+            ("a quote tweet id", self.quote_tweet_id.is_some()),
+        ];
+        let set: Vec<&str> = exclusive
+            .iter()
+            .filter(|(_, is_set)| *is_set)
+            .map(|(name, _)| *name)
+            .collect();
+        if set.len() > 1 {
+            return Err(TwitterBuilderError::BadTweetError(format!(
+                "a tweet cannot have both {}",
+                set.join(" and ")
+            )));
+        }
+        if !self.tagged_user_ids.is_empty() && !has_media {
+            return Err(TwitterBuilderError::BadTweetError(String::from(
+                "users can only be tagged in media, but no media was attached",
+            )));
+        }
+        if !self.exclude_reply_user_ids.is_empty() && self.in_reply_to_tweet_id.is_none() {
+            return Err(TwitterBuilderError::BadTweetError(String::from(
+                "reply user ids can only be excluded from replies",
+            )));
+        }
+        if self.media_ids.len() > MAX_MEDIA {
+            return Err(TwitterBuilderError::BadTweetError(format!(
+                "at most {} media items can be attached to a tweet",
+                MAX_MEDIA
+            )));
+        }
+        if let Some(poll) = &self.poll {
+            if !POLL_OPTIONS.contains(&poll.options.len()) {
+                return Err(TwitterBuilderError::BadTweetError(format!(
+                    "a poll must have between {} and {} options",
+                    POLL_OPTIONS.start(),
+                    POLL_OPTIONS.end()
+                )));
+            }
+            if let Some(option) = poll
+                .options
+                .iter()
+                .find(|o| o.is_empty() || o.chars().count() > MAX_POLL_OPTION_LENGTH)
+            {
+                return Err(TwitterBuilderError::BadTweetError(format!(
+                    "poll option \"{}\" must be between 1 and {} characters",
+                    option, MAX_POLL_OPTION_LENGTH
+                )));
+            }
+            if !POLL_DURATION_MINUTES.contains(&poll.duration_minutes) {
+                return Err(TwitterBuilderError::BadTweetError(format!(
+                    "a poll must be open for between {} and {} minutes",
+                    POLL_DURATION_MINUTES.start(),
+                    POLL_DURATION_MINUTES.end()
+                )));
+            }
+        }
+        match &self.text {
+            Some(text) if !text.trim().is_empty() => {
+                let length = tweet_length::weighted_length(text);
+                if length > MAX_WEIGHTED_LENGTH {
+                    return Err(TwitterBuilderError::BadTweetError(format!(
+                        "the text is {} characters long, but a tweet can at most be {}",
+                        length, MAX_WEIGHTED_LENGTH
+                    )));
+                }
+            }
+            _ if !has_media => {
+                return Err(TwitterBuilderError::BadTweetError(String::from(
+                    "a tweet without media must have text",
+                )))
+            }
+            _ => {}
+        }
+        Ok(NewTweet {
+            text: self.text,
+            reply: self.in_reply_to_tweet_id.map(|id| Reply {
+                in_reply_to_tweet_id: id,
+                exclude_reply_user_ids: self.exclude_reply_user_ids,
+            }),
+            quote_tweet_id: self.quote_tweet_id,
+            poll: self.poll,
+            media: if has_media {
+                Some(Media {
+                    media_ids: self.media_ids,
+                    tagged_user_ids: self.tagged_user_ids,
+                })
+            } else {
+                None
+            },
+            reply_settings: self.reply_settings,
+            for_super_followers_only: self.for_super_followers_only,
+            direct_message_deep_link: self.direct_message_deep_link,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_tweet_test() {
+        let tweet = NewTweet::builder()
+            .set_text("Hello world")
+            .set_in_reply_to(1)
+            .set_reply_settings(ReplySettings::MentionedUsers)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&tweet).unwrap(),
+            serde_json::json!({
+                "text": "Hello world",
+                "reply": { "in_reply_to_tweet_id": "1" },
+                "reply_settings": "mentionedUsers"
+            })
+        );
+
+        let tweet = NewTweet::builder()
+            .add_media_id(1234)
+            .add_tagged_user_id(5)
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&tweet).unwrap(),
+            serde_json::json!({ "media": { "media_ids": ["1234"], "tagged_user_ids": ["5"] } })
+        );
+    }
+
+    #[test]
+    fn exclusive_parts_test() {
+        assert!(NewTweet::builder()
+            .set_text("poll")
+            .set_poll(["yes", "no"], 60)
+            .add_media_id(1)
+            .build()
+            .is_err());
+        assert!(NewTweet::builder()
+            .set_text("quote")
+            .set_quote_tweet_id(1)
+            .set_poll(["yes", "no"], 60)
+            .build()
+            .is_err());
+        assert!(NewTweet::builder()
+            .set_text("link")
+            .set_quote_tweet_id(1)
+            .set_direct_message_deep_link("https://twitter.com/messages/compose?recipient_id=1")
+            .build()
+            .is_ok());
+        assert!(NewTweet::builder().build().is_err());
+        assert!(NewTweet::builder()
+            .set_text("poll")
+            .set_poll(["only one"], 60)
+            .build()
+            .is_err());
+        assert!(NewTweet::builder()
+            .set_text("poll")
+            .set_poll(["yes", "no"], 4)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn tweet_length_test() {
+        let max = "a".repeat(MAX_WEIGHTED_LENGTH);
+        assert!(NewTweet::builder().set_text(&max).build().is_ok());
+        let too_long = "a".repeat(MAX_WEIGHTED_LENGTH + 1);
+        assert!(NewTweet::builder().set_text(&too_long).build().is_err());
+        let cjk = "你".repeat(MAX_WEIGHTED_LENGTH / 2 + 1);
+        assert!(NewTweet::builder().set_text(&cjk).build().is_err());
+        let long_link = format!(
+            "see https://example.com/{}",
+            "a".repeat(MAX_WEIGHTED_LENGTH)
+        );
+        assert!(NewTweet::builder().set_text(&long_link).build().is_ok());
+    }
+}
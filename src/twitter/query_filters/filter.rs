@@ -20,7 +20,9 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 
-#[derive(PartialEq, Eq, Debug, Hash)]
+use crate::twitter::access_level::AccessLevel;
+
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
 pub enum Filter {
     /// This filter looks for the given keyword in the tweets.
     /// If exact the exact keyword is present the match must be exact
@@ -79,6 +81,20 @@ impl Filter {
                 | Filter::To(_, _)
         )
     }
+
+    /// Gets the lowest access tier that can use the operator. The geo operators and
+    /// `is:nullcast` are advanced operators that need Pro, Enterprise or Academic access.
+    pub fn access_level(&self) -> AccessLevel {
+        match self {
+            Filter::IsNullcast(_)
+            | Filter::HasGeo(_)
+            | Filter::LocPlaceCountry(_, _)
+            | Filter::LocPlace(_, _)
+            | Filter::LocBoundingBox(_, _)
+            | Filter::LocPointRadius(_, _) => AccessLevel::Pro,
+            _ => AccessLevel::Basic,
+        }
+    }
 }
 
 impl Display for Filter {
@@ -135,7 +151,7 @@ impl Display for Filter {
 }
 
 /// This enum is used to indicate if a filter is meant to be exact or not
-#[derive(PartialEq, Eq, Debug, Hash)]
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
 pub enum Exact {
     Is,
     Not,
@@ -158,7 +174,7 @@ impl From<Exact> for bool {
     }
 }
 /// This enum is used to indicate if a filter tests for existence or absence
-#[derive(PartialEq, Eq, Debug, Hash)]
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
 pub enum Is {
     Is,
     Not,
@@ -182,7 +198,7 @@ impl From<Is> for bool {
 }
 
 /// A struct for a bounding box location search, having two coordinate pairs
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct BoundingBox {
     x1: Real,
     y1: Real,
@@ -207,7 +223,7 @@ impl Display for BoundingBox {
 }
 /// A struct for a point+radius location search, having a coordinate pair for the
 /// circle center, and a radius expressed in km.
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct PointRadius {
     longitude: Real,
     latitude: Real,
@@ -235,7 +251,7 @@ impl Display for PointRadius {
 /// This struct is a simple wrapper for f32 that prevents NaN.
 /// Note that this struct will simply panic if passed a NaN, you as the library user need to
 /// filter f32's used to not be NaN's and handle those errors.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Real {
     r: f32,
 }
@@ -19,13 +19,14 @@
 
 use std::fmt::Write as _;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 
 use super::filter::Filter;
 
 type FilterItem = QueryItem<Filter>;
 type GroupItem = QueryItem<Group>;
 
-#[derive(Eq, PartialEq, Debug, Hash)]
+#[derive(Eq, PartialEq, Debug, Hash, Clone)]
 pub struct GroupList {
     groups: Vec<GroupItem>,
 }
@@ -46,7 +47,7 @@ impl GroupList {
         let filtered: Vec<GroupItem> = other
             .groups
             .into_iter()
-            .filter(|g| !self.groups.contains(&g) && &g.item.len() > &0)
+            .filter(|g| !self.groups.contains(g) && g.item.len() > 0)
             .collect();
         self.groups.extend(filtered)
     }
@@ -115,7 +116,12 @@ impl GroupList {
 
     /// Gets the number of groups in the GroupList
     pub fn len(&self) -> usize {
-        return self.groups.len();
+        self.groups.len()
+    }
+
+    /// Returns true if the GroupList has no groups
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
     }
 
     pub fn flatten(mut self) -> GroupList {
@@ -196,7 +202,7 @@ impl<'group_life> Iterator for GroupListFlatten<'group_life> {
         match &mut self.inner {
             // Check if self.inner has an iterator to iterate over
             None => match self.outer.next() {
-                None => return None,
+                None => None,
                 Some(i) => {
                     let group = &i.item;
                     self.inner = Some(group.into_iter());
@@ -219,7 +225,7 @@ impl<'group_life> Iterator for GroupListFlatten<'group_life> {
 
 /// A group is a group of filters in the twitter search query.
 /// Each filter is connected to another by an AND/OR connection.
-#[derive(Eq, PartialEq, Debug, Hash)]
+#[derive(Eq, PartialEq, Debug, Hash, Clone)]
 struct Group {
     list: Vec<FilterItem>,
 }
@@ -301,7 +307,7 @@ impl<'group_life> IntoIterator for &'group_life Group {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Hash)]
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
 enum AndOr {
     And,
     Or,
@@ -320,21 +326,22 @@ impl Display for AndOr {
     }
 }
 
-#[derive(Debug, Hash)]
+#[derive(Debug, Clone)]
 struct QueryItem<T> {
     and_or: AndOr,
     item: T,
 }
 
+impl<T: Hash> Hash for QueryItem<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Only the item takes part in equality, so only the item is hashed
+        self.item.hash(state)
+    }
+}
+
 impl<T: PartialEq> PartialEq for QueryItem<T> {
     fn eq(&self, other: &Self) -> bool {
         self.item.eq(&other.item)
     }
 }
 impl<T: Eq> Eq for QueryItem<T> {}
-
-impl<T> QueryItem<T> {
-    fn and_or(&self) -> &AndOr {
-        &self.and_or
-    }
-}
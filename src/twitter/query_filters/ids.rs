@@ -17,33 +17,39 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::fmt::Write as _;
 use std::num::ParseIntError;
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// This struct represents a list of tweet ids to use in
 /// a tweets GET request. Must not be empty to be used in a
 /// request.
+#[derive(Clone, Debug)]
 pub struct IDFilter {
     id_list: Vec<Id>,
+    /// The ids in `id_list`, so adding an id does not search the list
+    ids: HashSet<Id>,
 }
 
 impl IDFilter {
     pub fn new(id: impl Into<Id>) -> IDFilter {
-        IDFilter {
-            id_list: vec![id.into()],
-        }
+        IDFilter::from_iterator([id.into()])
     }
     /// Creates a new IDFilter from an Iterable
     pub fn from_iterator(ids: impl IntoIterator<Item = impl Into<Id>>) -> IDFilter {
+        let id_list = Vec::from_iter(ids.into_iter().map(|f| f.into()));
         IDFilter {
-            id_list: Vec::from_iter(ids.into_iter().map(|f| f.into())),
+            ids: id_list.iter().copied().collect(),
+            id_list,
         }
     }
     /// Adds an ID to the IDFilter
     pub fn add_id(&mut self, id: impl Into<Id>) {
         let id = id.into();
-        if !self.id_list.contains(&id) {
+        if self.ids.insert(id) {
             self.id_list.push(id)
         }
     }
@@ -54,9 +60,7 @@ impl IDFilter {
         let try_id = try_id.try_into();
         match try_id {
             Ok(id) => {
-                if !self.id_list.contains(&id) {
-                    self.id_list.push(id)
-                }
+                self.add_id(id);
                 Ok(())
             }
             Err(e) => Err(e),
@@ -64,16 +68,21 @@ impl IDFilter {
     }
     /// Adds all the IDS in the passed iterable into the IDFilter
     pub fn add_ids(&mut self, ids: impl IntoIterator<Item = impl Into<Id>>) {
-        let ids: Vec<Id> = ids
-            .into_iter()
-            .map(|f| f.into())
-            .filter(|f| !self.id_list.contains(&f))
-            .collect();
-        self.id_list.extend(ids)
+        for id in ids {
+            self.add_id(id);
+        }
     }
     pub fn is_empty(&self) -> bool {
         self.id_list.is_empty()
     }
+    /// The number of ids in the IDFilter
+    pub fn len(&self) -> usize {
+        self.id_list.len()
+    }
+    /// Iterates over the ids in the order they were added
+    pub fn iter(&self) -> std::slice::Iter<'_, Id> {
+        self.id_list.iter()
+    }
 }
 
 impl IntoIterator for IDFilter {
@@ -102,7 +111,7 @@ impl Display for IDFilter {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 pub struct Id {
     id: u64,
 }
@@ -124,6 +133,24 @@ impl TryFrom<&str> for Id {
         Ok(Id { id })
     }
 }
+impl From<Id> for u64 {
+    fn from(id: Id) -> Self {
+        id.id
+    }
+}
+/// Twitter sends ids as strings to avoid precision loss in javascript, so they
+/// are (de)serialized as strings as well.
+impl Serialize for Id {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Id::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -136,13 +163,12 @@ mod tests {
         let ids3: Vec<u64> = vec![1, 2, 3];
         let mut sut = IDFilter::from_iterator(ids);
         assert!(!sut.is_empty());
-        assert_eq!(String::from("ids=123,32323,235235"), format!("{}", &sut));
+        assert_eq!(String::from("123,32323,235235"), format!("{}", &sut));
         sut.add_ids(ids2);
-        assert_eq!(String::from("ids=123,32323,235235"), format!("{}", &sut));
+        assert_eq!(String::from("123,32323,235235"), format!("{}", &sut));
         sut.add_ids(ids3);
-        assert_eq!(
-            String::from("ids=123,32323,235235,1,2,3"),
-            format!("{}", &sut)
-        );
+        assert_eq!(String::from("123,32323,235235,1,2,3"), format!("{}", &sut));
+        assert_eq!(sut.len(), 6);
+        assert_eq!(sut.iter().next(), Some(&123.into()));
     }
 }
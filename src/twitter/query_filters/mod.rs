@@ -18,29 +18,35 @@
  */
 
 use crate::{
-    errors::{TwitterBuilderError, TwitterError},
-    Filter,
+    errors::TwitterBuilderError, twitter::query_params::main_options::MainOptions, Filter,
 };
 
-use self::{group::GroupList, ids::IDFilter};
+use self::{group::GroupList, ids::IDFilter, usernames::UsernameFilter};
 
 pub mod filter;
 pub mod group;
 pub mod ids;
+pub mod usernames;
 
-/// A wrapper struct to contain the filter types used to make requests to the twitter API
-/// Guaranteed to contain at least some data
+/// A wrapper struct to contain the filter types used to make requests to the twitter API.
+/// Which of the filters need to be present depends on the endpoint the request is made to.
+#[derive(Clone, Debug)]
 pub struct QueryFilters {
-    groups: Option<GroupList>,
-    ids: Option<IDFilter>,
+    pub(crate) groups: Option<GroupList>,
+    pub(crate) ids: Option<IDFilter>,
+    usernames: Option<UsernameFilter>,
+    options: Vec<MainOptions>,
 }
 
 impl QueryFilters {
     /// Returns an empty builder
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> QueryFiltersBuilder {
         QueryFiltersBuilder {
             groups: None,
             ids: None,
+            usernames: Vec::new(),
+            options: Vec::new(),
         }
     }
     /// Gets the tweet ids in the filter, if any exist
@@ -51,6 +57,20 @@ impl QueryFilters {
     pub fn groups(self) -> Option<GroupList> {
         self.groups
     }
+    /// Gets the usernames of the query filter, if any exist
+    pub fn usernames(&self) -> Option<&UsernameFilter> {
+        self.usernames.as_ref()
+    }
+    /// Gets the extra query options, like time windows and pagination tokens
+    pub fn options(&self) -> &[MainOptions] {
+        &self.options
+    }
+    /// Sets an option, replacing any option already set for the same query parameter
+    pub fn set_option(&mut self, option: MainOptions) {
+        let key = option.get_query_tuple().0;
+        self.options.retain(|o| o.get_query_tuple().0 != key);
+        self.options.push(option);
+    }
 }
 
 /// A builder object for QueryFilters to separate the build stage where filter
@@ -58,6 +78,8 @@ impl QueryFilters {
 pub struct QueryFiltersBuilder {
     groups: Option<GroupList>,
     ids: Option<IDFilter>,
+    usernames: Vec<String>,
+    options: Vec<MainOptions>,
 }
 
 impl QueryFiltersBuilder {
@@ -134,18 +156,46 @@ impl QueryFiltersBuilder {
         };
         self
     }
-    /// Consumes the QueryFiltersBuilder and returns an empty Err if
-    /// the builder had not been provided with either ids or groups.
-    /// Returns a QueryFilters struct if either was provided.
+    /// Adds a username to the filter. The username is validated when the builder is built.
+    pub fn add_username(mut self, username: &str) -> Self {
+        self.usernames.push(String::from(username));
+        self
+    }
+    /// Adds an option to the query. Adding an option for a query parameter that
+    /// already has one replaces the old option.
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        let key = option.get_query_tuple().0;
+        self.options.retain(|o| o.get_query_tuple().0 != key);
+        self.options.push(option);
+        self
+    }
+    /// Consumes the QueryFiltersBuilder and returns an Err if the builder
+    /// had not been provided with any ids, groups or usernames, or if the
+    /// usernames are not valid twitter handles.
+    /// Returns a QueryFilters struct otherwise.
     pub fn build(self) -> Result<QueryFilters, TwitterBuilderError> {
-        if self.ids.is_none() && self.groups.is_none() {
+        if self.ids.is_none() && self.groups.is_none() && self.usernames.is_empty() {
             return Err(TwitterBuilderError::BadQueryError(String::from(
                 "No query filters provided!",
             )));
         }
-        return Ok(QueryFilters {
+        self.build_for_request()
+    }
+    /// Builds the filters even if none were provided, as requests to many endpoints
+    /// need none. Checking that the filters fit the endpoint is left to the `Endpoint`.
+    pub(crate) fn build_for_request(self) -> Result<QueryFilters, TwitterBuilderError> {
+        let usernames = if self.usernames.is_empty() {
+            None
+        } else {
+            Some(UsernameFilter::try_from_iterator(
+                self.usernames.iter().map(String::as_str),
+            )?)
+        };
+        Ok(QueryFilters {
             groups: self.groups,
             ids: self.ids,
-        });
+            usernames,
+            options: self.options,
+        })
     }
 }
@@ -0,0 +1,200 @@
+/*
+The GPLv3 License (GPLv3)
+
+Copyright (c) 2022 Jacob Skoog
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use std::fmt::Write as _;
+
+use crate::errors::TwitterBuilderError;
+
+/// The most usernames twitter accepts in a single users lookup request
+pub const MAX_USERNAMES: usize = 100;
+/// The longest a twitter handle can be
+pub const MAX_USERNAME_LEN: usize = 15;
+
+/// This struct represents a list of usernames to use in a
+/// users GET request. Must contain between 1 and 100 usernames
+/// to be used in a request.
+#[derive(Clone, Debug)]
+pub struct UsernameFilter {
+    username_list: Vec<Username>,
+}
+
+impl UsernameFilter {
+    pub fn new(username: Username) -> UsernameFilter {
+        UsernameFilter {
+            username_list: vec![username],
+        }
+    }
+    /// Creates a new UsernameFilter from an Iterable of strings, failing on
+    /// the first string that is not a valid twitter handle
+    pub fn try_from_iterator<'a>(
+        usernames: impl IntoIterator<Item = &'a str>,
+    ) -> Result<UsernameFilter, TwitterBuilderError> {
+        let mut filter = UsernameFilter {
+            username_list: Vec::new(),
+        };
+        for username in usernames {
+            filter.try_add_username(username)?;
+        }
+        Ok(filter)
+    }
+    /// Adds a username to the UsernameFilter, unless it is already in it.
+    /// Handles are not case sensitive, so "Jack" and "jack" are the same user.
+    pub fn add_username(&mut self, username: Username) -> Result<(), TwitterBuilderError> {
+        let lowercase = username.username.to_ascii_lowercase();
+        if self
+            .username_list
+            .iter()
+            .any(|u| u.username.to_ascii_lowercase() == lowercase)
+        {
+            return Ok(());
+        }
+        if self.username_list.len() >= MAX_USERNAMES {
+            return Err(TwitterBuilderError::BadQueryError(format!(
+                "cannot look up more than {} usernames in one request",
+                MAX_USERNAMES
+            )));
+        }
+        self.username_list.push(username);
+        Ok(())
+    }
+    /// Validates the passed string as a username and adds it to the UsernameFilter
+    pub fn try_add_username(&mut self, username: &str) -> Result<(), TwitterBuilderError> {
+        self.add_username(Username::try_from(username)?)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.username_list.is_empty()
+    }
+    pub fn len(&self) -> usize {
+        self.username_list.len()
+    }
+}
+
+impl IntoIterator for UsernameFilter {
+    type Item = Username;
+
+    type IntoIter = <Vec<Username> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.username_list.into_iter()
+    }
+}
+
+impl Display for UsernameFilter {
+    /// Formats the UsernameFilter for use in a request. Will fail and return an
+    /// error if the UsernameFilter is empty.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            // An empty UsernameFilter is invalid
+            return std::fmt::Result::Err(std::fmt::Error);
+        };
+        let mut s = String::with_capacity(16 * self.len());
+        self.username_list.iter().for_each(|username| {
+            write!(s, "{},", username).expect("Could not write username into string buffer")
+        });
+        write!(f, "{}", &s[0..(s.len() - 1)])
+    }
+}
+
+/// A twitter handle, guaranteed to be 1 to 15 characters long and
+/// to only contain ASCII letters, digits and underscores.
+/// A leading `@` is stripped when parsing.
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub struct Username {
+    username: String,
+}
+impl Display for Username {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.username)
+    }
+}
+impl TryFrom<&str> for Username {
+    type Error = TwitterBuilderError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let username = value.strip_prefix('@').unwrap_or(value);
+        if username.is_empty() || username.len() > MAX_USERNAME_LEN {
+            return Err(TwitterBuilderError::BadQueryError(format!(
+                "username \"{}\" must be between 1 and {} characters long",
+                value, MAX_USERNAME_LEN
+            )));
+        }
+        if !username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(TwitterBuilderError::BadQueryError(format!(
+                "username \"{}\" may only contain letters, digits and underscores",
+                value
+            )));
+        }
+        Ok(Username {
+            username: String::from(username),
+        })
+    }
+}
+impl TryFrom<String> for Username {
+    type Error = TwitterBuilderError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Username::try_from(value.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Username, UsernameFilter, MAX_USERNAMES};
+
+    #[test]
+    fn test_username_rules() {
+        assert!(Username::try_from("Archival_Blob").is_ok());
+        assert_eq!(
+            Username::try_from("@jack").unwrap().to_string(),
+            String::from("jack")
+        );
+        assert!(Username::try_from("").is_err());
+        assert!(Username::try_from("@").is_err());
+        assert!(Username::try_from("sixteen_chars_xx").is_err());
+        assert!(Username::try_from("fifteen_chars_x").is_ok());
+        assert!(Username::try_from("has space").is_err());
+        assert!(Username::try_from("dash-name").is_err());
+        assert!(Username::try_from("åäö").is_err());
+    }
+
+    #[test]
+    fn test_username_filter() {
+        let mut sut = UsernameFilter::try_from_iterator(vec!["jack", "@TwitterDev"]).unwrap();
+        sut.try_add_username("jack").unwrap();
+        sut.try_add_username("@JACK").unwrap();
+        sut.try_add_username("twitterdev").unwrap();
+        assert_eq!(String::from("jack,TwitterDev"), format!("{}", &sut));
+
+        let mut sut = UsernameFilter::try_from_iterator(
+            (0..MAX_USERNAMES)
+                .map(|i| format!("user{}", i))
+                .collect::<Vec<String>>()
+                .iter()
+                .map(String::as_str),
+        )
+        .unwrap();
+        assert_eq!(sut.len(), MAX_USERNAMES);
+        assert!(sut.try_add_username("user0").is_ok());
+        assert!(sut.try_add_username("one_too_many").is_err());
+    }
+}
@@ -17,4 +17,98 @@
   along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub enum Expansions {}
+use std::fmt::{Display, Formatter};
+
+use crate::errors::TwitterBuilderError;
+
+/// The objects that can be expanded in a response. Expanded objects are returned in
+/// the `includes` of the response, next to the `data` that references them.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Expansions {
+    AuthorId,
+    ReferencedTweetsId,
+    ReferencedTweetsIdAuthorId,
+    EditHistoryTweetIds,
+    EntitiesMentionsUsername,
+    InReplyToUserId,
+    AttachmentsMediaKeys,
+    AttachmentsPollIds,
+    GeoPlaceId,
+    PinnedTweetId,
+    OwnerId,
+    SenderId,
+    ParticipantIds,
+    CreatorId,
+    HostIds,
+    SpeakerIds,
+    InvitedUserIds,
+    TopicIds,
+}
+
+impl Display for Expansions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Expansions::AuthorId => "author_id",
+            Expansions::ReferencedTweetsId => "referenced_tweets.id",
+            Expansions::ReferencedTweetsIdAuthorId => "referenced_tweets.id.author_id",
+            Expansions::EditHistoryTweetIds => "edit_history_tweet_ids",
+            Expansions::EntitiesMentionsUsername => "entities.mentions.username",
+            Expansions::InReplyToUserId => "in_reply_to_user_id",
+            Expansions::AttachmentsMediaKeys => "attachments.media_keys",
+            Expansions::AttachmentsPollIds => "attachments.poll_ids",
+            Expansions::GeoPlaceId => "geo.place_id",
+            Expansions::PinnedTweetId => "pinned_tweet_id",
+            Expansions::OwnerId => "owner_id",
+            Expansions::SenderId => "sender_id",
+            Expansions::ParticipantIds => "participant_ids",
+            Expansions::CreatorId => "creator_id",
+            Expansions::HostIds => "host_ids",
+            Expansions::SpeakerIds => "speaker_ids",
+            Expansions::InvitedUserIds => "invited_user_ids",
+            Expansions::TopicIds => "topic_ids",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+impl Expansions {
+    /// Every expansion
+    pub const ALL: [Expansions; 18] = [
+        Expansions::AuthorId,
+        Expansions::ReferencedTweetsId,
+        Expansions::ReferencedTweetsIdAuthorId,
+        Expansions::EditHistoryTweetIds,
+        Expansions::EntitiesMentionsUsername,
+        Expansions::InReplyToUserId,
+        Expansions::AttachmentsMediaKeys,
+        Expansions::AttachmentsPollIds,
+        Expansions::GeoPlaceId,
+        Expansions::PinnedTweetId,
+        Expansions::OwnerId,
+        Expansions::SenderId,
+        Expansions::ParticipantIds,
+        Expansions::CreatorId,
+        Expansions::HostIds,
+        Expansions::SpeakerIds,
+        Expansions::InvitedUserIds,
+        Expansions::TopicIds,
+    ];
+}
+
+impl TryFrom<&str> for Expansions {
+    type Error = TwitterBuilderError;
+
+    /// Parses the name twitter uses for the expansion, e.g. "author_id"
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Expansions::ALL
+            .into_iter()
+            .find(|v| v.to_string() == value)
+            .ok_or_else(|| {
+                TwitterBuilderError::BadQueryError(format!(
+                    "\"{}\" is not a known expansion",
+                    value
+                ))
+            })
+    }
+}
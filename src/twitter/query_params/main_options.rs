@@ -19,11 +19,16 @@
 
 use std::fmt::{Display, Formatter};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 
 use crate::twitter::query_filters::{group::GroupList, ids::Id};
+use crate::twitter::query_params::expansions::Expansions;
+use crate::twitter::twitter_objects::compliance::{ComplianceJobStatus, ComplianceJobType};
+use crate::twitter::twitter_objects::dm::{DmEventField, DmEventType};
+use crate::twitter::twitter_objects::space::SpaceField;
+use crate::twitter::twitter_objects::{list::ListField, tweet::TweetField, user::UserField};
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub enum MainOptions {
     Query(GroupList),
     StartTime(DateTime<Utc>),
@@ -32,16 +37,48 @@ pub enum MainOptions {
     UntilId(Id),
     MaxResults(Max),
     SortOrder(Order),
-    NextToken,       // WIP
-    PaginationToken, // WIP
+    NextToken(String),
+    PaginationToken(String),
+    /// Leaves out retweets and/or replies from a user timeline
+    Exclude(Vec<Exclude>),
+    /// The tweet fields to include in returned tweets
+    TweetFields(Vec<TweetField>),
+    /// The user fields to include in returned users
+    UserFields(Vec<UserField>),
+    /// The list fields to include in returned lists
+    ListFields(Vec<ListField>),
+    /// The direct message event fields to include in returned events
+    DmEventFields(Vec<DmEventField>),
+    /// The kinds of direct message events to return
+    EventTypes(Vec<DmEventType>),
+    /// The space fields to include in returned spaces
+    SpaceFields(Vec<SpaceField>),
+    /// The ids of the spaces to look up
+    SpaceIds(Vec<String>),
+    /// Which spaces a space search returns, all of them if not set
+    State(SearchState),
+    /// The type of the compliance jobs to list
+    ComplianceType(ComplianceJobType),
+    /// Only lists the compliance jobs with this status
+    ComplianceStatus(ComplianceJobStatus),
+    /// The length of the time periods tweets are counted in
+    Granularity(Granularity),
+    /// The referenced objects to include in the response
+    Expansions(Vec<Expansions>),
 }
 
 impl MainOptions {
-    pub fn get_query_tuple<'s>(&'s self) -> (String, String) {
+    pub fn get_query_tuple(&self) -> (String, String) {
         match self {
             MainOptions::Query(groups) => (String::from("query"), groups.to_string()),
-            MainOptions::StartTime(start) => (String::from("start_time"), start.to_string()),
-            MainOptions::EndTime(end) => (String::from("end_time"), end.to_string()),
+            MainOptions::StartTime(start) => (
+                String::from("start_time"),
+                start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            MainOptions::EndTime(end) => (
+                String::from("end_time"),
+                end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
             MainOptions::SinceId(since_id) => (String::from("since_id"), since_id.to_string()),
             MainOptions::UntilId(until_id) => (String::from("until_id"), until_id.to_string()),
             MainOptions::MaxResults(max_results) => {
@@ -50,13 +87,54 @@ impl MainOptions {
             MainOptions::SortOrder(sort_order) => {
                 (String::from("sort_order"), sort_order.to_string())
             }
-            MainOptions::NextToken => (String::from("next_token"), todo!()),
-            MainOptions::PaginationToken => (String::from("pagination_token"), todo!()),
+            MainOptions::NextToken(token) => (String::from("next_token"), token.to_string()),
+            MainOptions::PaginationToken(token) => {
+                (String::from("pagination_token"), token.to_string())
+            }
+            MainOptions::Exclude(excludes) => (String::from("exclude"), join(excludes)),
+            MainOptions::TweetFields(fields) => (String::from("tweet.fields"), join(fields)),
+            MainOptions::UserFields(fields) => (String::from("user.fields"), join(fields)),
+            MainOptions::ListFields(fields) => (String::from("list.fields"), join(fields)),
+            MainOptions::DmEventFields(fields) => (String::from("dm_event.fields"), join(fields)),
+            MainOptions::EventTypes(types) => (String::from("event_types"), join(types)),
+            MainOptions::SpaceFields(fields) => (String::from("space.fields"), join(fields)),
+            MainOptions::SpaceIds(ids) => (String::from("ids"), ids.join(",")),
+            MainOptions::State(state) => (String::from("state"), state.to_string()),
+            MainOptions::ComplianceType(job_type) => (String::from("type"), job_type.to_string()),
+            MainOptions::ComplianceStatus(status) => (String::from("status"), status.to_string()),
+            MainOptions::Granularity(granularity) => {
+                (String::from("granularity"), granularity.to_string())
+            }
+            MainOptions::Expansions(expansions) => (String::from("expansions"), join(expansions)),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+/// Joins the values into the comma-separated list twitter expects
+fn join<T: Display>(values: &[T]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    values.join(",")
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Exclude {
+    Retweets,
+    Replies,
+}
+impl Display for Exclude {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exclude::Retweets => {
+                write!(f, "retweets")
+            }
+            Exclude::Replies => {
+                write!(f, "replies")
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum Order {
     Recency,
     Relevancy,
@@ -74,21 +152,67 @@ impl Display for Order {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SearchState {
+    Live,
+    Scheduled,
+    All,
+}
+impl Display for SearchState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchState::Live => {
+                write!(f, "live")
+            }
+            SearchState::Scheduled => {
+                write!(f, "scheduled")
+            }
+            SearchState::All => {
+                write!(f, "all")
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Granularity {
+    Minute,
+    Hour,
+    Day,
+}
+impl Display for Granularity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Granularity::Minute => {
+                write!(f, "minute")
+            }
+            Granularity::Hour => {
+                write!(f, "hour")
+            }
+            Granularity::Day => {
+                write!(f, "day")
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub struct Max {
-    max: u8,
+    max: u16,
 }
 impl Max {
-    pub fn new(max: u8) -> Max {
-        assert!(
-            (10..=100).contains(&max),
-            "Max must be between 10 and 100 (inclusive both ends)"
-        );
+    /// Creates a maximum number of results. The range it must be in depends on the
+    /// endpoint and is checked when the request is built.
+    pub fn new(max: u16) -> Max {
         Max { max }
     }
+    /// Gets the maximum as a number
+    pub fn get(&self) -> u16 {
+        self.max
+    }
 }
-impl From<u8> for Max {
-    fn from(max: u8) -> Self {
+impl From<u16> for Max {
+    fn from(max: u16) -> Self {
         Max { max }
     }
 }
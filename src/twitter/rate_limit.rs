@@ -0,0 +1,82 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderMap;
+
+/// The rate limit state twitter reports in the `x-rate-limit-*` headers of a response
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct RateLimit {
+    /// The number of requests allowed in the current window
+    pub limit: u32,
+    /// The number of requests left in the current window
+    pub remaining: u32,
+    /// When the current window ends and `remaining` is reset to `limit`
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Reads the rate limit from response headers, None if any of the headers are missing
+    pub fn from_headers(headers: &HeaderMap) -> Option<RateLimit> {
+        let header = |name: &str| -> Option<i64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+        Some(RateLimit {
+            limit: header("x-rate-limit-limit")? as u32,
+            remaining: header("x-rate-limit-remaining")? as u32,
+            reset: Utc
+                .timestamp_opt(header("x-rate-limit-reset")?, 0)
+                .single()?,
+        })
+    }
+
+    /// True if no more requests can be made before the window resets
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// The time left until the window resets, zero if it already has
+    pub fn time_until_reset(&self) -> Duration {
+        (self.reset - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// Sleeps until the window resets, plus a second to not race twitter's clock
+    pub async fn wait_for_reset(&self) {
+        tokio::time::sleep(self.time_until_reset() + Duration::from_secs(1)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn from_headers_test() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-rate-limit-limit", HeaderValue::from_static("15"));
+        headers.insert("x-rate-limit-remaining", HeaderValue::from_static("0"));
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("x-rate-limit-reset", HeaderValue::from_static("1666000000"));
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(rate_limit.limit, 15);
+        assert!(rate_limit.is_exhausted());
+        assert_eq!(rate_limit.reset.timestamp(), 1666000000);
+        assert_eq!(rate_limit.time_until_reset(), Duration::ZERO);
+    }
+}
@@ -15,23 +15,43 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
+
+use futures::stream::{self, Stream, StreamExt};
 use http::Method;
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 
 use crate::errors::{TwitterBuilderError, TwitterError};
-use crate::twitter::endpoints::AuthenticationData;
+use crate::twitter::access_level::AccessLevel;
+use crate::twitter::endpoints::{AuthenticationData, TWITTER_URL};
 
-use super::query_filters::{QueryFilters, QueryFiltersBuilder};
-use super::{
-    query_filters::{filter::Filter, group::GroupList},
-    Endpoint,
-};
+use super::cache::{CacheKey, ResponseCache};
+use super::cassette::Cassette;
+use super::new_tweet::NewTweet;
+use super::query_filters::{group::GroupList, QueryFilters, QueryFiltersBuilder};
+use super::query_params::main_options::MainOptions;
+use super::rate_limit::RateLimit;
+use super::twitter_objects::response::{Meta, TwitterResponse};
+use super::{query_filters::filter::Filter, Endpoint};
 
+#[derive(Clone)]
 pub struct TwitterRequest {
     query_filters: QueryFilters,
     endpoint: Endpoint,
     method: Method,
     authentication: AuthenticationData,
+    body: Option<serde_json::Value>,
+    /// The URL of the API the request is sent to
+    base_url: String,
+    /// The number of results fetched by the pages before this request
+    fetched: u64,
+    /// The cassette the request is recorded to or replayed from, if any
+    cassette: Option<Arc<Cassette>>,
+    /// The cache the response is looked up in and stored to, if any
+    cache: Option<Arc<ResponseCache>>,
+    /// Whether a cached response is ignored, the response is still cached
+    bypass_cache: bool,
 }
 
 impl TwitterRequest {
@@ -41,15 +61,204 @@ impl TwitterRequest {
             endpoint: None,
             method: None,
             authentication: None,
+            body: None,
+            base_url: None,
+            cassette: None,
+            cache: None,
+            bypass_cache: false,
+        }
+    }
+
+    /// Creates the request for the page following the page `meta` was returned with.
+    /// Returns `Ok(None)` when there are no more pages, and a `HistoryLimitError` when
+    /// they ran out because the endpoint's limit on how far back it reaches was hit,
+    /// such as the 3200 most recent tweets of a user's timeline.
+    pub fn next_page(&self, meta: &Meta) -> Result<Option<TwitterRequest>, TwitterError> {
+        let fetched = self.fetched + meta.result_count.unwrap_or(0);
+        match &meta.next_token {
+            Some(token) => {
+                let mut next = self.clone();
+                next.query_filters
+                    .set_option(self.endpoint.pagination_option(token));
+                next.fetched = fetched;
+                Ok(Some(next))
+            }
+            None => match self.endpoint.history_limit(self.query_filters.options()) {
+                Some(limit) if fetched >= limit as u64 => Err(TwitterError::HistoryLimitError(
+                    self.endpoint.clone(),
+                    limit,
+                )),
+                _ => Ok(None),
+            },
         }
     }
 
     /// Sends the request, consuming the `TwitterRequest` object and return the request Result
     pub async fn send_request(self, client: &Client) -> Result<reqwest::Response, TwitterError> {
-        self.endpoint
-            .send_request(client, self.method, self.query_filters, self.authentication)
-            .await
+        if self.cassette.is_none() && self.cache.is_none() {
+            return self
+                .endpoint
+                .send_request_to(
+                    &self.base_url,
+                    client,
+                    self.method,
+                    self.query_filters,
+                    self.authentication,
+                    self.body,
+                )
+                .await;
+        }
+        let request = self.endpoint.build_request_to(
+            &self.base_url,
+            client,
+            self.method,
+            &self.query_filters,
+            &self.authentication,
+            self.body,
+        )?;
+        // A stream's body never ends, so it can't be read into the cache
+        let key = self
+            .cache
+            .as_ref()
+            .filter(|_| !self.endpoint.is_streaming())
+            .map(|_| CacheKey::of(&request));
+        if let (Some(cache), Some(key)) = (&self.cache, &key) {
+            if !self.bypass_cache {
+                if let Some(response) = cache.lookup(key).await? {
+                    return Ok(response);
+                }
+            }
+        }
+        let response = match &self.cassette {
+            Some(cassette) => {
+                cassette
+                    .send(client, request, &self.authentication.secrets())
+                    .await?
+            }
+            None => client
+                .execute(request)
+                .await
+                .map_err(TwitterError::RequestError)?,
+        };
+        match (&self.cache, key) {
+            (Some(cache), Some(key)) => cache.update(&self.endpoint, key, response).await,
+            _ => Ok(response),
+        }
+    }
+
+    /// Sends the request and parses the response body into a `TwitterResponse`.
+    /// `T` is the type of the response's `data` field, e.g. `User` for `Endpoint::UserById`
+    /// or `Vec<User>` for `Endpoint::UsersByUsernames`.
+    /// Returns a `RateLimitError` if the rate limit was hit, and an `ApiError` if
+    /// twitter did not respond with a success status for any other reason.
+    pub async fn send_typed<T: DeserializeOwned>(
+        self,
+        client: &Client,
+    ) -> Result<TwitterResponse<T>, TwitterError> {
+        let response = check_response(self.send_request(client).await?).await?;
+        response.json().await.map_err(TwitterError::RequestError)
+    }
+
+    /// Sends a request to a streaming endpoint such as `Endpoint::StreamTweets` and returns
+    /// the objects twitter sends as they arrive. `T` is the type of each line, e.g.
+    /// `TweetResponse` for the tweets of a filtered stream.
+    pub async fn send_streaming<T: DeserializeOwned>(
+        self,
+        client: &Client,
+    ) -> Result<impl Stream<Item = Result<T, TwitterError>>, TwitterError> {
+        let response = check_response(self.send_request(client).await?).await?;
+        Ok(json_lines(response.bytes_stream().boxed()))
+    }
+
+    /// Like `send_typed`, but when the rate limit is hit it waits for the limit
+    /// to reset and sends the request again instead of returning a `RateLimitError`.
+    pub async fn send_typed_waiting<T: DeserializeOwned>(
+        self,
+        client: &Client,
+    ) -> Result<TwitterResponse<T>, TwitterError> {
+        loop {
+            match self.clone().send_typed(client).await {
+                Err(TwitterError::RateLimitError(rate_limit)) => rate_limit.wait_for_reset().await,
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Turns a response without a success status into a `RateLimitError` if the rate
+/// limit was hit, or an `ApiError` otherwise.
+pub(crate) async fn check_response(
+    response: reqwest::Response,
+) -> Result<reqwest::Response, TwitterError> {
+    let status = response.status();
+    if status == http::StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+            return Err(TwitterError::RateLimitError(rate_limit));
+        }
+    }
+    if !status.is_success() {
+        let body = response.text().await.map_err(TwitterError::RequestError)?;
+        return Err(TwitterError::ApiError(status, body));
+    }
+    Ok(response)
+}
+
+/// Splits a body of newline delimited JSON into its values as the bytes arrive. Blank
+/// lines, such as the keep-alive newlines of streaming endpoints, are skipped.
+pub(crate) fn json_lines<T, S, B>(bytes: S) -> impl Stream<Item = Result<T, TwitterError>>
+where
+    T: DeserializeOwned,
+    S: Stream<Item = reqwest::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    stream::unfold(
+        (bytes, Vec::new(), false),
+        |(mut bytes, mut buffer, mut done)| async move {
+            loop {
+                if let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    return Some((parse_line(&line), (bytes, buffer, done)));
+                }
+                if done {
+                    if buffer.iter().all(u8::is_ascii_whitespace) {
+                        return None;
+                    }
+                    let line = std::mem::take(&mut buffer);
+                    return Some((parse_line(&line), (bytes, buffer, done)));
+                }
+                match bytes.next().await {
+                    Some(Ok(chunk)) => buffer.extend_from_slice(chunk.as_ref()),
+                    Some(Err(e)) => {
+                        // The rest of the body is lost, so the stream ends after the error
+                        buffer.clear();
+                        return Some((Err(TwitterError::RequestError(e)), (bytes, buffer, true)));
+                    }
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
+
+/// Builds a response out of a stored status, headers and body, for answering a
+/// request without sending it
+pub(crate) fn response_from_parts(
+    status: u16,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+) -> Result<reqwest::Response, http::Error> {
+    let mut response = http::Response::builder().status(status);
+    for (name, value) in headers {
+        response = response.header(name, value);
     }
+    Ok(reqwest::Response::from(response.body(body)?))
+}
+
+fn parse_line<T: DeserializeOwned>(line: &[u8]) -> Result<T, TwitterError> {
+    serde_json::from_slice(line).map_err(TwitterError::JsonError)
 }
 
 pub struct TwitterRequestBuilder {
@@ -57,6 +266,11 @@ pub struct TwitterRequestBuilder {
     endpoint: Option<Endpoint>,
     method: Option<Method>,
     authentication: Option<AuthenticationData>,
+    body: Option<serde_json::Value>,
+    base_url: Option<String>,
+    cassette: Option<Arc<Cassette>>,
+    cache: Option<Arc<ResponseCache>>,
+    bypass_cache: bool,
 }
 
 impl TwitterRequestBuilder {
@@ -120,11 +334,42 @@ impl TwitterRequestBuilder {
         self
     }
 
+    /// Adds all the groups and filters of the group list to the query
+    pub fn add_group_list(mut self, group_list: GroupList) -> Self {
+        self.query_filters = self.query_filters.add_group_list(group_list);
+        self
+    }
+
     pub fn add_id(mut self, id: u64) -> Self {
         self.query_filters = self.query_filters.add_id(id);
         self
     }
 
+    /// Adds an option such as a time window, `since_id` or pagination token to the request.
+    /// Options the endpoint does not accept make the build fail.
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        self.query_filters = self.query_filters.add_option(option);
+        self
+    }
+
+    /// Adds a username to look up. Usernames are validated when the request is built,
+    /// they must be 1 to 15 letters, digits or underscores and at most 100 can be added.
+    pub fn add_username(mut self, username: &str) -> Self {
+        self.query_filters = self.query_filters.add_username(username);
+        self
+    }
+
+    /// Adds a set of usernames to look up
+    pub fn add_usernames<'a, I>(mut self, usernames: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for username in usernames {
+            self.query_filters = self.query_filters.add_username(username);
+        }
+        self
+    }
+
     /// Adds a new filter group that can evaluate to true alternatively to previous groups.
     /// The group will be initiated with the passed filter
     pub fn add_or_group(mut self, init_filter: Filter) -> Self {
@@ -138,27 +383,264 @@ impl TwitterRequestBuilder {
         self
     }
 
+    /// Adds OAuth 1.0a user context authentication to the request builder
+    pub fn add_oauth_auth(
+        mut self,
+        consumer_key: &str,
+        consumer_secret: &str,
+        access_token: &str,
+        access_token_secret: &str,
+    ) -> Self {
+        self.authentication = Some(AuthenticationData::new_oauth_auth(
+            consumer_key,
+            consumer_secret,
+            access_token,
+            access_token_secret,
+        ));
+        self
+    }
+
+    /// Adds an OAuth 2.0 user context token to the request builder
+    pub fn add_oauth2_user_token(mut self, token: &str) -> Self {
+        self.authentication = Some(AuthenticationData::new_oauth2_user_auth(token));
+        self
+    }
+
+    /// Sets already constructed authentication data on the request builder
+    pub fn set_authentication(mut self, authentication: AuthenticationData) -> Self {
+        self.authentication = Some(authentication);
+        self
+    }
+
+    /// Sets the JSON body sent with the request, used by endpoints that create things
+    pub fn set_body(mut self, body: serde_json::Value) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Sets a tweet to create as the body of the request
+    pub fn set_new_tweet(mut self, tweet: &NewTweet) -> Self {
+        self.body =
+            Some(serde_json::to_value(tweet).expect("A NewTweet can always be turned into JSON"));
+        self
+    }
+
     /// Sets the method of the request
     pub fn set_method(mut self, method: Method) -> Self {
         self.method = Some(method);
         self
     }
 
-    /// Builds a TwitterRequest that can then be used to send the request
+    /// Sets the URL of the API to send the request to instead of twitter's, such as
+    /// the URL of a mock server
+    pub fn set_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(String::from(base_url));
+        self
+    }
+
+    /// Records the request to the cassette or replays it from there, depending on
+    /// the cassette's mode. Pages requested with `next_page` use the same cassette.
+    pub fn set_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// Answers the request from the cache while the cached response is fresh, and
+    /// caches the response otherwise. Pages requested with `next_page` use the same cache.
+    /// Streaming endpoints are never cached.
+    pub fn set_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sends the request even if the cache has a fresh response to it, the new
+    /// response then replaces the cached one
+    pub fn set_bypass_cache(mut self, bypass: bool) -> Self {
+        self.bypass_cache = bypass;
+        self
+    }
+
+    /// Builds a TwitterRequest that can then be used to send the request.
+    /// Fails if the endpoint, method or authentication is missing, if the
+    /// query filters do not contain what the endpoint needs, or if the authentication
+    /// has an access level that can't use the endpoint or an operator of the query.
     pub fn build(self) -> Result<TwitterRequest, TwitterBuilderError> {
-        if self.endpoint.is_none() {
-            Err(TwitterBuilderError::NoEndpointError)
-        } else if self.method.is_none() {
-            Err(TwitterBuilderError::NoMethodError)
-        } else if self.authentication.is_none() {
-            Err(TwitterBuilderError::NoAuthError)
-        } else {
-            Ok(TwitterRequest {
-                query_filters: self.query_filters.build()?,
-                endpoint: self.endpoint.unwrap(),
-                method: self.method.unwrap(),
-                authentication: self.authentication.unwrap(),
-            })
+        let endpoint = self.endpoint.ok_or(TwitterBuilderError::NoEndpointError)?;
+        let method = self.method.ok_or(TwitterBuilderError::NoMethodError)?;
+        let authentication = self
+            .authentication
+            .ok_or(TwitterBuilderError::NoAuthError)?;
+        let query_filters = self.query_filters.build_for_request()?;
+        endpoint.get_query_params(&method, &query_filters)?;
+        if let Some(access_level) = authentication.get_access_level() {
+            check_access_level(access_level, &endpoint, &method, &query_filters)?;
+        }
+        Ok(TwitterRequest {
+            query_filters,
+            endpoint,
+            method,
+            authentication,
+            body: self.body,
+            base_url: self.base_url.unwrap_or_else(|| String::from(TWITTER_URL)),
+            fetched: 0,
+            cassette: self.cassette,
+            cache: self.cache,
+            bypass_cache: self.bypass_cache,
+        })
+    }
+}
+
+/// Checks that the access level can use the endpoint with the method and every
+/// operator in the query
+fn check_access_level(
+    access_level: AccessLevel,
+    endpoint: &Endpoint,
+    method: &Method,
+    query_filters: &QueryFilters,
+) -> Result<(), TwitterBuilderError> {
+    // Unsupported methods are reported when the request is sent
+    if let Ok(required) = endpoint.get_access_level(method) {
+        if !access_level.satisfies(&required) {
+            return Err(TwitterBuilderError::AccessLevelError(
+                format!("{} {}", method, endpoint),
+                required,
+                access_level,
+            ));
         }
     }
+    for filter in query_filters.groups.iter().flatten() {
+        let required = filter.access_level();
+        if !access_level.satisfies(&required) {
+            return Err(TwitterBuilderError::AccessLevelError(
+                format!("The operator {}", filter),
+                required,
+                access_level,
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::twitter::query_filters::filter::{Exact, Is};
+
+    fn timeline_request() -> TwitterRequest {
+        TwitterRequest::builder()
+            .set_endpoint(Endpoint::TimelineUserMentions(1.into()))
+            .set_method(Method::GET)
+            .add_bearer_token("token")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn next_page_test() {
+        let request = timeline_request();
+        let meta = Meta {
+            result_count: Some(100),
+            next_token: Some(String::from("next")),
+            ..Default::default()
+        };
+        let next = request.next_page(&meta).unwrap().unwrap();
+        assert_eq!(
+            next.query_filters.options(),
+            &[MainOptions::PaginationToken(String::from("next"))]
+        );
+        assert_eq!(next.fetched, 100);
+
+        let last = Meta {
+            result_count: Some(50),
+            ..Default::default()
+        };
+        assert!(next.next_page(&last).unwrap().is_none());
+    }
+
+    #[test]
+    fn next_page_history_limit_test() {
+        let mut request = timeline_request();
+        request.fetched = 700;
+        let last = Meta {
+            result_count: Some(100),
+            ..Default::default()
+        };
+        // Reaching the 800 tweet mentions limit ends the pages with an error
+        assert!(matches!(
+            request.next_page(&last),
+            Err(TwitterError::HistoryLimitError(
+                Endpoint::TimelineUserMentions(_),
+                800
+            ))
+        ));
+
+        let more = Meta {
+            result_count: Some(100),
+            next_token: Some(String::from("next")),
+            ..Default::default()
+        };
+        assert!(request.next_page(&more).unwrap().is_some());
+    }
+
+    #[test]
+    fn access_level_test() {
+        let auth = |level| AuthenticationData::new_bearer_auth("token").with_access_level(level);
+        let search_all = |level| {
+            TwitterRequest::builder()
+                .set_endpoint(Endpoint::SearchTweetsAll)
+                .set_method(Method::GET)
+                .add_and_filter(Filter::From(String::from("rustlang"), true.into()))
+                .set_authentication(auth(level))
+                .build()
+        };
+        assert!(matches!(
+            search_all(AccessLevel::Basic),
+            Err(TwitterBuilderError::AccessLevelError(
+                _,
+                AccessLevel::Pro,
+                AccessLevel::Basic
+            ))
+        ));
+        assert!(search_all(AccessLevel::Pro).is_ok());
+        assert!(search_all(AccessLevel::Academic).is_ok());
+
+        let recent_with = |filter, level| {
+            TwitterRequest::builder()
+                .set_endpoint(Endpoint::SearchTweetsRecent)
+                .set_method(Method::GET)
+                .add_and_filter(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is))
+                .add_and_filter(filter)
+                .set_authentication(auth(level))
+                .build()
+        };
+        assert!(recent_with(Filter::HasLinks(Is::Is), AccessLevel::Basic).is_ok());
+        let error = recent_with(Filter::HasGeo(Is::Is), AccessLevel::Basic)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The operator has:geo requires pro access, but the credentials have basic access"
+        );
+        assert!(recent_with(Filter::HasGeo(Is::Is), AccessLevel::Enterprise).is_ok());
+
+        // The free tier can post tweets but not read them
+        let lookup = |method| {
+            TwitterRequest::builder()
+                .set_endpoint(Endpoint::LookupTweets)
+                .set_method(method)
+                .add_id(1)
+                .set_authentication(auth(AccessLevel::Free))
+                .build()
+        };
+        assert!(lookup(Method::GET).is_err());
+        assert!(lookup(Method::POST).is_ok());
+        // Without an access level nothing is checked
+        assert!(TwitterRequest::builder()
+            .set_endpoint(Endpoint::SearchTweetsAll)
+            .set_method(Method::GET)
+            .add_and_filter(Filter::HasGeo(Is::Is))
+            .add_bearer_token("token")
+            .build()
+            .is_ok());
+    }
 }
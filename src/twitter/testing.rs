@@ -0,0 +1,742 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use http::Method;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::twitter::endpoints::Endpoint;
+
+/// A request a mock server received
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    pub method: String,
+    /// The path of the request, including the query string
+    pub path: String,
+    /// Header names are lower case
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The path without the query string
+    pub fn route(&self) -> &str {
+        self.path.split('?').next().unwrap_or_default()
+    }
+
+    /// The decoded query parameters, in the order they were sent
+    pub fn query_params(&self) -> Vec<(String, String)> {
+        reqwest::Url::parse(&format!("http://localhost{}", self.path))
+            .map(|url| url.query_pairs().into_owned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Gets the decoded value of a query parameter
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        self.query_params()
+            .into_iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// The status, headers and body a mock answers with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A response with the JSON body
+    pub fn json(status: u16, body: &Value) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![(
+                String::from("content-type"),
+                String::from("application/json"),
+            )],
+            body: body.to_string(),
+        }
+    }
+
+    /// A successful response with `data` and no `meta`, e.g. for looking up a single user
+    pub fn data(data: Value) -> MockResponse {
+        MockResponse::json(200, &json!({ "data": data }))
+    }
+
+    /// One page of results with `meta` filled in the way twitter fills it. With a
+    /// `next_token` the client is told there is another page.
+    pub fn page(data: Vec<Value>, next_token: Option<&str>) -> MockResponse {
+        let mut meta = json!({ "result_count": data.len() });
+        let id = |value: Option<&Value>| value.and_then(|v| v.get("id")).cloned();
+        if let (Some(newest), Some(oldest)) = (id(data.first()), id(data.last())) {
+            meta["newest_id"] = newest;
+            meta["oldest_id"] = oldest;
+        }
+        if let Some(next_token) = next_token {
+            meta["next_token"] = json!(next_token);
+        }
+        let mut body = json!({ "meta": meta });
+        if !data.is_empty() {
+            body["data"] = json!(data);
+        }
+        MockResponse::json(200, &body)
+    }
+
+    /// A lookup response with the objects that were found and a not found error for
+    /// each of the missing ids, such as `Endpoint::LookupTweets` returns
+    pub fn lookup(found: Vec<Value>, missing_ids: &[&str]) -> MockResponse {
+        let mut body = json!({});
+        if !found.is_empty() {
+            body["data"] = json!(found);
+        }
+        if !missing_ids.is_empty() {
+            let errors: Vec<Value> = missing_ids
+                .iter()
+                .map(|id| {
+                    json!({
+                        "value": id,
+                        "detail": format!("Could not find tweet with ids: [{}].", id),
+                        "title": "Not Found Error",
+                        "resource_type": "tweet",
+                        "parameter": "ids",
+                        "resource_id": id,
+                        "type": "https://api.twitter.com/2/problems/resource-not-found"
+                    })
+                })
+                .collect();
+            body["errors"] = json!(errors);
+        }
+        MockResponse::json(200, &body)
+    }
+
+    /// The response of the counts endpoints, with the total in `meta`
+    pub fn counts(counts: Vec<Value>) -> MockResponse {
+        let total: u64 = counts
+            .iter()
+            .filter_map(|count| count.get("tweet_count")?.as_u64())
+            .sum();
+        MockResponse::json(
+            200,
+            &json!({ "data": counts, "meta": { "total_tweet_count": total } }),
+        )
+    }
+
+    /// A streaming response, with each object on its own line like the filtered stream
+    pub fn stream(lines: Vec<Value>) -> MockResponse {
+        let body: String = lines.iter().map(|line| format!("{}\r\n", line)).collect();
+        MockResponse {
+            status: 200,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// An error response with a problem body like twitter's
+    pub fn error(status: u16, title: &str, detail: &str) -> MockResponse {
+        MockResponse::json(
+            status,
+            &json!({
+                "title": title,
+                "detail": detail,
+                "type": "about:blank",
+                "status": status
+            }),
+        )
+    }
+
+    /// A 429 response telling the client that the limit resets at `reset`
+    pub fn rate_limited(limit: u32, reset: DateTime<Utc>) -> MockResponse {
+        MockResponse::error(429, "Too Many Requests", "Too Many Requests")
+            .with_rate_limit(limit, 0, reset)
+    }
+
+    /// Adds the `x-rate-limit-*` headers
+    pub fn with_rate_limit(self, limit: u32, remaining: u32, reset: DateTime<Utc>) -> Self {
+        self.with_header("x-rate-limit-limit", &limit.to_string())
+            .with_header("x-rate-limit-remaining", &remaining.to_string())
+            .with_header("x-rate-limit-reset", &reset.timestamp().to_string())
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .push((name.to_ascii_lowercase(), String::from(value)));
+        self
+    }
+}
+
+/// A canned tweet object
+pub fn tweet(id: &str, text: &str) -> Value {
+    json!({ "id": id, "text": text, "edit_history_tweet_ids": [id] })
+}
+
+/// A canned user object
+pub fn user(id: &str, username: &str) -> Value {
+    json!({ "id": id, "name": username, "username": username })
+}
+
+/// A canned tweet count of the counts endpoints
+pub fn tweet_count(start: &str, end: &str, tweet_count: u64) -> Value {
+    json!({ "start": start, "end": end, "tweet_count": tweet_count })
+}
+
+/// Makes the response to a request a mock matched
+type RespondFn = Arc<dyn Fn(&ReceivedRequest) -> MockResponse + Send + Sync>;
+
+/// How a mock answers the requests it matches
+#[derive(Clone)]
+enum Responder {
+    Fixed(MockResponse),
+    Fn(RespondFn),
+}
+
+impl std::fmt::Debug for Responder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Responder::Fixed(response) => f.debug_tuple("Fixed").field(response).finish(),
+            Responder::Fn(_) => f.write_str("Fn"),
+        }
+    }
+}
+
+/// An expected request and the response to answer it with. Requests are matched on
+/// their method and path, and must have every expected query parameter.
+#[derive(Debug, Clone)]
+pub struct Mock {
+    /// None matches any method
+    method: Option<Method>,
+    /// None matches any path
+    path: Option<String>,
+    params: Vec<(String, Option<String>)>,
+    responder: Responder,
+    times: Option<usize>,
+}
+
+impl Mock {
+    /// Expects a request with the method to the path, e.g. "/2/tweets/search/recent"
+    pub fn new(method: Method, path: &str) -> Mock {
+        Mock {
+            method: Some(method),
+            path: Some(String::from(path)),
+            ..Mock::any()
+        }
+    }
+
+    /// Expects any request, whatever its method and path. Useful to answer every
+    /// request the same way and check what was sent with `MockServer::received`.
+    pub fn any() -> Mock {
+        Mock {
+            method: None,
+            path: None,
+            params: Vec::new(),
+            responder: Responder::Fixed(MockResponse::error(
+                500,
+                "No response",
+                "The mock has no response",
+            )),
+            times: None,
+        }
+    }
+
+    /// Expects a request with the method to the endpoint
+    pub fn endpoint(endpoint: &Endpoint, method: Method) -> Mock {
+        Mock::new(method, &endpoint.url(""))
+    }
+
+    /// Expects the query parameter to have the value, e.g. `("query", "(from:rustlang)")`
+    pub fn expect_param(mut self, name: &str, value: &str) -> Self {
+        self.params
+            .push((String::from(name), Some(String::from(value))));
+        self
+    }
+
+    /// Expects the query parameter to not be sent
+    pub fn expect_no_param(mut self, name: &str) -> Self {
+        self.params.push((String::from(name), None));
+        self
+    }
+
+    pub fn respond_with(mut self, response: MockResponse) -> Self {
+        self.responder = Responder::Fixed(response);
+        self
+    }
+
+    /// Answers each request with the response `respond` makes for it, e.g. to return
+    /// the tweets with the requested ids
+    pub fn respond_with_fn<F>(mut self, respond: F) -> Self
+    where
+        F: Fn(&ReceivedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        self.responder = Responder::Fn(Arc::new(respond));
+        self
+    }
+
+    /// Only answers `times` requests, after which the next mock for the path is used.
+    /// `verify` checks that the mock was used exactly that many times.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    fn matches_route(&self, request: &ReceivedRequest) -> bool {
+        let method_matches = match &self.method {
+            Some(method) => request.method == method.as_str(),
+            None => true,
+        };
+        let path_matches = match &self.path {
+            Some(path) => request.route() == path,
+            None => true,
+        };
+        method_matches && path_matches
+    }
+
+    fn respond(&self, request: &ReceivedRequest) -> MockResponse {
+        match &self.responder {
+            Responder::Fixed(response) => response.clone(),
+            Responder::Fn(respond) => respond(request),
+        }
+    }
+
+    /// The method and path the mock expects, e.g. "GET /2/tweets"
+    fn describe(&self) -> String {
+        match (&self.method, &self.path) {
+            (Some(method), Some(path)) => format!("{} {}", method, path),
+            _ => String::from("Any request"),
+        }
+    }
+
+    /// Describes the expected parameters the request does not have, None if it has all
+    fn mismatch(&self, request: &ReceivedRequest) -> Option<String> {
+        let mismatches: Vec<String> = self
+            .params
+            .iter()
+            .filter_map(|(name, expected)| {
+                let actual = request.query_param(name);
+                if actual == *expected {
+                    return None;
+                }
+                Some(format!(
+                    "{} should be {:?} but was {:?}",
+                    name, expected, actual
+                ))
+            })
+            .collect();
+        if mismatches.is_empty() {
+            None
+        } else {
+            Some(mismatches.join(", "))
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    mocks: Vec<(Mock, usize)>,
+    received: Vec<ReceivedRequest>,
+    failures: Vec<String>,
+}
+
+impl State {
+    fn respond(&mut self, request: &ReceivedRequest) -> MockResponse {
+        self.received.push(request.clone());
+        let mut mismatches = Vec::new();
+        for (mock, hits) in self.mocks.iter_mut() {
+            if !mock.matches_route(request) || mock.times.is_some_and(|times| *hits >= times) {
+                continue;
+            }
+            match mock.mismatch(request) {
+                None => {
+                    *hits += 1;
+                    return mock.respond(request);
+                }
+                Some(mismatch) => mismatches.push(mismatch),
+            }
+        }
+        let failure = if mismatches.is_empty() {
+            format!("Unexpected request {} {}", request.method, request.path)
+        } else {
+            format!(
+                "{} {} did not match any mock: {}",
+                request.method,
+                request.path,
+                mismatches.join("; ")
+            )
+        };
+        self.failures.push(failure.clone());
+        MockResponse::error(400, "Invalid Request", &failure)
+    }
+}
+
+/// An in-process HTTP server that answers requests like the twitter API would, for
+/// testing code that uses this crate without credentials or network access. Point
+/// requests at it with `TwitterRequestBuilder::set_base_url(&server.url())`, or the
+/// `base_url` setters of the higher level helpers.
+///
+/// ```no_run
+/// # async fn example() {
+/// use twitter_request::twitter::testing::{tweet, Mock, MockResponse, MockServer};
+/// use twitter_request::{Endpoint, Method};
+///
+/// let server = MockServer::start().await;
+/// server.mock(
+///     Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+///         .expect_param("query", "(from:rustlang)")
+///         .respond_with(MockResponse::page(vec![tweet("1", "Rust 2.0")], None)),
+/// );
+/// // ... send requests to server.url() ...
+/// server.verify();
+/// # }
+/// ```
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts a server on a free port of localhost
+    pub async fn start() -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind the mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let task = tokio::spawn(serve(listener, state.clone()));
+        MockServer { url, state, task }
+    }
+
+    /// The base url of the server, e.g. `http://127.0.0.1:1234`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Adds a mock. When several mocks match a request, the one added first answers it.
+    pub fn mock(&self, mock: Mock) -> &Self {
+        self.state.lock().unwrap().mocks.push((mock, 0));
+        self
+    }
+
+    /// Every request received so far, in the order they arrived
+    pub fn received(&self) -> Vec<ReceivedRequest> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    /// Descriptions of the requests that no mock matched
+    pub fn failures(&self) -> Vec<String> {
+        self.state.lock().unwrap().failures.clone()
+    }
+
+    /// Panics if a request did not match any mock, or if a mock with `times` was not
+    /// used exactly that many times
+    pub fn verify(&self) {
+        let state = self.state.lock().unwrap();
+        let mut failures = state.failures.clone();
+        for (mock, hits) in &state.mocks {
+            if let Some(times) = mock.times {
+                if *hits != times {
+                    failures.push(format!(
+                        "{} was expected {} times but was requested {} times",
+                        mock.describe(),
+                        times,
+                        hits
+                    ));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut stream = BufReader::new(stream);
+            while let Some(request) = read_request(&mut stream).await {
+                let response = state.lock().unwrap().respond(&request);
+                if write_response(stream.get_mut(), &response).await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, response: &MockResponse) -> std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", response.body.len()));
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await
+}
+
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<ReceivedRequest> {
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.ok()? == 0 {
+        return None;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = String::from(parts.next()?);
+    let path = String::from(parts.next()?);
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        stream.read_line(&mut header).await.ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), String::from(value.trim())));
+        }
+    }
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await.ok()?;
+    Some(ReceivedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::TwitterError;
+    use crate::twitter::batch_lookup::BatchTweetLookup;
+    use crate::twitter::query_filters::filter::Filter;
+    use crate::twitter::query_filters::ids::IDFilter;
+    use crate::twitter::request::TwitterRequest;
+    use crate::twitter::twitter_objects::tweet::{Tweet, TweetCount};
+    use crate::twitter::twitter_objects::user::User;
+    use futures::StreamExt;
+    use reqwest::Client;
+
+    fn search(server: &MockServer, from: &str) -> TwitterRequest {
+        TwitterRequest::builder()
+            .set_endpoint(Endpoint::SearchTweetsRecent)
+            .set_method(Method::GET)
+            .add_and_filter(Filter::From(String::from(from), true.into()))
+            .add_bearer_token("token")
+            .set_base_url(server.url())
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pagination_test() {
+        let server = MockServer::start().await;
+        let mock = Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+            .expect_param("query", "(from:rustlang)");
+        server
+            .mock(
+                mock.clone()
+                    .expect_no_param("next_token")
+                    .respond_with(MockResponse::page(
+                        vec![tweet("3", "three"), tweet("2", "two")],
+                        Some("page-2"),
+                    ))
+                    .times(1),
+            )
+            .mock(
+                mock.expect_param("next_token", "page-2")
+                    .respond_with(MockResponse::page(vec![tweet("1", "one")], None))
+                    .times(1),
+            );
+        let client = Client::new();
+        let request = search(&server, "rustlang");
+        let first = request
+            .clone()
+            .send_typed::<Vec<Tweet>>(&client)
+            .await
+            .unwrap();
+        let meta = first.meta.unwrap();
+        assert_eq!(meta.newest_id.as_deref(), Some("3"));
+        let next = request.next_page(&meta).unwrap().unwrap();
+        let second = next
+            .clone()
+            .send_typed::<Vec<Tweet>>(&client)
+            .await
+            .unwrap();
+        assert_eq!(second.data.unwrap()[0].text, "one");
+        assert!(next.next_page(&second.meta.unwrap()).unwrap().is_none());
+        server.verify();
+        assert_eq!(server.received().len(), 2);
+        assert_eq!(
+            server.received()[0].header("authorization"),
+            Some("Bearer token")
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_and_users_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(
+                Mock::endpoint(&Endpoint::LookupTweets, Method::GET)
+                    .expect_param("ids", "1,2")
+                    .respond_with(MockResponse::lookup(vec![tweet("1", "one")], &["2"])),
+            )
+            .mock(
+                Mock::endpoint(&Endpoint::UserById(7.into()), Method::GET)
+                    .respond_with(MockResponse::data(user("7", "rustlang"))),
+            );
+        let client = Client::new();
+        let auth = crate::AuthenticationData::new_bearer_auth("token");
+        let result = BatchTweetLookup::new()
+            .base_url(server.url())
+            .lookup(&client, &auth, IDFilter::from_iterator([1u64, 2]))
+            .await
+            .unwrap();
+        assert_eq!(result.tweets.len(), 1);
+        assert_eq!(result.not_found, vec![2.into()]);
+        let user = TwitterRequest::builder()
+            .set_endpoint(Endpoint::UserById(7.into()))
+            .set_method(Method::GET)
+            .set_authentication(auth)
+            .set_base_url(server.url())
+            .build()
+            .unwrap()
+            .send_typed::<User>(&client)
+            .await
+            .unwrap()
+            .into_data()
+            .unwrap();
+        assert_eq!(user.username, "rustlang");
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn counts_and_stream_test() {
+        let server = MockServer::start().await;
+        server
+            .mock(
+                Mock::endpoint(&Endpoint::LookupTweetsCountRecent, Method::GET).respond_with(
+                    MockResponse::counts(vec![
+                        tweet_count("2022-01-01T00:00:00.000Z", "2022-01-01T01:00:00.000Z", 4),
+                        tweet_count("2022-01-01T01:00:00.000Z", "2022-01-01T02:00:00.000Z", 6),
+                    ]),
+                ),
+            )
+            .mock(
+                Mock::endpoint(&Endpoint::StreamTweets, Method::GET).respond_with(
+                    MockResponse::stream(vec![
+                        json!({ "data": tweet("1", "one") }),
+                        json!({ "data": tweet("2", "two") }),
+                    ]),
+                ),
+            );
+        let client = Client::new();
+        let request = |endpoint| {
+            TwitterRequest::builder()
+                .set_endpoint(endpoint)
+                .set_method(Method::GET)
+                .add_bearer_token("token")
+                .set_base_url(server.url())
+        };
+        let counts = request(Endpoint::LookupTweetsCountRecent)
+            .add_and_filter(Filter::From(String::from("rustlang"), true.into()))
+            .build()
+            .unwrap()
+            .send_typed::<Vec<TweetCount>>(&client)
+            .await
+            .unwrap();
+        assert_eq!(counts.meta.unwrap().total_tweet_count, Some(10));
+        let lines: Vec<Value> = request(Endpoint::StreamTweets)
+            .build()
+            .unwrap()
+            .send_streaming::<Value>(&client)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["data"]["text"], "two");
+        server.verify();
+    }
+
+    #[tokio::test]
+    async fn errors_test() {
+        let server = MockServer::start().await;
+        let reset = Utc::now();
+        server.mock(
+            Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+                .expect_param("query", "(from:rustlang)")
+                .respond_with(MockResponse::rate_limited(450, reset)),
+        );
+        let client = Client::new();
+        match search(&server, "rustlang")
+            .send_typed::<Vec<Tweet>>(&client)
+            .await
+        {
+            Err(TwitterError::RateLimitError(rate_limit)) => {
+                assert_eq!(rate_limit.limit, 450);
+                assert_eq!(rate_limit.reset.timestamp(), reset.timestamp());
+            }
+            other => panic!("Expected a rate limit error, got {:?}", other.map(|_| ())),
+        }
+        server.verify();
+
+        // A request without the expected query is answered with an error and fails verify
+        match search(&server, "someone_else")
+            .send_typed::<Vec<Tweet>>(&client)
+            .await
+        {
+            Err(TwitterError::ApiError(status, _)) => assert_eq!(status, 400),
+            other => panic!("Expected an API error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(
+            server.failures(),
+            vec![String::from(
+                "GET /2/tweets/search/recent?query=%28from%3Asomeone_else%29 did not match \
+                 any mock: query should be Some(\"(from:rustlang)\") but was \
+                 Some(\"(from:someone_else)\")"
+            )]
+        );
+        let verify = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| server.verify()));
+        assert!(verify.is_err());
+    }
+}
@@ -0,0 +1,283 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::{Range, RangeInclusive};
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::tlds::{COUNTRY_TLDS, GENERIC_TLDS};
+
+/// The most weighted characters a tweet can have
+pub const MAX_WEIGHTED_LENGTH: usize = 280;
+
+// The twitter-text v3 configuration. Weights are scaled by `SCALE` so that
+// characters can count as fractions of a character.
+const SCALE: usize = 100;
+const DEFAULT_WEIGHT: usize = 200;
+const TRANSFORMED_URL_LENGTH: usize = 23;
+const RANGES: [(RangeInclusive<u32>, usize); 4] = [
+    (0..=4351, 100),
+    (8192..=8205, 100),
+    (8208..=8223, 100),
+    (8242..=8247, 100),
+];
+/// Characters that make a tweet invalid no matter its length
+const INVALID_CHARS: [char; 3] = ['\u{FFFE}', '\u{FEFF}', '\u{FFFF}'];
+
+/// Country code top level domains that make a word without a protocol a link
+/// even without a path or subdomain, e.g. t.co
+const SPECIAL_COUNTRY_TLDS: [&str; 2] = ["co", "tv"];
+
+/// Counts the text the way twitter does with the twitter-text v3 configuration.
+/// The text is NFC normalized, links count as 23 characters, emoji sequences
+/// count as 2 and every other character counts as 1 or 2 depending on which
+/// unicode range it is in.
+pub fn weighted_length(text: &str) -> usize {
+    let text: String = text.nfc().collect();
+    let mut weight = 0;
+    let mut position = 0;
+    for url in find_urls(&text) {
+        weight += weigh_text(&text[position..url.start]);
+        weight += TRANSFORMED_URL_LENGTH * SCALE;
+        position = url.end;
+    }
+    weight += weigh_text(&text[position..]);
+    weight / SCALE
+}
+
+/// Returns true if twitter would accept the text as the text of a tweet,
+/// i.e. it is not empty, has no invalid characters and is not too long.
+pub fn is_valid(text: &str) -> bool {
+    !text.is_empty()
+        && !text.contains(INVALID_CHARS)
+        && weighted_length(text) <= MAX_WEIGHTED_LENGTH
+}
+
+fn weigh_text(text: &str) -> usize {
+    text.graphemes(true)
+        .map(|grapheme| {
+            if is_emoji_sequence(grapheme) {
+                DEFAULT_WEIGHT
+            } else {
+                grapheme.chars().map(char_weight).sum()
+            }
+        })
+        .sum()
+}
+
+fn char_weight(c: char) -> usize {
+    RANGES
+        .iter()
+        .find(|(range, _)| range.contains(&(c as u32)))
+        .map(|(_, weight)| *weight)
+        .unwrap_or(DEFAULT_WEIGHT)
+}
+
+/// An emoji made up of several code points, like a flag, a keycap, an emoji with a
+/// skin tone or a zero width joined family. Single code point emoji already get
+/// the default weight from their range.
+fn is_emoji_sequence(grapheme: &str) -> bool {
+    let mut chars = grapheme.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return false,
+    };
+    if chars.next().is_none() {
+        return false;
+    }
+    is_pictographic(first)
+        || grapheme.chars().any(is_regional_indicator)
+        || ("0123456789#*".contains(first) && grapheme.contains('\u{20E3}'))
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32,
+        0xA9 | 0xAE | 0x203C | 0x2049 | 0x2122 | 0x2139
+        | 0x2194..=0x21AA | 0x231A..=0x23FF | 0x24C2 | 0x25AA..=0x25FE
+        | 0x2600..=0x27BF | 0x2934 | 0x2935 | 0x2B05..=0x2B55
+        | 0x3030 | 0x303D | 0x3297 | 0x3299 | 0x1F000..=0x1FAFF)
+}
+
+/// Finds the byte ranges of the links in the text. Links either start with a
+/// http(s) protocol or are a domain ending with a known top level domain.
+fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let mut urls = Vec::new();
+    for (start, word) in words(text) {
+        let lower = word.to_ascii_lowercase();
+        let url = match ["https://", "http://"]
+            .iter()
+            .filter_map(|scheme| lower.find(scheme).map(|i| (i, scheme.len())))
+            .min()
+        {
+            Some((i, scheme_len)) => {
+                let preceded_ok = !matches!(
+                    word[..i].chars().last(),
+                    Some(c) if c.is_alphanumeric() || "@$#".contains(c)
+                );
+                let url = trim_url_end(&word[i..]);
+                if preceded_ok && is_valid_domain(host(&url[scheme_len..]), true, true) {
+                    Some(start + i..start + i + url.len())
+                } else {
+                    None
+                }
+            }
+            None => {
+                let i = word
+                    .find(|c: char| c.is_alphanumeric() || "-_./@#$".contains(c))
+                    .unwrap_or(word.len());
+                let preceded_ok = word[i..].starts_with(|c: char| c.is_alphanumeric());
+                let url = trim_url_end(&word[i..]);
+                let domain = host(url);
+                let has_path = url[domain.len()..].starts_with('/');
+                if preceded_ok && is_valid_domain(domain, false, has_path) {
+                    Some(start + i..start + i + url.len())
+                } else {
+                    None
+                }
+            }
+        };
+        urls.extend(url);
+    }
+    urls
+}
+
+/// Splits the text into runs of non whitespace, with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// Drops punctuation at the end of a link that most likely belongs to the
+/// sentence, keeping closing parentheses that close one in the link.
+fn trim_url_end(url: &str) -> &str {
+    let mut url = url;
+    while let Some(last) = url.chars().last() {
+        let trailing = match last {
+            ')' => url.matches('(').count() < url.matches(')').count(),
+            _ => ".,:;!?'\"]}".contains(last),
+        };
+        if !trailing {
+            break;
+        }
+        url = &url[..url.len() - last.len_utf8()];
+    }
+    url
+}
+
+fn host(url: &str) -> &str {
+    let end = url.find(['/', '?', '#', ':']).unwrap_or(url.len());
+    &url[..end]
+}
+
+/// Checks that the domain has at least two labels of ASCII letters, digits and dashes
+/// before its top level domain. With a protocol any alphabetic top level domain will do,
+/// without one it must be known, and a country code also needs a path or a subdomain
+/// unless it is one of `SPECIAL_COUNTRY_TLDS`.
+fn is_valid_domain(domain: &str, has_protocol: bool, has_path: bool) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    let (tld, names) = match labels.split_last() {
+        Some((tld, names)) if !names.is_empty() => (tld.to_lowercase(), names),
+        _ => return false,
+    };
+    if tld.is_empty()
+        || names.iter().any(|label| {
+            label.is_empty()
+                || label.starts_with('-')
+                || !label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+    {
+        return false;
+    }
+    if has_protocol && tld.chars().count() >= 2 && tld.chars().all(char::is_alphabetic) {
+        return true;
+    }
+    GENERIC_TLDS.contains(&tld.as_str())
+        || (COUNTRY_TLDS.contains(&tld.as_str())
+            && (has_protocol
+                || has_path
+                || names.len() > 1
+                || SPECIAL_COUNTRY_TLDS.contains(&tld.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{is_valid, weighted_length};
+
+    #[derive(Deserialize)]
+    struct Conformance {
+        tests: Tests,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Tests {
+        weighted_tweets_with_discounted_emoji_counter_test: Vec<Case>,
+        unicode_directional_marker_counter_test: Vec<Case>,
+    }
+
+    #[derive(Deserialize)]
+    struct Case {
+        description: String,
+        text: String,
+        expected: Expected,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Expected {
+        weighted_length: usize,
+        valid: bool,
+    }
+
+    #[test]
+    fn conformance_test() {
+        let conformance: Conformance =
+            serde_yaml::from_str(include_str!("../../test_data/twitter_text/validate.yml"))
+                .unwrap();
+        let tests = conformance.tests;
+        assert!(!tests
+            .weighted_tweets_with_discounted_emoji_counter_test
+            .is_empty());
+        let cases = tests
+            .weighted_tweets_with_discounted_emoji_counter_test
+            .into_iter()
+            .chain(tests.unicode_directional_marker_counter_test);
+        for case in cases {
+            assert_eq!(
+                weighted_length(&case.text),
+                case.expected.weighted_length,
+                "{}",
+                case.description
+            );
+            assert_eq!(
+                is_valid(&case.text),
+                case.expected.valid,
+                "{}",
+                case.description
+            );
+        }
+    }
+}
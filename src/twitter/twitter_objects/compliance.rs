@@ -0,0 +1,98 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::twitter::query_filters::ids::Id;
+
+/// A batch compliance job. The ids to check are uploaded to `upload_url`, and once the
+/// job is complete the results can be downloaded from `download_url`.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ComplianceJob {
+    pub id: Id,
+    #[serde(rename = "type")]
+    pub job_type: ComplianceJobType,
+    pub name: Option<String>,
+    pub upload_url: String,
+    pub upload_expires_at: DateTime<Utc>,
+    pub download_url: String,
+    pub download_expires_at: DateTime<Utc>,
+    pub status: ComplianceJobStatus,
+    pub created_at: DateTime<Utc>,
+    pub resumable: Option<bool>,
+}
+
+/// Whether a compliance job checks tweet ids or user ids
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ComplianceJobType {
+    Tweets,
+    Users,
+}
+
+impl Display for ComplianceJobType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ComplianceJobType::Tweets => "tweets",
+            ComplianceJobType::Users => "users",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceJobStatus {
+    Created,
+    InProgress,
+    Complete,
+    Failed,
+    Expired,
+}
+
+impl Display for ComplianceJobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ComplianceJobStatus::Created => "created",
+            ComplianceJobStatus::InProgress => "in_progress",
+            ComplianceJobStatus::Complete => "complete",
+            ComplianceJobStatus::Failed => "failed",
+            ComplianceJobStatus::Expired => "expired",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// One line of the results of a compliance job, telling what happened to one of the
+/// uploaded ids. Ids that need no action are left out of the results.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ComplianceResult {
+    pub id: Id,
+    /// What should be done with the stored tweet or user, e.g. `delete` or `scrub_geo`
+    pub action: String,
+    pub created_at: DateTime<Utc>,
+    pub redacted_at: Option<DateTime<Utc>>,
+    /// Why, e.g. `deleted`, `suspended`, `protected` or `deactivated`
+    pub reason: Option<String>,
+}
@@ -0,0 +1,100 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::tweet::ReferencedTweet;
+use crate::twitter::query_filters::ids::Id;
+
+/// A direct message event as returned by the v2 API. Only `id` and `event_type`
+/// are always present, the rest depend on the `dm_event.fields` requested.
+/// `text` is present on `MessageCreate` events.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct DmEvent {
+    pub id: Id,
+    pub event_type: DmEventType,
+    pub text: Option<String>,
+    pub sender_id: Option<Id>,
+    pub participant_ids: Option<Vec<Id>>,
+    pub dm_conversation_id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub referenced_tweets: Option<Vec<ReferencedTweet>>,
+    pub attachments: Option<serde_json::Value>,
+}
+
+/// What happened in a direct message conversation
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DmEventType {
+    MessageCreate,
+    ParticipantsJoin,
+    ParticipantsLeave,
+}
+
+impl Display for DmEventType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DmEventType::MessageCreate => "MessageCreate",
+            DmEventType::ParticipantsJoin => "ParticipantsJoin",
+            DmEventType::ParticipantsLeave => "ParticipantsLeave",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+/// The message twitter responds with after sending a direct message
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct SentDm {
+    pub dm_conversation_id: String,
+    pub dm_event_id: Id,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DmEventField {
+    Id,
+    Text,
+    EventType,
+    CreatedAt,
+    DmConversationId,
+    SenderId,
+    ParticipantIds,
+    ReferencedTweets,
+    Attachments,
+}
+
+impl Display for DmEventField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DmEventField::Id => "id",
+            DmEventField::Text => "text",
+            DmEventField::EventType => "event_type",
+            DmEventField::CreatedAt => "created_at",
+            DmEventField::DmConversationId => "dm_conversation_id",
+            DmEventField::SenderId => "sender_id",
+            DmEventField::ParticipantIds => "participant_ids",
+            DmEventField::ReferencedTweets => "referenced_tweets",
+            DmEventField::Attachments => "attachments",
+        };
+
+        write!(f, "{}", s)
+    }
+}
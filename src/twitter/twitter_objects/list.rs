@@ -0,0 +1,95 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::twitter::query_filters::ids::Id;
+
+/// A twitter list as returned by the v2 API. Only `id` and `name` are always
+/// present, the rest depend on the `list.fields` requested.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct List {
+    pub id: Id,
+    pub name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub private: Option<bool>,
+    pub follower_count: Option<u64>,
+    pub member_count: Option<u64>,
+    pub owner_id: Option<Id>,
+    pub description: Option<String>,
+}
+
+/// The list twitter responds with after creating a list
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct CreatedList {
+    pub id: Id,
+    pub name: String,
+}
+
+/// The result of updating a list
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ListUpdatedStatus {
+    pub updated: bool,
+}
+
+/// The result of deleting a list
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ListDeletedStatus {
+    pub deleted: bool,
+}
+
+/// The result of adding a member to or removing a member from a list
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ListMemberStatus {
+    pub is_member: bool,
+}
+
+/// The result of pinning or unpinning a list
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ListPinnedStatus {
+    pub pinned: bool,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ListField {
+    CreatedAt,
+    FollowerCount,
+    MemberCount,
+    Private,
+    Description,
+    OwnerId,
+}
+
+impl Display for ListField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ListField::CreatedAt => "created_at",
+            ListField::FollowerCount => "follower_count",
+            ListField::MemberCount => "member_count",
+            ListField::Private => "private",
+            ListField::Description => "description",
+            ListField::OwnerId => "owner_id",
+        };
+
+        write!(f, "{}", s)
+    }
+}
@@ -16,5 +16,11 @@
   You should have received a copy of the GNU General Public License
   along with this program.  If not, see <http://www.gnu.org/licenses/>.
 */
+pub mod compliance;
+pub mod dm;
+pub mod list;
+pub mod response;
+pub mod space;
+pub mod stream_rule;
 pub mod tweet;
 pub mod user;
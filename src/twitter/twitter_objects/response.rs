@@ -0,0 +1,158 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    compliance::ComplianceJob, dm::DmEvent, list::List, space::Space, tweet::Tweet, user::User,
+};
+use crate::errors::TwitterError;
+
+/// The envelope every v2 API response comes in. `T` is the type of the `data`
+/// field, a single object for endpoints that return one item and a `Vec` otherwise.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct TwitterResponse<T> {
+    pub data: Option<T>,
+    pub includes: Option<Includes>,
+    #[serde(default)]
+    pub errors: Vec<ResponseError>,
+    pub meta: Option<Meta>,
+}
+
+impl<T> TwitterResponse<T> {
+    /// Takes the data out of the response, or returns the errors twitter
+    /// sent instead if the response had no data
+    pub fn into_data(self) -> Result<T, TwitterError> {
+        match self.data {
+            Some(data) => Ok(data),
+            None => Err(TwitterError::ResponseErrors(self.errors)),
+        }
+    }
+}
+
+/// Objects referenced by the `data` objects, present when expansions are requested
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct Includes {
+    #[serde(default)]
+    pub users: Vec<User>,
+    #[serde(default)]
+    pub tweets: Vec<Tweet>,
+    /// The other kinds of objects, such as `media`, `places` and `polls`, as twitter
+    /// sends them
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Includes {
+    /// Adds the objects of `other` that are not included already
+    pub fn merge(&mut self, other: Includes) {
+        for user in other.users {
+            if !self.users.iter().any(|u| u.id == user.id) {
+                self.users.push(user);
+            }
+        }
+        for tweet in other.tweets {
+            if !self.tweets.iter().any(|t| t.id == tweet.id) {
+                self.tweets.push(tweet);
+            }
+        }
+        for (kind, objects) in other.other {
+            let objects = match objects {
+                serde_json::Value::Array(objects) => objects,
+                object => vec![object],
+            };
+            let included = self
+                .other
+                .entry(kind)
+                .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+            if let serde_json::Value::Array(included) = included {
+                for object in objects {
+                    if !included.contains(&object) {
+                        included.push(object);
+                    }
+                }
+            }
+        }
+    }
+
+    /// True if nothing is included
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+            && self.tweets.is_empty()
+            && self.other.values().all(|objects| match objects {
+                serde_json::Value::Array(objects) => objects.is_empty(),
+                _ => false,
+            })
+    }
+}
+
+/// Information about the result set, used for pagination
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct Meta {
+    pub result_count: Option<u64>,
+    pub newest_id: Option<String>,
+    pub oldest_id: Option<String>,
+    pub next_token: Option<String>,
+    pub previous_token: Option<String>,
+    /// The sum of the counts returned by the counts endpoints
+    pub total_tweet_count: Option<u64>,
+}
+
+/// A partial error, returned next to `data` when parts of a request could not be
+/// fulfilled, for example when one of the looked up users does not exist.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ResponseError {
+    pub title: String,
+    pub detail: Option<String>,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+    pub value: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub parameter: Option<String>,
+}
+
+const NOT_FOUND_PROBLEM: &str = "https://api.twitter.com/2/problems/resource-not-found";
+const NOT_AUTHORIZED_PROBLEM: &str =
+    "https://api.twitter.com/2/problems/not-authorized-for-resource";
+
+impl ResponseError {
+    /// True if the error is about an object that does not exist, e.g. a deleted tweet
+    pub fn is_not_found(&self) -> bool {
+        self.error_type.as_deref() == Some(NOT_FOUND_PROBLEM)
+    }
+
+    /// True if the error is about an object the authentication may not see,
+    /// e.g. a tweet of a protected user
+    pub fn is_not_authorized(&self) -> bool {
+        self.error_type.as_deref() == Some(NOT_AUTHORIZED_PROBLEM)
+    }
+}
+
+pub type TweetResponse = TwitterResponse<Tweet>;
+pub type TweetsResponse = TwitterResponse<Vec<Tweet>>;
+pub type UserResponse = TwitterResponse<User>;
+pub type UsersResponse = TwitterResponse<Vec<User>>;
+pub type ListResponse = TwitterResponse<List>;
+pub type ListsResponse = TwitterResponse<Vec<List>>;
+pub type DmEventsResponse = TwitterResponse<Vec<DmEvent>>;
+pub type SpaceResponse = TwitterResponse<Space>;
+pub type SpacesResponse = TwitterResponse<Vec<Space>>;
+pub type ComplianceJobResponse = TwitterResponse<ComplianceJob>;
+pub type ComplianceJobsResponse = TwitterResponse<Vec<ComplianceJob>>;
@@ -0,0 +1,104 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::twitter::query_filters::ids::Id;
+
+/// A space as returned by the v2 API. Only `id` and `state` are always present,
+/// the rest depend on the `space.fields` requested. Unlike tweet and user ids,
+/// space ids are not numeric.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct Space {
+    pub id: String,
+    pub state: SpaceState,
+    pub title: Option<String>,
+    pub creator_id: Option<Id>,
+    pub host_ids: Option<Vec<Id>>,
+    pub speaker_ids: Option<Vec<Id>>,
+    pub invited_user_ids: Option<Vec<Id>>,
+    pub topic_ids: Option<Vec<String>>,
+    pub lang: Option<String>,
+    pub is_ticketed: Option<bool>,
+    pub participant_count: Option<u64>,
+    pub subscriber_count: Option<u64>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum SpaceState {
+    Live,
+    Scheduled,
+    Ended,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SpaceField {
+    Id,
+    State,
+    Title,
+    CreatorId,
+    HostIds,
+    SpeakerIds,
+    InvitedUserIds,
+    TopicIds,
+    Lang,
+    IsTicketed,
+    ParticipantCount,
+    SubscriberCount,
+    CreatedAt,
+    ScheduledStart,
+    StartedAt,
+    EndedAt,
+    UpdatedAt,
+}
+
+impl Display for SpaceField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SpaceField::Id => "id",
+            SpaceField::State => "state",
+            SpaceField::Title => "title",
+            SpaceField::CreatorId => "creator_id",
+            SpaceField::HostIds => "host_ids",
+            SpaceField::SpeakerIds => "speaker_ids",
+            SpaceField::InvitedUserIds => "invited_user_ids",
+            SpaceField::TopicIds => "topic_ids",
+            SpaceField::Lang => "lang",
+            SpaceField::IsTicketed => "is_ticketed",
+            SpaceField::ParticipantCount => "participant_count",
+            SpaceField::SubscriberCount => "subscriber_count",
+            SpaceField::CreatedAt => "created_at",
+            SpaceField::ScheduledStart => "scheduled_start",
+            SpaceField::StartedAt => "started_at",
+            SpaceField::EndedAt => "ended_at",
+            SpaceField::UpdatedAt => "updated_at",
+        };
+
+        write!(f, "{}", s)
+    }
+}
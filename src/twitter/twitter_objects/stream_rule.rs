@@ -0,0 +1,32 @@
+/*
+  The GPLv3 License (GPLv3)
+
+  Copyright (c) 2022 Jacob Skoog
+
+  This program is free software: you can redistribute it and/or modify
+  it under the terms of the GNU General Public License as published by
+  the Free Software Foundation, either version 3 of the License, or
+  (at your option) any later version.
+
+  This program is distributed in the hope that it will be useful,
+  but WITHOUT ANY WARRANTY; without even the implied warranty of
+  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+  GNU General Public License for more details.
+
+  You should have received a copy of the GNU General Public License
+  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::twitter::query_filters::ids::Id;
+
+/// A rule of the filtered stream. Tweets matching `value` are sent through the stream,
+/// with the ids and tags of the rules they matched.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct StreamRule {
+    pub id: Id,
+    /// The query of the rule, written like a search query
+    pub value: String,
+    pub tag: Option<String>,
+}
@@ -19,7 +19,95 @@
 
 use std::fmt::{Display, Formatter};
 
-#[derive(PartialEq, Debug)]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TwitterBuilderError;
+use crate::twitter::query_filters::ids::Id;
+
+/// A tweet as returned by the v2 API. Only `id` and `text` are always present,
+/// the rest depend on the `tweet.fields` requested.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Tweet {
+    pub id: Id,
+    pub text: String,
+    pub author_id: Option<Id>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub conversation_id: Option<Id>,
+    pub in_reply_to_user_id: Option<Id>,
+    pub lang: Option<String>,
+    pub source: Option<String>,
+    pub possibly_sensitive: Option<bool>,
+    pub reply_settings: Option<String>,
+    pub referenced_tweets: Option<Vec<ReferencedTweet>>,
+    pub public_metrics: Option<TweetPublicMetrics>,
+    pub edit_history_tweet_ids: Option<Vec<Id>>,
+    pub attachments: Option<serde_json::Value>,
+    pub entities: Option<serde_json::Value>,
+    pub geo: Option<serde_json::Value>,
+    pub context_annotations: Option<serde_json::Value>,
+    pub withheld: Option<serde_json::Value>,
+}
+
+/// A tweet that is replied to, quoted or retweeted by another tweet
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct ReferencedTweet {
+    /// One of `retweeted`, `quoted` or `replied_to`
+    #[serde(rename = "type")]
+    pub reference_type: String,
+    pub id: Id,
+}
+
+/// The tweet twitter responds with after creating a tweet
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct CreatedTweet {
+    pub id: Id,
+    pub text: String,
+}
+
+/// The result of bookmarking a tweet or removing a bookmark
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct BookmarkedStatus {
+    pub bookmarked: bool,
+}
+
+/// The result of hiding or unhiding a reply
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct HiddenStatus {
+    pub hidden: bool,
+}
+
+/// The result of liking or unliking a tweet
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct LikedStatus {
+    pub liked: bool,
+}
+
+/// The result of retweeting or undoing a retweet
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct RetweetedStatus {
+    pub retweeted: bool,
+}
+
+/// The number of tweets matching a query in a time period, as returned by the counts
+/// endpoints
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct TweetCount {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tweet_count: u64,
+}
+
+/// Engagement counts of a tweet
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct TweetPublicMetrics {
+    pub retweet_count: u64,
+    pub reply_count: u64,
+    pub like_count: u64,
+    pub quote_count: u64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum TweetField {
     Id,
     CreatedAt,
@@ -38,6 +126,7 @@ pub enum TweetField {
     ContextAnnotations,
     ConversationId,
     ReplySettings,
+    EditHistoryTweetIds,
 }
 
 impl Display for TweetField {
@@ -52,7 +141,7 @@ impl Display for TweetField {
             TweetField::Attachments => "attachments",
             TweetField::Withheld => "withheld",
             TweetField::Geo => "geo",
-            TweetField::Entities => "twitter_objects",
+            TweetField::Entities => "entities",
             TweetField::PublicMetrics => "public_metrics",
             TweetField::PossiblySensitive => "possibly_sensitive",
             TweetField::Source => "source",
@@ -60,8 +149,50 @@ impl Display for TweetField {
             TweetField::ContextAnnotations => "context_annotations",
             TweetField::ConversationId => "conversation_id",
             TweetField::ReplySettings => "reply_settings",
+            TweetField::EditHistoryTweetIds => "edit_history_tweet_ids",
         };
 
         write!(f, "{}", s)
     }
 }
+
+impl TweetField {
+    /// Every tweet field
+    pub const ALL: [TweetField; 18] = [
+        TweetField::Id,
+        TweetField::CreatedAt,
+        TweetField::Text,
+        TweetField::AuthorId,
+        TweetField::InReplyToUserId,
+        TweetField::ReferencedTweets,
+        TweetField::Attachments,
+        TweetField::Withheld,
+        TweetField::Geo,
+        TweetField::Entities,
+        TweetField::PublicMetrics,
+        TweetField::PossiblySensitive,
+        TweetField::Source,
+        TweetField::Lang,
+        TweetField::ContextAnnotations,
+        TweetField::ConversationId,
+        TweetField::ReplySettings,
+        TweetField::EditHistoryTweetIds,
+    ];
+}
+
+impl TryFrom<&str> for TweetField {
+    type Error = TwitterBuilderError;
+
+    /// Parses the name twitter uses for the tweet field, e.g. "created_at"
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        TweetField::ALL
+            .into_iter()
+            .find(|v| v.to_string() == value)
+            .ok_or_else(|| {
+                TwitterBuilderError::BadQueryError(format!(
+                    "\"{}\" is not a known tweet field",
+                    value
+                ))
+            })
+    }
+}
@@ -19,7 +19,62 @@
 
 use std::fmt::{Display, Formatter};
 
-#[derive(PartialEq, Debug)]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TwitterBuilderError;
+use crate::twitter::query_filters::ids::Id;
+
+/// A twitter user as returned by the v2 API. Only `id`, `name` and `username`
+/// are always present, the rest depend on the `user.fields` requested.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct User {
+    pub id: Id,
+    pub name: String,
+    pub username: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub protected: Option<bool>,
+    pub verified: Option<bool>,
+    pub profile_image_url: Option<String>,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub pinned_tweet_id: Option<Id>,
+    pub public_metrics: Option<UserPublicMetrics>,
+    pub entities: Option<serde_json::Value>,
+    pub withheld: Option<serde_json::Value>,
+}
+
+/// The result of following or unfollowing a user. `pending_follow` is true
+/// when the followed user is protected and has to accept the follow request.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct FollowingStatus {
+    pub following: bool,
+    pub pending_follow: Option<bool>,
+}
+
+/// The result of blocking or unblocking a user
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct BlockingStatus {
+    pub blocking: bool,
+}
+
+/// The result of muting or unmuting a user
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct MutingStatus {
+    pub muting: bool,
+}
+
+/// Follower and tweet counts of a user
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct UserPublicMetrics {
+    pub followers_count: u64,
+    pub following_count: u64,
+    pub tweet_count: u64,
+    pub listed_count: u64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum UserField {
     Id,
     CreatedAt,
@@ -51,7 +106,7 @@ impl Display for UserField {
             UserField::Location => "location",
             UserField::Url => "url",
             UserField::Description => "description",
-            UserField::Entities => "twitter_objects",
+            UserField::Entities => "entities",
             UserField::PinnedTweetId => "pinned_tweet_id",
             UserField::PublicMetrics => "public_metrics",
         };
@@ -59,3 +114,40 @@ impl Display for UserField {
         write!(f, "{}", s)
     }
 }
+
+impl UserField {
+    /// Every user field
+    pub const ALL: [UserField; 14] = [
+        UserField::Id,
+        UserField::CreatedAt,
+        UserField::Name,
+        UserField::Username,
+        UserField::Protected,
+        UserField::Verified,
+        UserField::Withheld,
+        UserField::ProfileImageUrl,
+        UserField::Location,
+        UserField::Url,
+        UserField::Description,
+        UserField::Entities,
+        UserField::PinnedTweetId,
+        UserField::PublicMetrics,
+    ];
+}
+
+impl TryFrom<&str> for UserField {
+    type Error = TwitterBuilderError;
+
+    /// Parses the name twitter uses for the user field, e.g. "created_at"
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        UserField::ALL
+            .into_iter()
+            .find(|v| v.to_string() == value)
+            .ok_or_else(|| {
+                TwitterBuilderError::BadQueryError(format!(
+                    "\"{}\" is not a known user field",
+                    value
+                ))
+            })
+    }
+}
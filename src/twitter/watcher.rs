@@ -0,0 +1,318 @@
+// The GPLv3 License (GPLv3)
+//
+// Copyright (c) 2022 Jacob Skoog
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use futures::stream::{self, Stream};
+use http::Method;
+use reqwest::Client;
+
+use crate::errors::TwitterError;
+use crate::twitter::endpoints::{AuthenticationData, Endpoint, TWITTER_URL};
+use crate::twitter::query_filters::group::GroupList;
+use crate::twitter::query_filters::ids::Id;
+use crate::twitter::query_params::main_options::MainOptions;
+use crate::twitter::request::TwitterRequest;
+use crate::twitter::twitter_objects::tweet::Tweet;
+
+/// Where a `SearchWatcher` keeps the id of the last tweet it handed out, so a new
+/// watcher can continue after it
+pub trait CursorStore {
+    /// Gets the stored id, None if nothing has been stored yet
+    fn load(&self) -> Result<Option<Id>, TwitterError>;
+    /// Stores the id, replacing the one stored before
+    fn save(&mut self, since_id: Id) -> Result<(), TwitterError>;
+}
+
+/// Keeps the cursor in memory, so it is lost when the program exits
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    since_id: Option<Id>,
+}
+
+impl MemoryStore {
+    pub fn new(since_id: Option<Id>) -> MemoryStore {
+        MemoryStore { since_id }
+    }
+}
+
+impl CursorStore for MemoryStore {
+    fn load(&self) -> Result<Option<Id>, TwitterError> {
+        Ok(self.since_id)
+    }
+
+    fn save(&mut self, since_id: Id) -> Result<(), TwitterError> {
+        self.since_id = Some(since_id);
+        Ok(())
+    }
+}
+
+/// Keeps the cursor in a file holding only the id
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> FileStore {
+        FileStore { path: path.into() }
+    }
+}
+
+impl CursorStore for FileStore {
+    fn load(&self) -> Result<Option<Id>, TwitterError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(id) => Id::try_from(id.trim())
+                .map(Some)
+                .map_err(|_| TwitterError::CursorError(self.path.clone(), String::from(id.trim()))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(TwitterError::IoError(e)),
+        }
+    }
+
+    /// Writes through a temporary file so an interrupted write can not leave a
+    /// broken cursor behind
+    fn save(&mut self, since_id: Id) -> Result<(), TwitterError> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, since_id.to_string()).map_err(TwitterError::IoError)?;
+        std::fs::rename(&temporary, &self.path).map_err(TwitterError::IoError)
+    }
+}
+
+/// Polls the recent search for tweets newer than the newest tweet seen so far and
+/// hands them out as a stream, oldest first. Every poll pages through all the new
+/// tweets. When there is no cursor in the store yet, the first poll gets every
+/// tweet the recent search still has, which goes 7 days back.
+///
+/// The cursor is saved when the tweet after it is asked for, so a watcher started
+/// after a restart continues right after the last tweet that was handed out.
+pub struct SearchWatcher<S: CursorStore> {
+    query: GroupList,
+    interval: Duration,
+    store: S,
+    options: Vec<MainOptions>,
+    base_url: String,
+}
+
+impl<S: CursorStore> SearchWatcher<S> {
+    /// Creates a watcher that polls for tweets matching `query` every `interval`
+    pub fn new(query: GroupList, interval: Duration, store: S) -> SearchWatcher<S> {
+        SearchWatcher {
+            query,
+            interval,
+            store,
+            options: Vec::new(),
+            base_url: String::from(TWITTER_URL),
+        }
+    }
+
+    /// Adds a field or expansion option to the search requests
+    pub fn add_option(mut self, option: MainOptions) -> Self {
+        self.options.push(option);
+        self
+    }
+
+    /// Sets the base url of the API instead of `TWITTER_URL`
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = String::from(base_url.trim_end_matches('/'));
+        self
+    }
+
+    /// Starts watching. The first poll is made right away. Errors are handed out in the
+    /// stream, after which the watcher polls again once the interval has passed.
+    pub fn watch(
+        self,
+        client: Client,
+        auth: AuthenticationData,
+    ) -> impl Stream<Item = Result<Tweet, TwitterError>> {
+        let state = WatchState {
+            watcher: self,
+            client,
+            auth,
+            since_id: None,
+            loaded: false,
+            pending: VecDeque::new(),
+            handed_out: None,
+            polled: false,
+        };
+        stream::unfold(state, |mut state| async move {
+            let item = state.next().await;
+            Some((item, state))
+        })
+    }
+
+    /// Gets every tweet newer than `since_id`, oldest first
+    async fn poll(
+        &self,
+        client: &Client,
+        auth: &AuthenticationData,
+        since_id: Option<Id>,
+    ) -> Result<Vec<Tweet>, TwitterError> {
+        let mut builder = TwitterRequest::builder()
+            .set_endpoint(Endpoint::SearchTweetsRecent)
+            .set_method(Method::GET)
+            .set_authentication(auth.clone())
+            .set_base_url(&self.base_url)
+            .add_group_list(self.query.clone())
+            .add_option(MainOptions::MaxResults(100.into()));
+        if let Some(since_id) = since_id {
+            builder = builder.add_option(MainOptions::SinceId(since_id));
+        }
+        for option in &self.options {
+            builder = builder.add_option(option.clone());
+        }
+        let mut tweets = Vec::new();
+        let mut request = Some(builder.build()?);
+        while let Some(page) = request {
+            let response = page
+                .clone()
+                .send_typed_waiting::<Vec<Tweet>>(client)
+                .await?;
+            tweets.extend(response.data.unwrap_or_default());
+            request = match response.meta {
+                Some(meta) => page.next_page(&meta)?,
+                None => None,
+            };
+        }
+        tweets.sort_by_key(|tweet| tweet.id);
+        tweets.dedup_by_key(|tweet| tweet.id);
+        Ok(tweets)
+    }
+}
+
+struct WatchState<S: CursorStore> {
+    watcher: SearchWatcher<S>,
+    client: Client,
+    auth: AuthenticationData,
+    since_id: Option<Id>,
+    /// Whether `since_id` has been loaded from the store
+    loaded: bool,
+    /// Tweets of the last poll that have not been handed out yet
+    pending: VecDeque<Tweet>,
+    /// The last tweet handed out, saved as the cursor when the next item is asked for
+    handed_out: Option<Id>,
+    polled: bool,
+}
+
+impl<S: CursorStore> WatchState<S> {
+    async fn next(&mut self) -> Result<Tweet, TwitterError> {
+        if !self.loaded {
+            self.since_id = self.watcher.store.load()?;
+            self.loaded = true;
+        }
+        if let Some(id) = self.handed_out.take() {
+            self.watcher.store.save(id)?;
+            self.since_id = Some(id);
+        }
+        loop {
+            if let Some(tweet) = self.pending.pop_front() {
+                self.handed_out = Some(tweet.id);
+                return Ok(tweet);
+            }
+            if self.polled {
+                tokio::time::sleep(self.watcher.interval).await;
+            }
+            self.polled = true;
+            let tweets = self
+                .watcher
+                .poll(&self.client, &self.auth, self.since_id)
+                .await?;
+            self.pending.extend(tweets);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use serde_json::json;
+
+    use super::*;
+    use crate::twitter::query_filters::filter::{Exact, Filter, Is};
+    use crate::twitter::testing::{Mock, MockResponse, MockServer};
+
+    fn tweets(ids: &[u64]) -> Vec<serde_json::Value> {
+        ids.iter()
+            .map(|id| json!({ "id": id.to_string(), "text": "watched" }))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn watcher_test() {
+        let server = MockServer::start().await;
+        let url = server.url();
+        // Two pages in the first poll, then one poll each after tweet 5 and 6
+        server.mock(
+            Mock::endpoint(&Endpoint::SearchTweetsRecent, Method::GET)
+                .respond_with_fn(|request| {
+                    let since_id = request.query_param("since_id");
+                    let body = if since_id.as_deref() == Some("6") {
+                        json!({ "data": tweets(&[7]), "meta": { "result_count": 1 } })
+                    } else if since_id.as_deref() == Some("5") {
+                        json!({ "data": tweets(&[7, 6]), "meta": { "result_count": 2 } })
+                    } else if request.query_param("next_token").as_deref() == Some("older") {
+                        json!({ "data": tweets(&[3]), "meta": { "result_count": 1 } })
+                    } else {
+                        json!({
+                            "data": tweets(&[5, 4]),
+                            "meta": { "result_count": 2, "next_token": "older" },
+                        })
+                    };
+                    MockResponse::json(200, &body)
+                })
+                .times(4),
+        );
+        let cursor = std::env::temp_dir().join("twitter_request_watcher_test_cursor");
+        let _ = std::fs::remove_file(&cursor);
+        let query = GroupList::new(Filter::Keyword(String::from("rust"), Exact::Not, Is::Is));
+        let auth = AuthenticationData::new_bearer_auth("token");
+
+        let watched: Vec<Id> =
+            SearchWatcher::new(query.clone(), Duration::ZERO, FileStore::new(&cursor))
+                .base_url(url)
+                .watch(Client::new(), auth.clone())
+                .take(5)
+                .map(|tweet| tweet.unwrap().id)
+                .collect()
+                .await;
+        let expected: Vec<Id> = (3..=7u64).map(Id::from).collect();
+        assert_eq!(watched, expected);
+        // Tweet 7 was handed out, but the stream was dropped before the next item was
+        // asked for, so it was not saved
+        assert_eq!(FileStore::new(&cursor).load().unwrap(), Some(Id::from(6)));
+
+        let mut restarted = SearchWatcher::new(query, Duration::ZERO, FileStore::new(&cursor))
+            .base_url(url)
+            .watch(Client::new(), auth)
+            .boxed();
+        let restarted = restarted.next().await.unwrap().unwrap();
+        assert_eq!(restarted.id, Id::from(7));
+        server.verify();
+
+        std::fs::write(&cursor, "not an id").unwrap();
+        match FileStore::new(&cursor).load() {
+            Err(TwitterError::CursorError(path, value)) => {
+                assert_eq!((path, value.as_str()), (cursor.clone(), "not an id"))
+            }
+            _ => panic!("The cursor is not an id"),
+        }
+        std::fs::remove_file(&cursor).unwrap();
+    }
+}